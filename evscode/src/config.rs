@@ -144,6 +144,20 @@ impl<T: Configurable> Configurable for Option<T> {
 		obj
 	}
 }
+impl<T: Configurable> Configurable for Vec<T> {
+	fn to_json(&self) -> serde_json::Value {
+		serde_json::Value::Array(self.iter().map(T::to_json).collect())
+	}
+
+	fn schema(default: Option<&Self>) -> serde_json::Value {
+		optobject! {
+			"type" => "array",
+			optional "default" => default.map(Self::to_json),
+			"items" => T::schema(None),
+		}
+	}
+}
+
 /// This implementation is not editable in VS Code setting UI. I am not sure why, because VS Code has builtin
 /// configuration entries that have the same manifest entry, but are editable. Naturally, the [documentation] of this
 /// behaviour does not exist.
@@ -1,6 +1,9 @@
 //! Extension API used for interacting with VS Code.
 
 pub mod console;
+pub mod debug;
+pub mod decorations;
+pub mod diagnostics;
 pub mod input_box;
 pub mod message;
 pub mod open_dialog;
@@ -13,10 +16,13 @@ pub mod terminal;
 pub mod types;
 pub mod webview;
 
+pub use debug::start_debugging;
+pub use decorations::DecorationType;
+pub use diagnostics::DiagnosticCollection;
 pub use input_box::InputBox;
 pub use message::Message;
 pub use open_dialog::OpenDialog;
-pub use open_editor::open_editor;
+pub use open_editor::{open_editor, open_untitled};
 pub use progress::Progress;
 pub use quick_pick::QuickPick;
 pub use state::State;
@@ -26,7 +32,7 @@ pub use webview::Webview;
 
 use crate::{error::ResultExt, E, R};
 use std::cell::RefCell;
-use wasm_bindgen::{closure::Closure, JsValue};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
 
 /// Save all modified files in the workspace.
 ///
@@ -83,20 +89,82 @@ pub async fn edit_paste(path: &str, text: &str, position: (usize, usize)) -> R<(
 	if suc { Ok(()) } else { Err(E::error("could not apply requested edits")) }
 }
 
+/// Make an edit action that consists of replacing the text between given positions in a given file with a given text.
+///
+/// The indices in the (row, column) tuples are 0-based.
+pub async fn edit_replace(path: &str, text: &str, start: (usize, usize), end: (usize, usize)) -> R<()> {
+	let text = text.to_owned();
+	let doc = vscode_sys::workspace::open_text_document(path).await?;
+	let edi = vscode_sys::window::show_text_document(&doc).await;
+	let suc = edi
+		.edit(&Closure::wrap(Box::new(move |edit_builder: &vscode_sys::TextEditorEdit| {
+			let start = vscode_sys::Position::new(start.0, start.1);
+			let end = vscode_sys::Position::new(end.0, end.1);
+			edit_builder.replace(&vscode_sys::Range::new(&start, &end), &text);
+		}) as Box<dyn FnMut(&vscode_sys::TextEditorEdit)>))
+		.await;
+	if suc { Ok(()) } else { Err(E::error("could not apply requested edits")) }
+}
+
 /// Get the path to workspace folder.
 /// Returns an error if no folder is opened.
 pub fn workspace_root() -> R<String> {
 	vscode_sys::workspace::ROOT_PATH.as_string().wrap("this operation requires a folder to be open")
 }
 
+/// Get the paths to all workspace folders. There can be more than one if a multi-root workspace is open.
+pub fn workspace_folders() -> Vec<String> {
+	let folders = js_sys::Reflect::get(&vscode_sys::workspace::NAMESPACE, &JsValue::from_str("workspaceFolders"));
+	let folders = match folders.unwrap() {
+		folders if folders.is_undefined() => return Vec::new(),
+		folders => folders.unchecked_into::<js_sys::Array>(),
+	};
+	folders.iter().map(|folder| folder.unchecked_into::<vscode_sys::WorkspaceFolder>().uri().fs_path()).collect()
+}
+
+/// Add folders at the end of the current workspace, turning it into a multi-root workspace if necessary.
+/// Returns false if VS Code refused to update the workspace, e.g. because another update is still in progress.
+pub fn add_workspace_folders(paths: &[String]) -> bool {
+	let start = workspace_folders().len() as f64;
+	let folders = paths
+		.iter()
+		.map(|path| {
+			let folder = js_sys::Object::new();
+			js_sys::Reflect::set(&folder, &JsValue::from_str("uri"), &vscode_sys::Uri::file(path)).unwrap();
+			JsValue::from(folder)
+		})
+		.collect::<js_sys::Array>();
+	vscode_sys::workspace::update_workspace_folders(start, &JsValue::NULL, folders)
+}
+
+/// Get the path to the workspace folder containing the currently edited file.
+/// Returns None if no file is edited or if it is outside of the workspace.
+pub fn active_workspace_folder() -> Option<String> {
+	let uri = active_editor()?.document().uri();
+	Some(vscode_sys::workspace::get_workspace_folder(&uri)?.uri().fs_path())
+}
+
 /// Get the path to the root directory of the extension installation.
 pub fn extension_root() -> &'static str {
 	crate::glue::EXTENSION_PATH.get().unwrap()
 }
 
+/// Get the path to a directory where the extension can store global data, such as caches shared between workspaces.
+/// The directory may not exist yet.
+pub fn global_storage_root() -> String {
+	crate::glue::EXTENSION_CONTEXT.with(|ext_ctx| {
+		ext_ctx.get().unwrap().unchecked_ref::<vscode_sys::ExtensionContext>().get_global_storage_path()
+	})
+}
+
 /// Get the path to the currently edited file.
 pub async fn active_editor_file() -> Option<String> {
-	vscode_sys::window::ACTIVE_TEXT_EDITOR.as_ref().map(|edi| edi.document().file_name())
+	active_editor().map(|edi| edi.document().file_name())
+}
+
+fn active_editor() -> Option<vscode_sys::TextEditor> {
+	let editor = js_sys::Reflect::get(&vscode_sys::window::NAMESPACE, &JsValue::from_str("activeTextEditor")).unwrap();
+	if editor.is_undefined() { None } else { Some(editor.unchecked_into()) }
 }
 
 /// Set the OS clipboard content to a given value.
@@ -0,0 +1,19 @@
+//! Debugging sessions run by the debuggers installed in VS Code.
+//!
+//! The debuggers themselves are provided by other extensions, like the C/C++ extension providing the "cppdbg" type.
+
+use crate::{E, R};
+use wasm_bindgen::JsValue;
+
+/// Start a debugging session with a given launch configuration, in the same format as the ones in launch.json. The
+/// configuration must contain at least the type, request and name fields.
+///
+/// The returned future will complete when the session is started, not when it ends.
+pub async fn start_debugging(configuration: &serde_json::Value) -> R<()> {
+	let configuration = JsValue::from_serde(configuration).unwrap();
+	if vscode_sys::debug::start_debugging(JsValue::UNDEFINED, configuration).await? {
+		Ok(())
+	} else {
+		Err(E::error("could not start the debugging session"))
+	}
+}
@@ -0,0 +1,97 @@
+//! Decorations that change how parts of a text editor look, like text displayed after the end of a line.
+//!
+//! Typically used for displaying information about the code that does not fit in diagnostics, like profiling results.
+
+use crate::Range;
+use wasm_bindgen::{JsCast, JsValue};
+
+/// Handle to a decoration type, which describes the style shared by all its decorations.
+pub struct DecorationType {
+	native: vscode_sys::TextEditorDecorationType,
+}
+
+/// Style shared by all decorations of a given type.
+#[derive(Default)]
+pub struct Style<'a> {
+	/// CSS color of the text displayed after the decorated range.
+	pub after_color: Option<&'a str>,
+	/// CSS background color of the decorated range.
+	pub background_color: Option<&'a str>,
+	/// Whether the decoration should span the whole line, even if the range is shorter.
+	pub whole_line: bool,
+}
+
+/// A single decoration of a part of a file.
+pub struct Decoration {
+	/// Range of the file that will be decorated.
+	pub range: Range,
+	/// Message displayed when hovering over the decorated range.
+	pub hover_message: Option<String>,
+	/// Text displayed after the decorated range.
+	pub after_text: Option<String>,
+}
+
+impl DecorationType {
+	/// Create a new decoration type with a given style.
+	pub fn new(style: Style) -> DecorationType {
+		let after = vscode_sys::window::ThemableDecorationAttachmentRenderOptions {
+			color: style.after_color,
+			content_text: None,
+			margin: Some("0 0 0 2em"),
+		};
+		let options = vscode_sys::window::DecorationRenderOptions {
+			after,
+			background_color: style.background_color,
+			is_whole_line: style.whole_line,
+		};
+		DecorationType { native: vscode_sys::window::create_text_editor_decoration_type(options) }
+	}
+
+	/// Replace all decorations of this type in the visible editors of a given file. The decorations will not be
+	/// displayed in editors opened later, so the file should be opened before calling this.
+	pub fn set(&self, path: &str, decorations: &[Decoration]) {
+		for editor in visible_editors().filter(|editor| editor.document().file_name() == path) {
+			let array = js_sys::Array::new();
+			for decoration in decorations {
+				array.push(&decoration.as_native());
+			}
+			editor.set_decorations(&self.native, array);
+		}
+	}
+
+	/// Remove all decorations of this type from the visible editors.
+	pub fn clear(&self) {
+		for editor in visible_editors() {
+			editor.set_decorations(&self.native, js_sys::Array::new());
+		}
+	}
+}
+
+impl Drop for DecorationType {
+	fn drop(&mut self) {
+		self.native.dispose();
+	}
+}
+
+impl Decoration {
+	fn as_native(&self) -> JsValue {
+		let after = vscode_sys::window::ThemableDecorationAttachmentRenderOptions {
+			color: None,
+			content_text: self.after_text.as_deref(),
+			margin: None,
+		};
+		let render_options = vscode_sys::window::DecorationInstanceRenderOptions { after };
+		let object = js_sys::Object::new();
+		let set = |key: &str, value: &JsValue| js_sys::Reflect::set(&object, &JsValue::from_str(key), value).unwrap();
+		set("range", &self.range.as_native());
+		if let Some(hover_message) = &self.hover_message {
+			set("hoverMessage", &JsValue::from_str(hover_message));
+		}
+		set("renderOptions", &JsValue::from_serde(&render_options).unwrap());
+		object.into()
+	}
+}
+
+fn visible_editors() -> impl Iterator<Item=vscode_sys::TextEditor> {
+	vscode_sys::window::VISIBLE_TEXT_EDITORS.values().into_iter().map(|editor| editor.unwrap().unchecked_into())
+}
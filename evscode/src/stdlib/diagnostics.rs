@@ -0,0 +1,98 @@
+//! Errors and warnings displayed as squiggles in the editor and listed in the Problems panel.
+//!
+//! Typically used for displaying compilation errors, so that they can be browsed without reading raw compiler output.
+
+use crate::Range;
+
+/// Handle to a named collection of diagnostics.
+pub struct DiagnosticCollection {
+	name: String,
+	native: vscode_sys::DiagnosticCollection,
+}
+
+/// A single error, warning or other message about a part of a file.
+pub struct Diagnostic {
+	/// Range of the file that the message concerns.
+	pub range: Range,
+	/// Human-readable message.
+	pub message: String,
+	/// Severity, which decides the color and icon of the diagnostic.
+	pub severity: Severity,
+	/// Additional messages, possibly about other files, such as notes about where a template was instantiated.
+	pub related: Vec<Related>,
+}
+
+/// Additional message attached to a diagnostic, with a location that can be clicked to navigate to it.
+pub struct Related {
+	/// Path to the file that the message concerns.
+	pub path: String,
+	/// Range of the file that the message concerns.
+	pub range: Range,
+	/// Human-readable message.
+	pub message: String,
+}
+
+/// Severity of a diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+	/// Something that makes the program invalid, like a compilation error.
+	Error,
+	/// Something suspicious, but not invalid.
+	Warning,
+	/// Something worth informing about, but not suspicious.
+	Information,
+	/// Something that could be done better, displayed in a less noticeable way.
+	Hint,
+}
+
+impl DiagnosticCollection {
+	/// Create a new diagnostic collection with a given name. The name is displayed as the source of every diagnostic.
+	pub fn new(name: &str) -> DiagnosticCollection {
+		DiagnosticCollection {
+			name: name.to_owned(),
+			native: vscode_sys::languages::create_diagnostic_collection(name),
+		}
+	}
+
+	/// Replace all diagnostics of a given file.
+	pub fn set(&self, path: &str, diagnostics: &[Diagnostic]) {
+		let array = js_sys::Array::new();
+		for diagnostic in diagnostics {
+			let native = vscode_sys::Diagnostic::new(
+				&diagnostic.range.as_native(),
+				&diagnostic.message,
+				diagnostic.severity.as_native(),
+			);
+			native.set_source(&self.name);
+			let related = js_sys::Array::new();
+			for info in &diagnostic.related {
+				let location = vscode_sys::Location::new(&vscode_sys::Uri::file(&info.path), &info.range.as_native());
+				related.push(&vscode_sys::DiagnosticRelatedInformation::new(&location, &info.message));
+			}
+			native.set_related_information(related);
+			array.push(&native);
+		}
+		self.native.set(&vscode_sys::Uri::file(path), array);
+	}
+
+	/// Remove all diagnostics of a given file.
+	pub fn delete(&self, path: &str) {
+		self.native.delete(&vscode_sys::Uri::file(path));
+	}
+
+	/// Remove all diagnostics from the collection.
+	pub fn clear(&self) {
+		self.native.clear();
+	}
+}
+
+impl Severity {
+	fn as_native(self) -> vscode_sys::DiagnosticSeverity {
+		match self {
+			Severity::Error => vscode_sys::DiagnosticSeverity::Error,
+			Severity::Warning => vscode_sys::DiagnosticSeverity::Warning,
+			Severity::Information => vscode_sys::DiagnosticSeverity::Information,
+			Severity::Hint => vscode_sys::DiagnosticSeverity::Hint,
+		}
+	}
+}
@@ -16,6 +16,15 @@ pub fn open_editor(path: &str) -> Builder {
 	Builder { path, cursor: None, force_new: false }
 }
 
+/// Open a new untitled editor with a given content, which will not be saved anywhere unless the user decides to. The
+/// language identifier, like "cpp" or "python", decides how the content is highlighted.
+pub async fn open_untitled(content: &str, language: &str) -> R<()> {
+	let options = vscode_sys::workspace::OpenTextDocumentOptions { content, language };
+	let doc = vscode_sys::workspace::open_untitled_text_document(options).await?;
+	vscode_sys::window::show_text_document(&doc).await;
+	Ok(())
+}
+
 impl<'a> Builder<'a> {
 	/// Set cursor position in the text editor. The indices are 0-based.
 	pub fn cursor(mut self, pos: impl Into<Option<Position>>) -> Self {
@@ -3,7 +3,7 @@
 use js_sys::Array;
 use serde::{Deserialize, Serialize};
 use std::iter::{Chain, Empty, Once};
-use wasm_bindgen::JsValue;
+use wasm_bindgen::{closure::Closure, JsValue};
 
 /// Builder object for an item that can be selected.
 #[must_use]
@@ -46,9 +46,10 @@ pub struct Builder<'a, T, I: Iterator<Item=Item<T>>> {
 	match_on_description: bool,
 	match_on_detail: bool,
 	placeholder: Option<&'a str>,
+	on_highlight: Option<Box<dyn FnMut(T)>>,
 	items: I,
 }
-impl<'a, T: Serialize+for<'d> Deserialize<'d>, I: Iterator<Item=Item<T>>> Builder<'a, T, I> {
+impl<'a, T: Serialize+for<'d> Deserialize<'d>+'static, I: Iterator<Item=Item<T>>> Builder<'a, T, I> {
 	/// Do not make the quick pick disappear when user breaks focus.
 	pub fn ignore_focus_out(mut self) -> Self {
 		self.ignore_focus_out = true;
@@ -81,6 +82,12 @@ impl<'a, T: Serialize+for<'d> Deserialize<'d>, I: Iterator<Item=Item<T>>> Builde
 		self
 	}
 
+	/// Call a function with the ID of an item whenever the user highlights it, for example to show its preview.
+	pub fn on_highlight(mut self, callback: impl FnMut(T)+'static) -> Self {
+		self.on_highlight = Some(Box::new(callback));
+		self
+	}
+
 	/// Add an item to the selection.
 	pub fn item(self, item: Item<T>) -> Builder<'a, T, Chain<I, Once<Item<T>>>> {
 		Builder {
@@ -88,6 +95,7 @@ impl<'a, T: Serialize+for<'d> Deserialize<'d>, I: Iterator<Item=Item<T>>> Builde
 			match_on_description: self.match_on_description,
 			match_on_detail: self.match_on_detail,
 			placeholder: self.placeholder,
+			on_highlight: self.on_highlight,
 			items: self.items.chain(std::iter::once(item)),
 		}
 	}
@@ -99,6 +107,7 @@ impl<'a, T: Serialize+for<'d> Deserialize<'d>, I: Iterator<Item=Item<T>>> Builde
 			match_on_description: self.match_on_description,
 			match_on_detail: self.match_on_detail,
 			placeholder: self.placeholder,
+			on_highlight: self.on_highlight,
 			items: self.items.chain(items.into_iter()),
 		}
 	}
@@ -127,7 +136,21 @@ impl<'a, T: Serialize+for<'d> Deserialize<'d>, I: Iterator<Item=Item<T>>> Builde
 			match_on_detail: self.match_on_detail,
 			place_holder: self.placeholder,
 		};
-		let item = vscode_sys::window::show_quick_pick(&items, options).await;
+		let on_highlight = self.on_highlight.map(|mut on_highlight| {
+			Closure::wrap(Box::new(move |item: JsValue| {
+				if let Ok(item) = item.into_serde::<vscode_sys::ItemRet<T>>() {
+					on_highlight(item.id);
+				}
+			}) as Box<dyn FnMut(JsValue)>)
+		});
+		let item = match &on_highlight {
+			Some(on_highlight) => {
+				let options = JsValue::from_serde(&options).unwrap();
+				js_sys::Reflect::set(&options, &JsValue::from_str("onDidSelectItem"), on_highlight.as_ref()).unwrap();
+				vscode_sys::window::show_quick_pick_with_callbacks(&items, &options).await
+			},
+			None => vscode_sys::window::show_quick_pick(&items, options).await,
+		};
 		if !item.is_undefined() {
 			let item: vscode_sys::ItemRet<T> = item.into_serde().unwrap();
 			Some(item.id)
@@ -152,6 +175,7 @@ impl QuickPick {
 			match_on_detail: false,
 			match_on_description: false,
 			placeholder: None,
+			on_highlight: None,
 			items: std::iter::empty(),
 		}
 	}
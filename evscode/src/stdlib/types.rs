@@ -16,6 +16,18 @@ pub struct Range {
 	pub end: Position,
 }
 
+impl Position {
+	pub(crate) fn as_native(&self) -> vscode_sys::Position {
+		vscode_sys::Position::new(self.line, self.column)
+	}
+}
+
+impl Range {
+	pub(crate) fn as_native(&self) -> vscode_sys::Range {
+		vscode_sys::Range::new(&self.start.as_native(), &self.end.as_native())
+	}
+}
+
 /// View column where a tab can appear.
 ///
 /// The values are from the [docs], and hopefully no one changes them.
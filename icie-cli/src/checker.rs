@@ -0,0 +1,54 @@
+use crate::{
+	error::{ResultExt, R}, executable::{Environment, Executable}
+};
+use icie_common::checker::equal_bew;
+use std::{
+	fs, path::PathBuf, sync::atomic::{AtomicUsize, Ordering::SeqCst}
+};
+
+pub enum Checker {
+	FreeWhitespace,
+	Exec { executable: Executable, environment: Environment },
+}
+
+impl Checker {
+	pub fn judge(&self, input: &str, desired: &str, out: &str) -> R<bool> {
+		match self {
+			Checker::FreeWhitespace => Ok(equal_bew(desired, out)),
+			Checker::Exec { executable, environment } => {
+				let input_file = Tempfile::new("input", ".in", input)?;
+				let desired_file = Tempfile::new("desired", ".out", desired)?;
+				let out_file = Tempfile::new("output", ".out", out)?;
+				let args = [input_file.arg(), out_file.arg(), desired_file.arg()];
+				let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+				let run = executable.run("", &args, environment)?;
+				Ok(run.success())
+			},
+		}
+	}
+}
+
+struct Tempfile {
+	path: PathBuf,
+}
+
+impl Tempfile {
+	fn new(uniq_name: &str, extension: &str, data: &str) -> R<Tempfile> {
+		static COUNTER: AtomicUsize = AtomicUsize::new(0);
+		let id = COUNTER.fetch_add(1, SeqCst);
+		let name = format!("icie_{}_{}_{}{}", uniq_name, std::process::id(), id, extension);
+		let path = std::env::temp_dir().join(name);
+		fs::write(&path, data).wrap("could not create a temporary file for the checker")?;
+		Ok(Tempfile { path })
+	}
+
+	fn arg(&self) -> String {
+		self.path.display().to_string()
+	}
+}
+
+impl Drop for Tempfile {
+	fn drop(&mut self) {
+		let _ = fs::remove_file(&self.path);
+	}
+}
@@ -0,0 +1,273 @@
+use crate::{
+	error::{ResultExt, E, R}, executable::{Environment, Executable}
+};
+use icie_common::{
+	cache::Fingerprint, compile::{compiler_flags, find_main_class, local_includes, rustc_flags}
+};
+use std::{
+	cell::RefCell, collections::{HashMap, HashSet}, fs, path::{Path, PathBuf}
+};
+
+pub use icie_common::compile::{Codegen, Standard, TaskFlags, Toolchain};
+
+const EXECUTABLE_EXTENSION: &str = "e";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+	Cpp,
+	Python,
+	Rust,
+	Java,
+	Kotlin,
+}
+
+pub struct Compiler {
+	pub command: String,
+	pub toolchain: Toolchain,
+	pub standard: Standard,
+	pub custom_flags: Vec<String>,
+	pub task: TaskFlags,
+	/// File storing the fingerprints of compiled artifacts, keyed by the artifact path.
+	pub fingerprints: PathBuf,
+	versions: RefCell<HashMap<String, String>>,
+}
+
+impl Language {
+	pub const LIST: &'static [Language] =
+		&[Language::Cpp, Language::Python, Language::Rust, Language::Java, Language::Kotlin];
+
+	pub fn from_path(path: &Path) -> Option<Language> {
+		let extension = path.extension()?.to_str()?;
+		match extension {
+			"cpp" | "cxx" | "cc" => Some(Language::Cpp),
+			_ => Language::LIST.iter().copied().find(|language| language.extension() == extension),
+		}
+	}
+
+	pub fn extension(self) -> &'static str {
+		match self {
+			Language::Cpp => "cpp",
+			Language::Python => "py",
+			Language::Rust => "rs",
+			Language::Java => "java",
+			Language::Kotlin => "kt",
+		}
+	}
+}
+
+impl Compiler {
+	pub fn new(
+		command: String,
+		toolchain: Toolchain,
+		standard: Standard,
+		custom_flags: Vec<String>,
+		task: TaskFlags,
+		fingerprints: PathBuf,
+	) -> Compiler {
+		let versions = RefCell::new(HashMap::new());
+		Compiler { command, toolchain, standard, custom_flags, task, fingerprints, versions }
+	}
+
+	pub fn default_command() -> &'static str {
+		if cfg!(windows) { "g++" } else { "clang++" }
+	}
+
+	pub fn compile(&self, source: &Path, codegen: Codegen, force: bool) -> R<Executable> {
+		if !source.exists() {
+			return Err(E::error(format!("source {} does not exist", source.display())));
+		}
+		let language = Language::from_path(source)
+			.wrap(format!("{} is not written in any of the supported languages", source.display()))?;
+		if language == Language::Python {
+			return Ok(python_executable(source));
+		}
+		let output_path = artifact(language, source, codegen)?;
+		let identity = self.compiler_identity(language, codegen)?;
+		let fingerprint = fingerprint(source, language == Language::Cpp, &identity)?;
+		let mut fingerprints = self.load_fingerprints();
+		let artifact_key = output_path.display().to_string();
+		if !force && fingerprints.get(&artifact_key) == Some(&fingerprint) && output_path.exists() {
+			return executable(language, source, &output_path);
+		}
+		let (command, args) = self.compiler_command(language, source, &output_path, codegen);
+		let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+		let environment = Environment { time_limit: None, cwd: None };
+		let run = Executable::new_name(command).run("", &args, &environment)?;
+		if !run.stderr.is_empty() {
+			eprint!("{}", run.stderr);
+		}
+		if !run.success() {
+			return Err(E::error(format!("compilation of {} failed", source.display())));
+		}
+		fingerprints.insert(artifact_key, fingerprint);
+		self.save_fingerprints(fingerprints)?;
+		executable(language, source, &output_path)
+	}
+
+	fn compiler_command(
+		&self,
+		language: Language,
+		source: &Path,
+		output_path: &Path,
+		codegen: Codegen,
+	) -> (String, Vec<String>) {
+		let source = source.display().to_string();
+		let output_path = output_path.display().to_string();
+		match language {
+			Language::Cpp => {
+				let mut args = self.cpp_flags(codegen);
+				args.extend(vec![source, "-o".to_owned(), output_path]);
+				(self.command.clone(), args)
+			},
+			Language::Rust => {
+				let mut args = vec!["--edition=2018".to_owned()];
+				args.extend(rustc_flags(codegen).iter().map(|flag| (*flag).to_owned()));
+				args.extend(vec![source, "-o".to_owned(), output_path]);
+				("rustc".to_owned(), args)
+			},
+			Language::Java => {
+				let class_dir = Path::new(&output_path).parent().unwrap().display().to_string();
+				let args = vec!["-encoding".to_owned(), "UTF-8".to_owned(), "-d".to_owned(), class_dir, source];
+				("javac".to_owned(), args)
+			},
+			Language::Kotlin => {
+				let args = vec![source, "-include-runtime".to_owned(), "-d".to_owned(), output_path];
+				(kotlinc().to_owned(), args)
+			},
+			Language::Python => unreachable!(),
+		}
+	}
+
+	fn cpp_flags(&self, codegen: Codegen) -> Vec<String> {
+		let standard = self.task.standard.unwrap_or(self.standard);
+		let mut flags: Vec<String> =
+			compiler_flags(standard, codegen, self.toolchain, cfg!(windows)).into_iter().map(String::from).collect();
+		flags.extend(self.custom_flags.iter().cloned());
+		flags.extend(self.task.defines.iter().map(|define| format!("-D{}", define)));
+		flags.extend(self.task.stack_size.and_then(|stack_size| stack_size_flag(stack_size * 1024 * 1024)));
+		flags
+	}
+
+	/// Describes everything apart from the source code that affects the compiled artifact, so that changing the
+	/// compiler or any of the flags causes a recompilation.
+	fn compiler_identity(&self, language: Language, codegen: Codegen) -> R<Vec<String>> {
+		Ok(match language {
+			Language::Cpp => {
+				let mut identity = vec![self.compiler_version(&self.command, "--version")?];
+				identity.extend(self.cpp_flags(codegen));
+				identity
+			},
+			Language::Rust => {
+				let mut identity = vec![self.compiler_version("rustc", "--version")?];
+				identity.extend(rustc_flags(codegen).iter().map(|flag| (*flag).to_owned()));
+				identity
+			},
+			Language::Java => vec![self.compiler_version("javac", "-version")?],
+			Language::Kotlin => vec![self.compiler_version(kotlinc(), "-version")?],
+			Language::Python => Vec::new(),
+		})
+	}
+
+	/// Returns the version description printed by the compiler. Running the compiler takes a noticeable amount of time,
+	/// especially for JVM-based ones, so the result is remembered for the whole command.
+	fn compiler_version(&self, command: &str, flag: &str) -> R<String> {
+		if let Some(version) = self.versions.borrow().get(command) {
+			return Ok(version.clone());
+		}
+		let environment = Environment { time_limit: None, cwd: None };
+		let run = Executable::new_name(command.to_owned()).run("", &[flag], &environment)?;
+		let version = format!("{}{}", run.stdout, run.stderr);
+		self.versions.borrow_mut().insert(command.to_owned(), version.clone());
+		Ok(version)
+	}
+
+	/// Loads the saved fingerprints. A missing or corrupted file only causes the sources to be compiled again.
+	fn load_fingerprints(&self) -> HashMap<String, String> {
+		fs::read_to_string(&self.fingerprints)
+			.ok()
+			.and_then(|fingerprints| serde_json::from_str(&fingerprints).ok())
+			.unwrap_or_default()
+	}
+
+	/// Saves the fingerprints, dropping the ones of artifacts that were deleted in the meantime.
+	fn save_fingerprints(&self, mut fingerprints: HashMap<String, String>) -> R<()> {
+		fingerprints.retain(|artifact, _| Path::new(artifact).exists());
+		let fingerprints = serde_json::to_string(&fingerprints).wrap("could not serialize source fingerprints")?;
+		fs::write(&self.fingerprints, fingerprints)
+			.wrap(format!("could not write source fingerprints to {}", self.fingerprints.display()))
+	}
+}
+
+/// Returns the file that will be created by compiling the source with the given profile.
+fn artifact(language: Language, source: &Path, codegen: Codegen) -> R<PathBuf> {
+	Ok(match language {
+		Language::Cpp | Language::Rust => source.with_extension(codegen.executable_extension(EXECUTABLE_EXTENSION)),
+		Language::Java => {
+			let code = fs::read_to_string(source).wrap(format!("could not read {}", source.display()))?;
+			let class = find_main_class(&code)
+				.wrap(format!("no class with a main method found in {}", source.display()))?;
+			source.with_extension("classes").join(format!("{}.class", class))
+		},
+		Language::Kotlin => source.with_extension("jar"),
+		Language::Python => source.to_owned(),
+	})
+}
+
+/// Returns how to run a source that has already been compiled to the given artifact.
+fn executable(language: Language, source: &Path, artifact: &Path) -> R<Executable> {
+	Ok(match language {
+		Language::Cpp | Language::Rust => Executable::new(artifact.to_owned()),
+		Language::Java => {
+			let class_path = artifact.parent().unwrap().display().to_string();
+			let class = artifact.file_stem().unwrap().to_string_lossy().into_owned();
+			Executable::new_interpreted("java".to_owned(), vec!["-cp".to_owned(), class_path, class])
+		},
+		Language::Kotlin => {
+			Executable::new_interpreted("java".to_owned(), vec!["-jar".to_owned(), artifact.display().to_string()])
+		},
+		Language::Python => python_executable(source),
+	})
+}
+
+fn kotlinc() -> &'static str {
+	if cfg!(windows) { "kotlinc.bat" } else { "kotlinc" }
+}
+
+fn python_executable(source: &Path) -> Executable {
+	let python = if cfg!(windows) { "python.exe" } else { "python3" };
+	Executable::new_interpreted(python.to_owned(), vec![source.display().to_string()])
+}
+
+/// Hashes the source and the compiler identity, along with the local headers the source transitively includes if
+/// `follow_includes` is set. Headers are looked up next to the including file, other ones are most likely system
+/// headers, which change along with the compiler.
+fn fingerprint(source: &Path, follow_includes: bool, compiler_identity: &[String]) -> R<String> {
+	let mut fingerprint = Fingerprint::new(compiler_identity);
+	let mut visited = HashSet::new();
+	let mut queue = vec![source.to_owned()];
+	while let Some(path) = queue.pop() {
+		if !visited.insert(path.clone()) {
+			continue;
+		}
+		let code = fs::read_to_string(&path).wrap(format!("could not read {}", path.display()))?;
+		fingerprint.add_file(&path.display().to_string(), &code);
+		if follow_includes {
+			let directory = path.parent().unwrap_or_else(|| Path::new(""));
+			queue.extend(local_includes(&code).map(|include| directory.join(include)).filter(|path| path.exists()));
+		}
+	}
+	Ok(fingerprint.finish())
+}
+
+/// Returns a linker flag that sets the stack size of the main thread. Linux ignores the size stored in the executable
+/// and uses the limit set with ulimit -s instead, so there it is set when running the program, see
+/// [`Executable::stack_size`].
+fn stack_size_flag(bytes: u64) -> Option<String> {
+	if cfg!(windows) {
+		Some(format!("-Wl,--stack,{}", bytes))
+	} else if cfg!(target_os = "macos") {
+		Some(format!("-Wl,-stack_size,{:#x}", bytes))
+	} else {
+		None
+	}
+}
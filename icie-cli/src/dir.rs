@@ -0,0 +1,109 @@
+use crate::{
+	compile::{Language, TaskFlags}, error::{ResultExt, E, R}
+};
+use serde::Deserialize;
+use std::{
+	fs, path::{Path, PathBuf}
+};
+
+// These mirror the defaults of the icie.dir.* configuration entries in the extension.
+const SOLUTION_STEM: &str = "main";
+const BRUTE_FORCE_STEM: &str = "brute-force";
+const TEST_GENERATOR_STEM: &str = "test-generator";
+const CHECKER_STEM: &str = "checker";
+const TESTS_DIRECTORY: &str = "tests";
+const CUSTOM_TESTS_SUBDIRECTORY: &str = "user";
+
+/// The part of the .icie manifest used by the command-line runner. The extension stores more, like the task URL.
+#[derive(Deserialize)]
+struct Manifest {
+	#[serde(default)]
+	flags: TaskFlags,
+}
+
+/// A task directory laid out the same way the extension creates it, with a .icie manifest in the root.
+pub struct Workspace {
+	root: PathBuf,
+}
+
+impl Workspace {
+	/// Uses the explicitly given directory, or searches for the nearest ancestor of the working directory that
+	/// contains a .icie manifest. Directories that have a solution but no manifest are also accepted, so that tasks
+	/// created by hand work too.
+	pub fn find(explicit: Option<&Path>) -> R<Workspace> {
+		if let Some(root) = explicit {
+			return Ok(Workspace { root: root.to_owned() });
+		}
+		let cwd = std::env::current_dir().wrap("could not query the working directory")?;
+		for dir in cwd.ancestors() {
+			if dir.join(".icie").exists() {
+				return Ok(Workspace { root: dir.to_owned() });
+			}
+		}
+		let workspace = Workspace { root: cwd };
+		if workspace.solution().exists() {
+			Ok(workspace)
+		} else {
+			Err(E::error("not inside an ICIE task directory, no .icie manifest or solution found"))
+		}
+	}
+
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+
+	/// Loads the compiler settings of the task, which are empty if the task was created by hand without a manifest.
+	pub fn task_flags(&self) -> R<TaskFlags> {
+		let path = self.root.join(".icie");
+		if !path.exists() {
+			return Ok(TaskFlags::default());
+		}
+		let manifest = fs::read_to_string(&path).wrap("could not read the .icie manifest")?;
+		let manifest: Manifest = serde_json::from_str(&manifest).wrap(".icie is not a valid manifest")?;
+		Ok(manifest.flags)
+	}
+
+	/// Path of the file storing fingerprints of the compiled sources, used to skip compiling unchanged ones.
+	pub fn fingerprints(&self) -> PathBuf {
+		self.root.join(".icie-fingerprints")
+	}
+
+	pub fn solution(&self) -> PathBuf {
+		self.source(SOLUTION_STEM)
+	}
+
+	pub fn brute_force(&self) -> PathBuf {
+		self.source(BRUTE_FORCE_STEM)
+	}
+
+	pub fn test_generator(&self) -> PathBuf {
+		self.source(TEST_GENERATOR_STEM)
+	}
+
+	pub fn checker(&self) -> PathBuf {
+		self.source(CHECKER_STEM)
+	}
+
+	pub fn tests(&self) -> PathBuf {
+		self.root.join(TESTS_DIRECTORY)
+	}
+
+	pub fn custom_tests(&self) -> PathBuf {
+		self.tests().join(CUSTOM_TESTS_SUBDIRECTORY)
+	}
+
+	pub fn fmt_relative(&self, path: &Path) -> String {
+		path.strip_prefix(&self.root).unwrap_or(path).display().to_string()
+	}
+
+	/// Finds a source with the given stem written in any supported language, preferring C++. If none exists, returns
+	/// the path of the C++ one.
+	fn source(&self, stem: &str) -> PathBuf {
+		let path = self.root.join(stem);
+		Language::LIST
+			.iter()
+			.map(|language| path.with_extension(language.extension()))
+			.find(|path| path.exists())
+			.unwrap_or_else(|| path.with_extension(Language::Cpp.extension()))
+	}
+}
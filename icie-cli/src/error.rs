@@ -0,0 +1,62 @@
+use std::fmt;
+
+/// Result type used for errors in the command-line runner. See [`E`] for details.
+pub type R<T> = Result<T, E>;
+
+/// Error type mirroring the message layering of `evscode::E`, without the VS Code specific parts like actions.
+pub struct E {
+	reasons: Vec<String>,
+	extended: Vec<String>,
+}
+
+impl E {
+	pub fn error(s: impl AsRef<str>) -> E {
+		E { reasons: vec![s.as_ref().to_owned()], extended: Vec::new() }
+	}
+
+	pub fn context(mut self, msg: impl AsRef<str>) -> E {
+		self.reasons.push(msg.as_ref().to_owned());
+		self
+	}
+
+	pub fn extended(mut self, extended: impl AsRef<str>) -> E {
+		self.extended.push(extended.as_ref().to_owned());
+		self
+	}
+
+	pub fn human(&self) -> String {
+		self.reasons.iter().rev().cloned().collect::<Vec<_>>().join("; ")
+	}
+
+	pub fn extended_logs(&self) -> &[String] {
+		&self.extended
+	}
+}
+
+impl fmt::Debug for E {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.human())
+	}
+}
+
+/// An extension trait for terser error handling.
+pub trait ResultExt {
+	type Ok;
+	fn wrap(self, s: impl AsRef<str>) -> R<Self::Ok>;
+}
+
+impl<T, E2: std::error::Error> ResultExt for Result<T, E2> {
+	type Ok = T;
+
+	fn wrap(self, s: impl AsRef<str>) -> R<T> {
+		self.map_err(|e| E::error(e.to_string()).context(s))
+	}
+}
+
+impl<T> ResultExt for Option<T> {
+	type Ok = T;
+
+	fn wrap(self, s: impl AsRef<str>) -> R<T> {
+		self.ok_or_else(|| E::error(s))
+	}
+}
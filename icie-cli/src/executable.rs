@@ -0,0 +1,127 @@
+use crate::error::{ResultExt, E, R};
+use std::{
+	io::{Read, Write}, path::PathBuf, process::{Command, Stdio}, thread, time::{Duration, Instant}
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+/// Printed by the shell wrapper that raises the stack size limit if it could not do so, which usually means the hard
+/// limit is lower than the requested size.
+const ULIMIT_FAILED: &str = "icie: ulimit -s failed";
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ExitKind {
+	Normal,
+	TimeLimitExceeded,
+}
+
+#[derive(Debug)]
+pub struct Run {
+	pub stdout: String,
+	pub stderr: String,
+	pub exit_code: Option<i32>,
+	pub exit_kind: ExitKind,
+	pub time: Duration,
+}
+
+impl Run {
+	pub fn success(&self) -> bool {
+		self.exit_code == Some(0) && self.exit_kind == ExitKind::Normal
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct Environment {
+	pub time_limit: Option<Duration>,
+	pub cwd: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Executable {
+	pub command: String,
+	/// Arguments passed before the ones given to [`Executable::run`], used by interpreted languages to pass the source
+	/// or class path to the interpreter.
+	pub args: Vec<String>,
+	/// Stack size in bytes, set before running the program on Linux, which ignores the size stored in the executable.
+	/// Only the solution has it set, like it would be on the judge.
+	pub stack_size: Option<u64>,
+}
+
+impl Executable {
+	pub fn new(path: PathBuf) -> Executable {
+		Executable { command: path.display().to_string(), args: Vec::new(), stack_size: None }
+	}
+
+	pub fn new_name(command: String) -> Executable {
+		Executable { command, args: Vec::new(), stack_size: None }
+	}
+
+	pub fn new_interpreted(command: String, args: Vec<String>) -> Executable {
+		Executable { command, args, stack_size: None }
+	}
+
+	pub fn run(&self, input: &str, args: &[&str], environment: &Environment) -> R<Run> {
+		let stack_size = self.stack_size.filter(|_| cfg!(target_os = "linux"));
+		let mut command = match stack_size {
+			Some(stack_size) => {
+				// The shell raises its own limit and then replaces itself with the program, passing it the arguments.
+				let script = format!(
+					"ulimit -s {} 2>/dev/null || {{ echo '{}' >&2; exit 1; }}; exec \"$0\" \"$@\"",
+					stack_size / 1024,
+					ULIMIT_FAILED
+				);
+				let mut command = Command::new("sh");
+				command.arg("-c").arg(script).arg(&self.command);
+				command
+			},
+			None => Command::new(&self.command),
+		};
+		command.args(&self.args).args(args).stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped());
+		if let Some(cwd) = &environment.cwd {
+			command.current_dir(cwd);
+		}
+		let t1 = Instant::now();
+		let mut kid = command.spawn().wrap(format!("running {} failed", self.command))?;
+		let mut stdin = kid.stdin.take().unwrap();
+		let input = input.as_bytes().to_owned();
+		// Ignore the error returned from stdin. This can happen when the app exits before any input can be written.
+		let feed_stdin = thread::spawn(move || {
+			let _ = stdin.write_all(&input);
+		});
+		let capture_stdout = capture_stream(kid.stdout.take().unwrap());
+		let capture_stderr = capture_stream(kid.stderr.take().unwrap());
+		let mut exit_kind = ExitKind::Normal;
+		let status = loop {
+			if let Some(status) = kid.try_wait().wrap("waiting for process failed")? {
+				break status;
+			}
+			if environment.time_limit.map_or(false, |time_limit| t1.elapsed() > time_limit) {
+				let _ = kid.kill();
+				exit_kind = ExitKind::TimeLimitExceeded;
+				break kid.wait().wrap("waiting for killed process failed")?;
+			}
+			thread::sleep(POLL_INTERVAL);
+		};
+		let time = t1.elapsed();
+		let _ = feed_stdin.join();
+		let stdout = String::from_utf8_lossy(&capture_stdout.join().unwrap()).into_owned();
+		let stderr = String::from_utf8_lossy(&capture_stderr.join().unwrap()).into_owned();
+		if let Some(stack_size) = stack_size {
+			if status.code() == Some(1) && stderr.trim_end() == ULIMIT_FAILED {
+				return Err(E::error(format!(
+					"could not raise the stack size limit to {} MB, the hard limit shown by ulimit -Hs is lower",
+					stack_size / 1024 / 1024
+				)));
+			}
+		}
+		Ok(Run { stdout, stderr, exit_code: status.code(), exit_kind, time })
+	}
+}
+
+fn capture_stream(mut stream: impl Read+Send+'static) -> thread::JoinHandle<Vec<u8>> {
+	thread::spawn(move || {
+		let mut buf = Vec::new();
+		let _ = stream.read_to_end(&mut buf);
+		buf
+	})
+}
@@ -0,0 +1,314 @@
+//! Command-line test runner for ICIE task directories. It uses the same workspace layout, compiler flags, checkers and
+//! verdicts as the VS Code extension, so that the workflow can be shared with other editors or used in git hooks.
+
+mod checker;
+mod compile;
+mod dir;
+mod error;
+mod executable;
+mod report;
+mod stress;
+mod term;
+mod test;
+
+use crate::{
+	compile::{Codegen, Compiler, Standard, Toolchain}, dir::Workspace, error::{ResultExt, E, R}, executable::Environment, term::Color
+};
+use std::{
+	io::Read, path::PathBuf, process::exit, time::Duration
+};
+
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_FAILURE: i32 = 1;
+const EXIT_ERROR: i32 = 2;
+
+const DEFAULT_TIME_LIMIT: u64 = 1500;
+// This mirrors the default of the icie.checker.timeLimit configuration entry in the extension.
+const DEFAULT_CHECKER_TIME_LIMIT: u64 = 1500;
+const DEFAULT_STANDARD: Standard = Standard::Cpp17;
+
+const USAGE: &str = "Usage: icie [OPTIONS] <COMMAND>
+
+Commands:
+  test [SOURCE] [--report F] [--junit F]  Compile the solution and run it on all saved tests, optionally writing a JSON
+                                          or JUnit XML report of the results
+  stress [--count N] [--save]             Compare the solution with the brute force solution on tests from the test
+                                          generator
+  add-test [--input F] [--output F]       Add a user test, reading the input from stdin if no file is given, and
+                                          evaluating the output with the brute force solution if no file is given
+
+Sources are found by their file stems, main, brute-force, test-generator and checker, and can be written in C++,
+Python, Rust, Java or Kotlin. The C++ standard, macros and stack size set for the task in the .icie manifest are used.
+
+Options:
+  --workspace DIR      Task directory, by default the nearest directory with a .icie manifest
+  --compiler CMD       C++ compiler command, by default clang++ (g++ on Windows)
+  --toolchain KIND     Kind of the compiler, clang or gcc, which decides the flags passed to it, by default clang
+  --std STANDARD       C++ standard, like c++17 or gnu++17, by default c++17
+  --flags FLAGS        Additional compiler flags, separated by spaces
+  --time-limit MS      Time limit in milliseconds, or \"none\", by default 1500
+  --checker-time-limit MS
+                       Time limit of the checker in milliseconds, or \"none\", by default 1500
+  --no-color           Disable colored output
+  -h, --help           Print this message
+
+Exit codes: 0 if all tests passed, 1 if any test failed, 2 on other errors.";
+
+pub struct Context {
+	pub workspace: Workspace,
+	pub compiler: Compiler,
+	pub time_limit: Option<Duration>,
+	pub checker_time_limit: Option<Duration>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+	Test { source: Option<PathBuf>, report: Option<PathBuf>, junit: Option<PathBuf> },
+	Stress { count: Option<usize>, save: bool },
+	AddTest { input: Option<PathBuf>, output: Option<PathBuf> },
+	Help,
+}
+
+#[derive(Debug)]
+struct Options {
+	command: Command,
+	workspace: Option<PathBuf>,
+	compiler: String,
+	toolchain: Toolchain,
+	standard: Standard,
+	flags: Vec<String>,
+	time_limit: Option<Duration>,
+	checker_time_limit: Option<Duration>,
+	no_color: bool,
+}
+
+impl Context {
+	pub fn environment(&self) -> Environment {
+		Environment { time_limit: self.time_limit, cwd: Some(self.workspace.root().to_owned()) }
+	}
+
+	pub fn checker_environment(&self) -> Environment {
+		Environment { time_limit: self.checker_time_limit, cwd: Some(self.workspace.root().to_owned()) }
+	}
+}
+
+fn main() {
+	let options = match parse_args(std::env::args().skip(1).collect()) {
+		Ok(options) => options,
+		Err(e) => {
+			eprintln!("error: {}\n\n{}", e.human(), USAGE);
+			exit(EXIT_ERROR);
+		},
+	};
+	term::initialize(options.no_color);
+	let code = match execute(options) {
+		Ok(true) => EXIT_SUCCESS,
+		Ok(false) => EXIT_FAILURE,
+		Err(e) => {
+			eprintln!("{} {}", term::paint(Color::Red, "error:"), e.human());
+			for extended in e.extended_logs() {
+				eprintln!("{}", extended);
+			}
+			EXIT_ERROR
+		},
+	};
+	exit(code);
+}
+
+fn execute(options: Options) -> R<bool> {
+	if let Command::Help = options.command {
+		println!("{}", USAGE);
+		return Ok(true);
+	}
+	let workspace = Workspace::find(options.workspace.as_deref())?;
+	let compiler = Compiler::new(
+		options.compiler,
+		options.toolchain,
+		options.standard,
+		options.flags,
+		workspace.task_flags()?,
+		workspace.fingerprints(),
+	);
+	let ctx = Context {
+		workspace,
+		compiler,
+		time_limit: options.time_limit,
+		checker_time_limit: options.checker_time_limit,
+	};
+	match options.command {
+		Command::Test { source, report, junit } => {
+			let source = source.unwrap_or_else(|| ctx.workspace.solution());
+			let runs = test::run(&ctx, &source)?;
+			if let Some(path) = report {
+				report::write_json(&path, &runs, &ctx.workspace.tests())?;
+			}
+			if let Some(path) = junit {
+				report::write_junit(&path, &runs, &ctx.workspace.tests())?;
+			}
+			Ok(runs.iter().all(|run| run.outcome.verdict.success()))
+		},
+		Command::Stress { count, save } => stress::run(&ctx, count, save),
+		Command::AddTest { input, output } => add_test(&ctx, input, output),
+		Command::Help => unreachable!(),
+	}
+}
+
+fn add_test(ctx: &Context, input: Option<PathBuf>, output: Option<PathBuf>) -> R<bool> {
+	let input = match input {
+		Some(path) => std::fs::read_to_string(&path).wrap(format!("could not read {}", path.display()))?,
+		None => {
+			let mut buf = String::new();
+			std::io::stdin().read_to_string(&mut buf).wrap("could not read test input from stdin")?;
+			buf
+		},
+	};
+	let desired = match output {
+		Some(path) => std::fs::read_to_string(&path).wrap(format!("could not read {}", path.display()))?,
+		None => {
+			let brute_force = ctx.workspace.brute_force();
+			if !brute_force.exists() {
+				return Err(E::error("no output file given and the brute force solution does not exist to evaluate it"));
+			}
+			let brute_force = ctx.compiler.compile(&brute_force, Codegen::Release, false)?;
+			stress::run_brute_force(&input, &brute_force, &ctx.environment())?
+		},
+	};
+	let path = test::add_test(&ctx.workspace.custom_tests(), &input, &desired)?;
+	println!("Added {}", ctx.workspace.fmt_relative(&path));
+	Ok(true)
+}
+
+fn parse_args(args: Vec<String>) -> R<Options> {
+	let mut positional = Vec::new();
+	let mut workspace = None;
+	let mut compiler = Compiler::default_command().to_owned();
+	let mut toolchain = Toolchain::Clang;
+	let mut standard = DEFAULT_STANDARD;
+	let mut flags = Vec::new();
+	let mut time_limit = Some(Duration::from_millis(DEFAULT_TIME_LIMIT));
+	let mut checker_time_limit = Some(Duration::from_millis(DEFAULT_CHECKER_TIME_LIMIT));
+	let mut no_color = false;
+	let mut count = None;
+	let mut save = false;
+	let mut input = None;
+	let mut output = None;
+	let mut report = None;
+	let mut junit = None;
+	let mut help = false;
+	let mut args = args.into_iter();
+	while let Some(arg) = args.next() {
+		let mut value = |name: &str| args.next().wrap(format!("option {} requires a value", name));
+		match arg.as_str() {
+			"--workspace" => workspace = Some(PathBuf::from(value("--workspace")?)),
+			"--compiler" => compiler = value("--compiler")?,
+			"--toolchain" => toolchain = parse_toolchain(&value("--toolchain")?)?,
+			"--std" => standard = parse_standard(&value("--std")?)?,
+			"--flags" => flags.extend(value("--flags")?.split(' ').filter(|f| !f.is_empty()).map(String::from)),
+			"--time-limit" => time_limit = parse_time_limit("--time-limit", &value("--time-limit")?)?,
+			"--checker-time-limit" => {
+				checker_time_limit = parse_time_limit("--checker-time-limit", &value("--checker-time-limit")?)?
+			},
+			"--no-color" => no_color = true,
+			"--count" => count = Some(value("--count")?.parse().wrap("--count must be a number")?),
+			"--save" => save = true,
+			"--input" => input = Some(PathBuf::from(value("--input")?)),
+			"--output" => output = Some(PathBuf::from(value("--output")?)),
+			"--report" => report = Some(PathBuf::from(value("--report")?)),
+			"--junit" => junit = Some(PathBuf::from(value("--junit")?)),
+			"-h" | "--help" => help = true,
+			_ if arg.starts_with('-') => return Err(E::error(format!("unrecognized option {}", arg))),
+			_ => positional.push(arg),
+		}
+	}
+	let command = match positional.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+		_ if help => Command::Help,
+		["test"] => Command::Test { source: None, report, junit },
+		["test", source] => Command::Test { source: Some(PathBuf::from(source)), report, junit },
+		["stress"] => Command::Stress { count, save },
+		["add-test"] => Command::AddTest { input, output },
+		[] => return Err(E::error("no command given")),
+		[command, ..] => return Err(E::error(format!("unrecognized command or arguments for {}", command))),
+	};
+	Ok(Options { command, workspace, compiler, toolchain, standard, flags, time_limit, checker_time_limit, no_color })
+}
+
+fn parse_toolchain(raw: &str) -> R<Toolchain> {
+	match raw {
+		"clang" => Ok(Toolchain::Clang),
+		"gcc" => Ok(Toolchain::Gcc),
+		_ => Err(E::error(format!("unrecognized toolchain {}, expected clang or gcc", raw))),
+	}
+}
+
+/// Accepts both the ISO and GNU dialect names, because the dialect is chosen according to the toolchain anyway.
+fn parse_standard(raw: &str) -> R<Standard> {
+	let flag = format!("-std={}", raw);
+	Standard::LIST
+		.iter()
+		.copied()
+		.find(|standard| standard.flag_clang() == flag || standard.flag_gcc() == flag)
+		.wrap(format!("unrecognized C++ standard {}, expected e.g. c++17", raw))
+}
+
+fn parse_time_limit(option: &str, raw: &str) -> R<Option<Duration>> {
+	if raw == "none" {
+		Ok(None)
+	} else {
+		Ok(Some(Duration::from_millis(raw.parse().wrap(format!("{} must be a number or \"none\"", option))?)))
+	}
+}
+
+#[cfg(test)]
+fn parse(args: &[&str]) -> R<Options> {
+	parse_args(args.iter().map(|arg| (*arg).to_owned()).collect())
+}
+
+#[test]
+fn test_parse_args() {
+	let options = parse(&["test"]).unwrap();
+	assert_eq!(options.command, Command::Test { source: None, report: None, junit: None });
+	assert_eq!(options.workspace, None);
+	assert_eq!(options.toolchain, Toolchain::Clang);
+	assert_eq!(options.standard, Standard::Cpp17);
+	assert_eq!(options.time_limit, Some(Duration::from_millis(1500)));
+	assert_eq!(options.checker_time_limit, Some(Duration::from_millis(1500)));
+	assert!(!options.no_color);
+	let options = parse(&[
+		"--std",
+		"gnu++14",
+		"--toolchain",
+		"gcc",
+		"test",
+		"a.py",
+		"--junit",
+		"report.xml",
+		"--flags",
+		"-DLOCAL  -O1",
+		"--time-limit",
+		"none",
+		"--checker-time-limit",
+		"200",
+	])
+	.unwrap();
+	let source = Some(PathBuf::from("a.py"));
+	assert_eq!(options.command, Command::Test { source, report: None, junit: Some(PathBuf::from("report.xml")) });
+	assert_eq!((options.toolchain, options.standard), (Toolchain::Gcc, Standard::Cpp14));
+	assert_eq!(options.flags, ["-DLOCAL", "-O1"]);
+	assert_eq!((options.time_limit, options.checker_time_limit), (None, Some(Duration::from_millis(200))));
+	assert_eq!(parse(&["stress", "--count", "10", "--save"]).unwrap().command, Command::Stress {
+		count: Some(10),
+		save: true
+	});
+	assert_eq!(parse(&["add-test", "--input", "a.in"]).unwrap().command, Command::AddTest {
+		input: Some(PathBuf::from("a.in")),
+		output: None
+	});
+	assert_eq!(parse(&["stress", "--help"]).unwrap().command, Command::Help);
+	assert_eq!(parse(&[]).unwrap_err().human(), "no command given");
+	assert_eq!(parse(&["test", "a.cpp", "b.cpp"]).unwrap_err().human(), "unrecognized command or arguments for test");
+	assert_eq!(parse(&["test", "--verbose"]).unwrap_err().human(), "unrecognized option --verbose");
+	assert_eq!(parse(&["test", "--time-limit"]).unwrap_err().human(), "option --time-limit requires a value");
+	assert!(parse(&["test", "--std", "c++98"]).is_err());
+	assert!(parse(&["test", "--toolchain", "msvc"]).is_err());
+	assert!(parse(&["stress", "--count", "ten"]).is_err());
+}
@@ -0,0 +1,31 @@
+use crate::{
+	error::{ResultExt, R}, test::TestRun
+};
+use icie_common::report::{Report, ReportTest};
+use std::{fs, path::Path};
+
+/// How many lines of the output and standard error are included in the report for every test.
+const EXCERPT_LINES: usize = 20;
+
+pub fn write_json(path: &Path, runs: &[TestRun], tests: &Path) -> R<()> {
+	let report = build(runs, tests);
+	fs::write(path, report.json()).wrap(format!("could not write JSON report to {}", path.display()))
+}
+
+pub fn write_junit(path: &Path, runs: &[TestRun], tests: &Path) -> R<()> {
+	let report = build(runs, tests);
+	fs::write(path, report.junit()).wrap(format!("could not write JUnit report to {}", path.display()))
+}
+
+fn build(runs: &[TestRun], tests: &Path) -> Report {
+	Report::new(
+		runs.iter()
+			.map(|run| {
+				let outcome = &run.outcome;
+				let path = run.in_path.strip_prefix(tests).unwrap_or(&run.in_path);
+				let path = path.display().to_string().replace('\\', "/");
+				ReportTest::new(path, outcome.verdict, outcome.time, &outcome.out, &outcome.stderr, EXCERPT_LINES)
+			})
+			.collect(),
+	)
+}
@@ -0,0 +1,63 @@
+use crate::{
+	compile::Codegen, error::{E, R}, executable::{Environment, Executable}, term::{self, Color}, test::{self, print_excerpt, simple_test, verdict_color, Task}, Context
+};
+use std::io::Write;
+
+/// Runs random tests until the solution fails one of them, or the test limit is reached. Returns whether all tests
+/// passed.
+pub fn run(ctx: &Context, count: Option<usize>, save: bool) -> R<bool> {
+	let solution = test::compile_solution(ctx, &ctx.workspace.solution())?;
+	let brute_force = ctx.compiler.compile(&ctx.workspace.brute_force(), Codegen::Release, false)?;
+	let test_generator = ctx.compiler.compile(&ctx.workspace.test_generator(), Codegen::Release, false)?;
+	let task = Task::simple(ctx)?;
+	let mut number = 1;
+	while count.map_or(true, |count| number <= count) {
+		let input = run_test_generator(&test_generator, &task.environment)?;
+		let desired = run_brute_force(&input, &brute_force, &task.environment)?;
+		let outcome = simple_test(&solution, &input, Some(&desired), None, &task)
+			.map_err(|e| e.context("failed to run test in stress"))?;
+		if term::is_interactive() {
+			print!("\rTest {} {}", number, term::paint(verdict_color(outcome.verdict), &outcome.verdict.to_string()));
+			let _ = std::io::stdout().flush();
+		}
+		if !outcome.verdict.success() {
+			if term::is_interactive() {
+				println!();
+			}
+			let message = format!("{} on test {}", outcome.verdict, number);
+			println!("{}", term::paint(verdict_color(outcome.verdict), &message));
+			print_excerpt("input", &input);
+			print_excerpt("output", &outcome.out);
+			print_excerpt("desired", &desired);
+			print_excerpt("stderr", &outcome.stderr);
+			if save {
+				let path = test::add_test(&ctx.workspace.custom_tests(), &input, &desired)?;
+				println!("Saved as {}", ctx.workspace.fmt_relative(&path));
+			}
+			return Ok(false);
+		}
+		number += 1;
+	}
+	if term::is_interactive() {
+		println!();
+	}
+	println!("{}", term::paint(Color::Green, &format!("No counterexample found in {} tests", number - 1)));
+	Ok(true)
+}
+
+fn run_test_generator(test_generator: &Executable, environment: &Environment) -> R<String> {
+	let run = test_generator.run("", &[], environment).map_err(|e| e.context("executing test generator aborted"))?;
+	if !run.success() {
+		return Err(E::error(format!("executing test generator failed, {:?}", run)));
+	}
+	Ok(run.stdout)
+}
+
+pub fn run_brute_force(input: &str, brute_force: &Executable, environment: &Environment) -> R<String> {
+	let run =
+		brute_force.run(input, &[], environment).map_err(|e| e.context("executing brute force solution aborted"))?;
+	if !run.success() {
+		return Err(E::error(format!("executing brute force solution failed, {:?}", run)));
+	}
+	Ok(run.stdout)
+}
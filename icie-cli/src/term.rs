@@ -0,0 +1,38 @@
+use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+#[derive(Clone, Copy)]
+pub enum Color {
+	Red,
+	Green,
+	Yellow,
+	Gray,
+}
+
+/// Enables colors only if they were not disabled with --no-color or NO_COLOR, and the output is a terminal.
+pub fn initialize(no_color: bool) {
+	let enabled = !no_color && std::env::var_os("NO_COLOR").is_none() && atty::is(atty::Stream::Stdout);
+	ENABLED.store(enabled, SeqCst);
+}
+
+pub fn is_interactive() -> bool {
+	ENABLED.load(SeqCst)
+}
+
+pub fn paint(color: Color, text: &str) -> String {
+	if !ENABLED.load(SeqCst) {
+		return text.to_owned();
+	}
+	let code = match color {
+		Color::Red => "1;31",
+		Color::Green => "1;32",
+		Color::Yellow => "1;33",
+		Color::Gray => "2",
+	};
+	format!("\x1b[{}m{}\x1b[0m", code, text)
+}
+
+pub fn bold(text: &str) -> String {
+	if ENABLED.load(SeqCst) { format!("\x1b[1m{}\x1b[0m", text) } else { text.to_owned() }
+}
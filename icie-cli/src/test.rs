@@ -0,0 +1,225 @@
+use crate::{
+	checker::Checker, compile::Codegen, error::{ResultExt, E, R}, executable::{Environment, Executable, ExitKind, Run}, term::{self, Color}, Context
+};
+use futures::{executor::block_on, future::ready};
+use icie_common::test::{compare_test_paths, mex, select_verdict, Exit};
+use std::{
+	fs, path::{Path, PathBuf}, time::Duration
+};
+
+pub use icie_common::test::Verdict;
+
+/// How many lines of the output and the desired output are printed for every failed test.
+const EXCERPT_LINES: usize = 12;
+
+#[derive(Debug)]
+pub struct Outcome {
+	pub verdict: Verdict,
+	pub out: String,
+	pub stderr: String,
+	pub time: Duration,
+}
+
+pub struct Task {
+	pub checker: Checker,
+	pub environment: Environment,
+}
+
+#[derive(Debug)]
+pub struct TestRun {
+	pub in_path: PathBuf,
+	pub outcome: Outcome,
+}
+
+impl Task {
+	pub fn simple(ctx: &Context) -> R<Task> {
+		let checker_path = ctx.workspace.checker();
+		let checker = if !checker_path.exists() {
+			Checker::FreeWhitespace
+		} else {
+			let executable = ctx.compiler.compile(&checker_path, Codegen::Release, false)?;
+			Checker::Exec { executable, environment: ctx.checker_environment() }
+		};
+		Ok(Task { checker, environment: ctx.environment() })
+	}
+}
+
+/// Compiles the solution and sets the stack size configured for the task, which is not applied to the other programs.
+pub fn compile_solution(ctx: &Context, source: &Path) -> R<Executable> {
+	let mut solution = ctx.compiler.compile(source, Codegen::Debug, false)?;
+	solution.stack_size = ctx.compiler.task.stack_size.map(|megabytes| megabytes * 1024 * 1024);
+	Ok(solution)
+}
+
+/// Runs the solution on all tests and prints the results.
+pub fn run(ctx: &Context, source: &Path) -> R<Vec<TestRun>> {
+	let solution = compile_solution(ctx, source)?;
+	let task = Task::simple(ctx)?;
+	let inputs = scan_for_tests(&ctx.workspace.tests())?;
+	if inputs.is_empty() {
+		return Err(E::error("no tests available, add some with icie add-test"));
+	}
+	let mut runs = Vec::new();
+	for in_path in &inputs {
+		let input = fs::read_to_string(in_path).wrap(format!("could not read test input {}", in_path.display()))?;
+		let output = load_test_output(in_path, "out")?;
+		let output_alt = load_test_output(in_path, "alt.out")?;
+		let outcome = simple_test(&solution, &input, output.as_deref(), output_alt.as_deref(), &task)?;
+		let run = TestRun { in_path: in_path.clone(), outcome };
+		print_test(ctx, &run, output.as_deref());
+		runs.push(run);
+	}
+	let passed = runs.iter().filter(|run| run.outcome.verdict.success()).count();
+	let all_passed = passed == inputs.len();
+	let summary = format!("{}/{} tests passed", passed, inputs.len());
+	println!("{}", term::paint(if all_passed { Color::Green } else { Color::Red }, &summary));
+	Ok(runs)
+}
+
+pub fn simple_test(
+	exec: &Executable,
+	input: &str,
+	output: Option<&str>,
+	output_alt: Option<&str>,
+	task: &Task,
+) -> R<Outcome> {
+	let run = exec.run(input, &[], &task.environment)?;
+	let judge = |desired| ready(task.checker.judge(input, desired, &run.stdout));
+	let verdict = block_on(select_verdict(exit(&run), output, output_alt, judge))?;
+	Ok(Outcome { verdict, out: run.stdout, stderr: run.stderr, time: run.time })
+}
+
+fn exit(run: &Run) -> Exit {
+	match run.exit_kind {
+		ExitKind::Normal if run.success() => Exit::Success,
+		ExitKind::Normal => Exit::Failure,
+		ExitKind::TimeLimitExceeded => Exit::TimeLimitExceeded,
+	}
+}
+
+pub fn verdict_color(verdict: Verdict) -> Color {
+	match verdict {
+		Verdict::Accepted { .. } => Color::Green,
+		Verdict::WrongAnswer | Verdict::RuntimeError => Color::Red,
+		Verdict::TimeLimitExceeded => Color::Yellow,
+		Verdict::IgnoredNoOut => Color::Gray,
+	}
+}
+
+fn load_test_output(input_path: &Path, ext: &str) -> R<Option<String>> {
+	let path = input_path.with_extension(ext);
+	if path.exists() {
+		Ok(Some(fs::read_to_string(&path).wrap(format!("could not read test output {}", path.display()))?))
+	} else {
+		Ok(None)
+	}
+}
+
+fn print_test(ctx: &Context, run: &TestRun, desired: Option<&str>) {
+	let verdict = run.outcome.verdict;
+	let name = ctx.workspace.fmt_relative(&run.in_path);
+	let time = fmt_time(&run.outcome.time);
+	let verdict_text = term::paint(verdict_color(verdict), &verdict.to_string());
+	println!("{:<28} {} {}", verdict_text, name, term::paint(Color::Gray, &time));
+	if !verdict.success() && verdict != Verdict::IgnoredNoOut {
+		print_excerpt("output", &run.outcome.out);
+		if let Some(desired) = desired {
+			print_excerpt("desired", desired);
+		}
+		print_excerpt("stderr", &run.outcome.stderr);
+	}
+}
+
+pub fn print_excerpt(title: &str, data: &str) {
+	if data.trim().is_empty() {
+		return;
+	}
+	println!("  {}", term::bold(title));
+	let lines = data.trim_end().lines().collect::<Vec<_>>();
+	for line in lines.iter().take(EXCERPT_LINES) {
+		println!("    {}", line);
+	}
+	if lines.len() > EXCERPT_LINES {
+		println!("    {}", term::paint(Color::Gray, &format!("... {} more lines", lines.len() - EXCERPT_LINES)));
+	}
+}
+
+/// Adds a new test to the given user tests directory and returns the path of its input file.
+pub fn add_test(tests: &Path, input: &str, desired: &str) -> R<PathBuf> {
+	fs::create_dir_all(tests).wrap("could not create the user tests directory")?;
+	let id = unused_test_id(tests)?;
+	let in_path = tests.join(format!("{}.in", id));
+	let out_path = tests.join(format!("{}.out", id));
+	fs::write(&in_path, input).wrap("could not write test input")?;
+	fs::write(&out_path, desired).wrap("could not write test output")?;
+	Ok(in_path)
+}
+
+fn unused_test_id(dir: &Path) -> R<i64> {
+	let taken = fs::read_dir(dir)
+		.wrap("could not list user tests")?
+		.filter_map(|entry| entry.ok())
+		.filter_map(|entry| entry.path().file_stem()?.to_str()?.parse().ok())
+		.collect();
+	Ok(mex(1, taken))
+}
+
+pub fn scan_for_tests(test_dir: &Path) -> R<Vec<PathBuf>> {
+	let mut tests = Vec::new();
+	if test_dir.exists() {
+		scan_unordered(test_dir, &mut tests)?;
+	}
+	tests.sort_by(|a, b| compare_test_paths(&a.display().to_string(), &b.display().to_string()));
+	Ok(tests)
+}
+
+fn scan_unordered(dir: &Path, tests: &mut Vec<PathBuf>) -> R<()> {
+	for entry in fs::read_dir(dir).wrap(format!("could not list tests in {}", dir.display()))? {
+		let path = entry.wrap("could not list tests")?.path();
+		if path.is_dir() {
+			scan_unordered(&path, tests)?;
+		} else if path.extension().map_or(false, |ext| ext == "in") {
+			tests.push(path);
+		}
+	}
+	Ok(())
+}
+
+pub fn fmt_time(t: &Duration) -> String {
+	format!("{}.{:03}s", t.as_secs(), t.as_millis() % 1000)
+}
+
+#[cfg(test)]
+fn scratch_dir(name: &str) -> PathBuf {
+	let dir = std::env::temp_dir().join(format!("icie_cli_{}_{}", name, std::process::id()));
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(&dir).unwrap();
+	dir
+}
+
+#[test]
+fn test_scan_for_tests() {
+	let dir = scratch_dir("scan_for_tests");
+	for path in &["example/1.in", "example/1.out", "example/10.in", "example/2.in", "user/1.in", "user/notes.txt"] {
+		let path = dir.join(path);
+		fs::create_dir_all(path.parent().unwrap()).unwrap();
+		fs::write(path, "").unwrap();
+	}
+	let tests = scan_for_tests(&dir).unwrap();
+	let tests = tests.iter().map(|path| path.strip_prefix(&dir).unwrap().to_str().unwrap().replace('\\', "/"));
+	assert_eq!(tests.collect::<Vec<_>>(), ["example/1.in", "example/2.in", "example/10.in", "user/1.in"]);
+	assert!(scan_for_tests(&dir.join("missing")).unwrap().is_empty());
+	fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_add_test() {
+	let dir = scratch_dir("add_test");
+	let tests = dir.join("user");
+	assert_eq!(add_test(&tests, "1 2\n", "3\n").unwrap(), tests.join("1.in"));
+	assert_eq!(fs::read_to_string(tests.join("1.in")).unwrap(), "1 2\n");
+	assert_eq!(fs::read_to_string(tests.join("1.out")).unwrap(), "3\n");
+	fs::write(tests.join("2.in"), "").unwrap();
+	assert_eq!(add_test(&tests, "", "").unwrap(), tests.join("3.in"));
+	fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,61 @@
+use siphasher::sip::SipHasher13;
+use std::hash::Hasher;
+
+/// Hashes everything that affects a compiled artifact, so that it is only rebuilt when one of the inputs changes. This
+/// uses SipHash-1-3 with fixed keys and feeds it raw bytes instead of relying on `Hash` implementations, so that the
+/// fingerprints saved by one version of ICIE stay valid after upgrading it or the Rust compiler.
+pub struct Fingerprint {
+	hasher: SipHasher13,
+}
+
+const KEYS: (u64, u64) = (0x6963_6965_2d63_6163, 0x6865_2d66_696e_6765);
+
+impl Fingerprint {
+	/// Starts a fingerprint with the compiler identity, which should describe everything apart from the source code
+	/// that affects the artifact, like the compiler version and flags.
+	pub fn new(compiler_identity: &[String]) -> Fingerprint {
+		let mut fingerprint = Fingerprint { hasher: SipHasher13::new_with_keys(KEYS.0, KEYS.1) };
+		fingerprint.write_u64(compiler_identity.len() as u64);
+		for part in compiler_identity {
+			fingerprint.write_str(part);
+		}
+		fingerprint
+	}
+
+	pub fn add_file(&mut self, path: &str, code: &str) {
+		self.write_str(path);
+		self.write_str(code);
+	}
+
+	pub fn finish(&self) -> String {
+		format!("{:016x}", self.hasher.finish())
+	}
+
+	fn write_str(&mut self, s: &str) {
+		self.write_u64(s.len() as u64);
+		self.hasher.write(s.as_bytes());
+	}
+
+	fn write_u64(&mut self, x: u64) {
+		self.hasher.write(&x.to_le_bytes());
+	}
+}
+
+#[test]
+fn test_fingerprint() {
+	let fingerprint = |identity: &[&str], files: &[(&str, &str)]| {
+		let identity = identity.iter().map(|part| (*part).to_owned()).collect::<Vec<_>>();
+		let mut fingerprint = Fingerprint::new(&identity);
+		for (path, code) in files {
+			fingerprint.add_file(path, code);
+		}
+		fingerprint.finish()
+	};
+	let base = fingerprint(&["clang 10", "-O2"], &[("main.cpp", "int main() {}")]);
+	assert_eq!(base, fingerprint(&["clang 10", "-O2"], &[("main.cpp", "int main() {}")]));
+	assert_ne!(base, fingerprint(&["clang 11", "-O2"], &[("main.cpp", "int main() {}")]));
+	assert_ne!(base, fingerprint(&["clang 10", "-O2"], &[("main.cpp", "int main() { }")]));
+	assert_ne!(base, fingerprint(&["clang 10"], &[("-O2main.cpp", "int main() {}")]));
+	// Saved fingerprints must not change between versions, so the exact value is checked too.
+	assert_eq!(base, "cd90578fb0cb2b8f");
+}
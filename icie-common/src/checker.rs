@@ -0,0 +1,32 @@
+/// Compares two outputs, treating every nonempty sequence of whitespace characters as equal. This is the default
+/// checker, used when the task does not have a custom one.
+pub fn equal_bew(a: &str, b: &str) -> bool {
+	let mut i = a.chars().peekable();
+	let mut j = b.chars().peekable();
+	while i.peek().is_some() && j.peek().is_some() {
+		if i.peek().unwrap().is_whitespace() && j.peek().unwrap().is_whitespace() {
+			while i.peek().map(|c| c.is_whitespace()).unwrap_or(false) {
+				i.next();
+			}
+			while j.peek().map(|c| c.is_whitespace()).unwrap_or(false) {
+				j.next();
+			}
+		} else {
+			if i.peek() != j.peek() {
+				return false;
+			}
+			i.next();
+			j.next();
+		}
+	}
+	i.all(char::is_whitespace) && j.all(char::is_whitespace)
+}
+
+#[test]
+fn test_equal_bew() {
+	assert!(equal_bew("1 2 3\n", "1  2\n3"));
+	assert!(equal_bew("YES", "YES\n\n"));
+	assert!(!equal_bew("1 2", "12"));
+	assert!(!equal_bew("YES", "NO"));
+	assert!(!equal_bew("1 2", "1 2 3"));
+}
@@ -0,0 +1,224 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+#[derive(Clone, Copy, Debug, serde::Deserialize, serde::Serialize)]
+pub enum Codegen {
+	Debug,
+	Release,
+	Profile,
+	AddressSanitizer,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "config", derive(evscode::Configurable))]
+pub enum Standard {
+	#[cfg_attr(feature = "config", evscode(name = "C++03"))]
+	Cpp03,
+	#[cfg_attr(feature = "config", evscode(name = "C++11"))]
+	Cpp11,
+	#[cfg_attr(feature = "config", evscode(name = "C++14"))]
+	Cpp14,
+	#[cfg_attr(feature = "config", evscode(name = "C++17"))]
+	Cpp17,
+	#[cfg_attr(feature = "config", evscode(name = "C++20"))]
+	FutureCpp20,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(evscode::Configurable))]
+pub enum Toolchain {
+	#[cfg_attr(feature = "config", evscode(name = "Clang"))]
+	Clang,
+	#[cfg_attr(feature = "config", evscode(name = "GCC"))]
+	Gcc,
+}
+
+/// Compiler settings that only apply to a single task, so that tasks which need e.g. a newer C++ standard or a larger
+/// stack do not require changing the settings shared by all the other tasks. These are stored in the .icie manifest.
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct TaskFlags {
+	/// Names of macros defined when compiling, like "LOCAL" or "N=100".
+	#[serde(default)]
+	pub defines: Vec<String>,
+	/// C++ standard used instead of the configured one.
+	#[serde(default)]
+	pub standard: Option<Standard>,
+	/// Stack size in megabytes.
+	#[serde(default)]
+	pub stack_size: Option<u64>,
+}
+
+// AddressSanitizer also enables LeakSanitizer on Linux, and frame pointers make the stack traces in reports more
+// accurate. _GLIBCXX_DEBUG is not defined, because its checks would abort the program without a stack trace before
+// AddressSanitizer could report the same error.
+const ADDRESS_SANITIZER_FLAGS: &[&str] = &[
+	"-g",
+	"-fno-omit-frame-pointer",
+	"-fno-sanitize-recover=undefined",
+	"-fsanitize=address,undefined",
+];
+
+/// Returns the flags passed to the C++ compiler, other than the ones specifying the source and output files and the
+/// flags added by the user.
+pub fn compiler_flags(standard: Standard, codegen: Codegen, toolchain: Toolchain, windows: bool) -> Vec<&'static str> {
+	let mut flags = vec![standard.flag(toolchain)];
+	// -Wconversion displays warnings on lossy implicit conversions between i32/i64, u32/u64 and
+	// others. These are awful to debug because no small tests trigger them, and using exclusively
+	// i64 can hurt performance too much. -Wno-sign-conversions disables warnings on i32 to u32
+	// conversions, because that happens every time a vector is indexed with an int.
+	flags.extend(&["-Wall", "-Wextra", "-Wconversion", "-Wshadow", "-Wno-sign-conversion"]);
+	flags.extend(codegen.flags(toolchain));
+	if windows {
+		// Sanitizers don't work because -lubsan is not found. There does not seem to be a fix.
+		// Static linking makes it possible to avoid adding MinGW DLLs to PATH.
+		flags.extend(&["-fno-sanitize=all", "-static"]);
+	}
+	flags
+}
+
+/// Returns the flags passed to rustc, other than the edition and the ones specifying the source and output files.
+pub fn rustc_flags(codegen: Codegen) -> &'static [&'static str] {
+	match codegen {
+		// Sanitizers require a nightly Rust compiler, so the AddressSanitizer profile is the same as Debug.
+		Codegen::Debug | Codegen::AddressSanitizer => &["-g", "-C", "debug-assertions=on", "-C", "overflow-checks=on"],
+		Codegen::Release => &["-O"],
+		Codegen::Profile => &["-g", "-O"],
+	}
+}
+
+/// Returns the name of the class with the main method, which has to be passed to the JVM to run a Java program.
+pub fn find_main_class(code: &str) -> Option<String> {
+	static CLASS: Lazy<Regex> = Lazy::new(|| Regex::new("\\bclass\\s+([A-Za-z_$][A-Za-z0-9_$]*)").unwrap());
+	static MAIN: Lazy<Regex> = Lazy::new(|| Regex::new("\\bstatic\\s+void\\s+main\\s*\\(").unwrap());
+	let main = MAIN.find(code)?.start();
+	// Nested classes can appear before the main method too, so only top-level classes are considered.
+	CLASS
+		.captures_iter(&code[..main])
+		.filter(|cap| brace_depth(&code[..cap.get(0).unwrap().start()]) == 0)
+		.last()
+		.map(|cap| cap[1].to_owned())
+}
+
+fn brace_depth(code: &str) -> i64 {
+	code.chars().map(|c| if c == '{' { 1 } else if c == '}' { -1 } else { 0 }).sum()
+}
+
+/// Returns the names of headers included with #include "...", which are usually the local ones.
+pub fn local_includes(code: &str) -> impl Iterator<Item=&str> {
+	code.lines().filter_map(include_directive)
+}
+
+pub fn include_directive(line: &str) -> Option<&str> {
+	static INCLUDE: Lazy<Regex> = Lazy::new(|| Regex::new("^\\s*#\\s*include\\s*\"([^\"]+)\"").unwrap());
+	Some(INCLUDE.captures(line)?.get(1)?.as_str())
+}
+
+impl Codegen {
+	pub const LIST: &'static [Codegen] =
+		&[Codegen::Debug, Codegen::Release, Codegen::Profile, Codegen::AddressSanitizer];
+
+	/// Returns the extension of executables built with this profile, so that switching between profiles does not
+	/// overwrite the previous builds. Debug builds keep the configured extension, because these are the ones users run
+	/// by hand and open in debuggers.
+	pub fn executable_extension(self, extension: &str) -> String {
+		match self {
+			Codegen::Debug => extension.to_owned(),
+			Codegen::Release => format!("release.{}", extension),
+			Codegen::Profile => format!("profile.{}", extension),
+			Codegen::AddressSanitizer => format!("asan.{}", extension),
+		}
+	}
+
+	pub fn flags(self, toolchain: Toolchain) -> &'static [&'static str] {
+		match toolchain {
+			Toolchain::Clang => self.flags_clang(),
+			Toolchain::Gcc => self.flags_gcc(),
+		}
+	}
+
+	pub fn flags_clang(self) -> &'static [&'static str] {
+		match &self {
+			Codegen::Debug => &["-g", "-D_GLIBCXX_DEBUG", "-fno-sanitize-recover=undefined", "-fsanitize=undefined"],
+			Codegen::Release => &["-Ofast"],
+			Codegen::Profile => &["-g", "-O2", "-fno-inline-functions"],
+			Codegen::AddressSanitizer => ADDRESS_SANITIZER_FLAGS,
+		}
+	}
+
+	pub fn flags_gcc(self) -> &'static [&'static str] {
+		// Newer GCC versions warn that -Ofast is deprecated, and judges using GCC almost universally compile with -O2.
+		match &self {
+			Codegen::Debug => &["-g", "-D_GLIBCXX_DEBUG", "-fno-sanitize-recover=undefined", "-fsanitize=undefined"],
+			Codegen::Release => &["-O2"],
+			Codegen::Profile => &["-g", "-O2", "-fno-inline-functions"],
+			Codegen::AddressSanitizer => ADDRESS_SANITIZER_FLAGS,
+		}
+	}
+}
+
+impl Standard {
+	pub const LIST: &'static [Standard] =
+		&[Standard::Cpp03, Standard::Cpp11, Standard::Cpp14, Standard::Cpp17, Standard::FutureCpp20];
+
+	pub fn flag(self, toolchain: Toolchain) -> &'static str {
+		match toolchain {
+			Toolchain::Clang => self.flag_clang(),
+			Toolchain::Gcc => self.flag_gcc(),
+		}
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Standard::Cpp03 => "C++03",
+			Standard::Cpp11 => "C++11",
+			Standard::Cpp14 => "C++14",
+			Standard::Cpp17 => "C++17",
+			Standard::FutureCpp20 => "C++20",
+		}
+	}
+
+	pub fn flag_clang(self) -> &'static str {
+		match self {
+			Standard::Cpp03 => "-std=c++03",
+			Standard::Cpp11 => "-std=c++11",
+			Standard::Cpp14 => "-std=c++14",
+			Standard::Cpp17 => "-std=c++17",
+			Standard::FutureCpp20 => "-std=c++2a",
+		}
+	}
+
+	/// GCC defaults to GNU dialects, which are also what judges like AtCoder use, so extensions such as __int128 type
+	/// traits behave the same way locally.
+	pub fn flag_gcc(self) -> &'static str {
+		match self {
+			Standard::Cpp03 => "-std=gnu++03",
+			Standard::Cpp11 => "-std=gnu++11",
+			Standard::Cpp14 => "-std=gnu++14",
+			Standard::Cpp17 => "-std=gnu++17",
+			Standard::FutureCpp20 => "-std=gnu++2a",
+		}
+	}
+}
+
+#[test]
+fn test_find_main_class() {
+	let code = "import java.util.*;
+
+class Main {
+    static class Pair { int a, b; }
+
+    public static void main(String[] args) {
+    }
+}
+";
+	assert_eq!(find_main_class(code).as_deref(), Some("Main"));
+	let code = "class Helper {}\npublic class A { public static void main(String[] a) {} }";
+	assert_eq!(find_main_class(code).as_deref(), Some("A"));
+	assert_eq!(find_main_class("class A {}"), None);
+}
+
+#[test]
+fn test_local_includes() {
+	let code = "#include <bits/stdc++.h>\n#include \"lib/fenwick.h\"\n  #  include\"util.h\"\nint main() {}\n";
+	assert_eq!(local_includes(code).collect::<Vec<_>>(), ["lib/fenwick.h", "util.h"]);
+}
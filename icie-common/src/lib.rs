@@ -0,0 +1,10 @@
+//! Logic shared by the VS Code extension and the command-line test runner, so that both compile, judge and report
+//! tests in exactly the same way. Nothing here does any IO, which is done differently by each of them.
+//!
+//! Enable the `config` feature to make the compiler options usable in the extension configuration.
+
+pub mod cache;
+pub mod checker;
+pub mod compile;
+pub mod report;
+pub mod test;
@@ -0,0 +1,198 @@
+use crate::test::Verdict;
+use serde::{Serialize, Serializer};
+use std::{fmt::Write, time::Duration};
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+	passed: usize,
+	total: usize,
+	tests: Vec<ReportTest>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportTest {
+	path: String,
+	#[serde(serialize_with = "serialize_verdict")]
+	verdict: Verdict,
+	success: bool,
+	time_ms: u64,
+	output: String,
+	stderr: String,
+}
+
+impl Report {
+	pub fn new(tests: Vec<ReportTest>) -> Report {
+		Report { passed: tests.iter().filter(|test| test.success).count(), total: tests.len(), tests }
+	}
+
+	pub fn json(&self) -> String {
+		serde_json::to_string_pretty(self).unwrap()
+	}
+
+	/// Formats the report as JUnit XML, which can be consumed by most CI systems.
+	pub fn junit(&self) -> String {
+		let failures = self.tests.iter().filter(|test| !test.success && test.verdict != Verdict::IgnoredNoOut).count();
+		let skipped = self.tests.iter().filter(|test| test.verdict == Verdict::IgnoredNoOut).count();
+		let time = self.tests.iter().map(|test| test.time_ms).sum::<u64>();
+		let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+		writeln!(
+			xml,
+			"<testsuite name=\"icie\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{}\">",
+			self.total,
+			failures,
+			skipped,
+			fmt_seconds(time)
+		)
+		.unwrap();
+		for test in &self.tests {
+			write!(xml, "  <testcase name=\"{}\" time=\"{}\"", xml_escape(&test.path), fmt_seconds(test.time_ms))
+				.unwrap();
+			if test.success {
+				xml += "/>\n";
+				continue;
+			}
+			xml += ">\n";
+			if test.verdict == Verdict::IgnoredNoOut {
+				xml += "    <skipped message=\"no output file\"/>\n";
+			} else {
+				writeln!(xml, "    <failure message=\"{}\">{}</failure>", test.verdict.id(), xml_escape(&test.output))
+					.unwrap();
+			}
+			if !test.stderr.is_empty() {
+				writeln!(xml, "    <system-err>{}</system-err>", xml_escape(&test.stderr)).unwrap();
+			}
+			xml += "  </testcase>\n";
+		}
+		xml += "</testsuite>\n";
+		xml
+	}
+}
+
+impl ReportTest {
+	/// Describes a single test, with the path relative to the tests directory and the output and standard error cut
+	/// to at most `excerpt_lines` lines.
+	pub fn new(
+		path: String,
+		verdict: Verdict,
+		time: Duration,
+		output: &str,
+		stderr: &str,
+		excerpt_lines: usize,
+	) -> ReportTest {
+		ReportTest {
+			path,
+			verdict,
+			success: verdict.success(),
+			time_ms: time.as_millis() as u64,
+			output: excerpt(output, excerpt_lines),
+			stderr: excerpt(stderr, excerpt_lines),
+		}
+	}
+}
+
+fn serialize_verdict<S: Serializer>(verdict: &Verdict, serializer: S) -> Result<S::Ok, S::Error> {
+	serializer.serialize_str(verdict.id())
+}
+
+fn excerpt(data: &str, max_lines: usize) -> String {
+	let lines = data.lines().collect::<Vec<_>>();
+	let mut excerpt = lines.iter().take(max_lines).copied().collect::<Vec<_>>().join("\n");
+	if lines.len() > max_lines {
+		write!(excerpt, "\n... {} more lines", lines.len() - max_lines).unwrap();
+	}
+	excerpt
+}
+
+fn fmt_seconds(ms: u64) -> String {
+	format!("{}.{:03}", ms / 1000, ms % 1000)
+}
+
+fn xml_escape(raw: &str) -> String {
+	let mut escaped = String::new();
+	for c in raw.chars() {
+		match c {
+			'&' => escaped += "&amp;",
+			'<' => escaped += "&lt;",
+			'>' => escaped += "&gt;",
+			'"' => escaped += "&quot;",
+			'\'' => escaped += "&apos;",
+			c if c.is_control() && c != '\n' && c != '\t' && c != '\r' => (),
+			c => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+#[test]
+fn test_json() {
+	let report = Report::new(vec![ReportTest::new(
+		"user/1.in".to_owned(),
+		Verdict::WrongAnswer,
+		Duration::from_millis(15),
+		"\"4\"\n\u{1}",
+		"",
+		20,
+	)]);
+	assert_eq!(
+		report.json(),
+		r#"{
+  "passed": 0,
+  "total": 1,
+  "tests": [
+    {
+      "path": "user/1.in",
+      "verdict": "wrong_answer",
+      "success": false,
+      "time_ms": 15,
+      "output": "\"4\"\n\u0001",
+      "stderr": ""
+    }
+  ]
+}"#
+	);
+	assert_eq!(Report::new(Vec::new()).json(), "{\n  \"passed\": 0,\n  \"total\": 0,\n  \"tests\": []\n}");
+}
+
+#[test]
+fn test_junit() {
+	let report = Report::new(vec![
+		ReportTest::new(
+			"example/1.in".to_owned(),
+			Verdict::Accepted { alternative: false },
+			Duration::from_millis(12),
+			"3",
+			"",
+			20,
+		),
+		ReportTest::new(
+			"user/1.in".to_owned(),
+			Verdict::WrongAnswer,
+			Duration::from_millis(1500),
+			"a < b",
+			"warning\u{1}",
+			20,
+		),
+		ReportTest::new("user/2.in".to_owned(), Verdict::IgnoredNoOut, Duration::from_millis(3), "", "", 20),
+	]);
+	assert_eq!(
+		report.junit(),
+		"<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<testsuite name=\"icie\" tests=\"3\" failures=\"1\" skipped=\"1\" time=\"1.515\">
+  <testcase name=\"example/1.in\" time=\"0.012\"/>
+  <testcase name=\"user/1.in\" time=\"1.500\">
+    <failure message=\"wrong_answer\">a &lt; b</failure>
+    <system-err>warning</system-err>
+  </testcase>
+  <testcase name=\"user/2.in\" time=\"0.003\">
+    <skipped message=\"no output file\"/>
+  </testcase>
+</testsuite>
+"
+	);
+}
+
+#[test]
+fn test_excerpt() {
+	assert_eq!(excerpt("1\n2\n3\n", 5), "1\n2\n3");
+	assert_eq!(excerpt("1\n2\n3\n", 2), "1\n2\n... 1 more lines");
+}
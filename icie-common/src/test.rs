@@ -0,0 +1,134 @@
+use std::{cmp::Ordering, fmt, future::Future};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+	Accepted { alternative: bool },
+	WrongAnswer,
+	RuntimeError,
+	TimeLimitExceeded,
+	IgnoredNoOut,
+}
+
+/// How the tested program has finished.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exit {
+	Success,
+	Failure,
+	TimeLimitExceeded,
+}
+
+#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum Word<'a> {
+	Text(&'a str),
+	Number(i64),
+}
+
+impl Verdict {
+	pub fn success(self) -> bool {
+		matches!(self, Verdict::Accepted { .. })
+	}
+
+	/// Returns the identifier used in test reports.
+	pub fn id(self) -> &'static str {
+		match self {
+			Verdict::Accepted { alternative: false } => "accepted",
+			Verdict::Accepted { alternative: true } => "accepted_alternative",
+			Verdict::WrongAnswer => "wrong_answer",
+			Verdict::RuntimeError => "runtime_error",
+			Verdict::TimeLimitExceeded => "time_limit_exceeded",
+			Verdict::IgnoredNoOut => "ignored",
+		}
+	}
+}
+
+impl fmt::Display for Verdict {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let message = match self {
+			Verdict::Accepted { .. } => "Accept",
+			Verdict::WrongAnswer => "Wrong Answer",
+			Verdict::RuntimeError => "Runtime Error",
+			Verdict::TimeLimitExceeded => "Time Limit Exceeded",
+			Verdict::IgnoredNoOut => "Ignored (no output file)",
+		};
+		write!(f, "{}", message)
+	}
+}
+
+/// Selects the verdict of a test. The `judge` function checks the program output against the given desired output,
+/// and is only called if the program has finished successfully. The alternative output is only tried if the main one
+/// does not match.
+pub async fn select_verdict<'a, E, F>(
+	exit: Exit,
+	output: Option<&'a str>,
+	output_alt: Option<&'a str>,
+	judge: impl Fn(&'a str) -> F,
+) -> Result<Verdict, E>
+where
+	F: Future<Output=Result<bool, E>>,
+{
+	Ok(match (exit, output) {
+		(Exit::TimeLimitExceeded, _) => Verdict::TimeLimitExceeded,
+		(Exit::Failure, _) => Verdict::RuntimeError,
+		(Exit::Success, None) => Verdict::IgnoredNoOut,
+		(Exit::Success, Some(output)) => {
+			if judge(output).await? {
+				Verdict::Accepted { alternative: false }
+			} else if let Some(output_alt) = output_alt {
+				if judge(output_alt).await? {
+					Verdict::Accepted { alternative: true }
+				} else {
+					Verdict::WrongAnswer
+				}
+			} else {
+				Verdict::WrongAnswer
+			}
+		},
+	})
+}
+
+/// Orders test paths so that numbers are compared by value, e.g. 2.in goes before 10.in.
+pub fn compare_test_paths(a: &str, b: &str) -> Ordering {
+	split_words(a).cmp(&split_words(b))
+}
+
+fn split_words(mut path: &str) -> Vec<Word<'_>> {
+	let mut words = Vec::new();
+	while !path.is_empty() {
+		let end_of_number = path.find(|c: char| !c.is_ascii_digit()).unwrap_or(path.len());
+		let end_of_word = path.find(|c: char| c.is_ascii_digit()).unwrap_or(path.len());
+		let word = match path[..end_of_number].parse() {
+			Ok(number) => Word::Number(number),
+			Err(_) => Word::Text(&path[..end_of_number.max(end_of_word)]),
+		};
+		path = &path[end_of_number.max(end_of_word)..];
+		words.push(word);
+	}
+	words
+}
+
+/// Returns the smallest number not smaller than `x0` which does not appear in `xs`, used for naming new tests.
+pub fn mex(x0: i64, mut xs: Vec<i64>) -> i64 {
+	xs.sort_unstable();
+	xs.dedup();
+	for (i, x) in xs.iter().enumerate() {
+		if x0 + i as i64 != *x {
+			return x0 + i as i64;
+		}
+	}
+	x0 + xs.len() as i64
+}
+
+#[test]
+fn test_compare_test_paths() {
+	let mut paths = vec!["tests/user/10.in", "tests/example/2.in", "tests/user/2.in", "tests/example/1.in"];
+	paths.sort_by(|a, b| compare_test_paths(a, b));
+	assert_eq!(paths, ["tests/example/1.in", "tests/example/2.in", "tests/user/2.in", "tests/user/10.in"]);
+}
+
+#[test]
+fn test_mex() {
+	assert_eq!(mex(0, vec![5, 3, 2, 0, 1]), 4);
+	assert_eq!(mex(0, vec![]), 0);
+	assert_eq!(mex(5, vec![10, 5, 7, 9, 8]), 6);
+	assert_eq!(mex(5, vec![]), 5);
+}
@@ -152,6 +152,8 @@ pub mod fs {
 		#[wasm_bindgen(js_name = readlink)]
 		pub fn read_link(path: &str, callback: JsValue);
 
+		pub fn rename(old_path: &str, new_path: &str, callback: JsValue);
+
 		pub fn stat(path: &str, options: StatOptions, callback: JsValue);
 
 		pub fn unlink(path: &str, callback: JsValue);
@@ -0,0 +1,251 @@
+use crate::{
+	compile::Language, dir, util::{self, fs, path::Path}
+};
+use evscode::R;
+use icie_common::compile::include_directive;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::{
+	collections::{HashMap, HashSet}, iter
+};
+
+pub use icie_common::compile::local_includes;
+
+/// Directories searched for headers included with #include "...", after the directory of the including file. They are
+/// passed to the compiler as -I flags, and headers found in them are pasted into the solution when submitting. Paths
+/// starting with ~ are relative to the home directory.
+#[evscode::config]
+static INCLUDE_PATHS: evscode::Config<Vec<String>> = Vec::new();
+
+/// Replace #include "..." directives with the contents of the included headers when submitting, so that solutions can
+/// use libraries kept in separate files. Headers with #pragma once or an include guard are pasted only once.
+#[evscode::config]
+static ENABLED: evscode::Config<bool> = true;
+
+/// Remove comments from the submitted source, including the comments in pasted headers.
+#[evscode::config]
+static STRIP_COMMENTS: evscode::Config<bool> = false;
+
+#[evscode::command(title = "ICIE Preview bundled source")]
+async fn preview() -> R<()> {
+	let _status = crate::STATUS.push("Bundling");
+	let solution = dir::solution().await?;
+	let code = bundle(&solution).await?;
+	evscode::open_untitled(&code, "cpp").await
+}
+
+/// Returns the code that should be submitted, with local headers pasted in if the solution is written in C++.
+pub async fn submitted_code(source: &Path, language: Language) -> R<String> {
+	if language == Language::Cpp && ENABLED.get() {
+		bundle(source).await
+	} else {
+		fs::read_to_string(source).await
+	}
+}
+
+/// Returns the source with every local header pasted in place of the directive that includes it.
+pub async fn bundle(source: &Path) -> R<String> {
+	let files = load(source).await?;
+	let mut bundler = Bundler { files: &files, pasted: HashSet::new(), stack: Vec::new(), output: String::new() };
+	bundler.paste(source.as_str());
+	Ok(if STRIP_COMMENTS.get() { strip_comments(&bundler.output) } else { bundler.output })
+}
+
+pub fn include_flags() -> Vec<String> {
+	include_paths().iter().map(|path| format!("-I{}", path.as_str())).collect()
+}
+
+/// Finds the header included with a given name, looking in the directory of the including file first and then in the
+/// configured include paths.
+pub async fn resolve(including: &Path, name: &str) -> R<Option<Path>> {
+	for directory in iter::once(including.parent()).chain(include_paths()) {
+		let path = directory.join(name);
+		if fs::exists(&path).await? {
+			return Ok(Some(path));
+		}
+	}
+	Ok(None)
+}
+
+fn include_paths() -> Vec<Path> {
+	INCLUDE_PATHS.get().iter().map(|path| util::expand_path(path)).collect()
+}
+
+struct File {
+	code: String,
+	/// Paths of the included local headers, keyed by the names used in #include directives.
+	includes: HashMap<String, String>,
+}
+
+async fn load(source: &Path) -> R<HashMap<String, File>> {
+	let mut files = HashMap::new();
+	let mut queue = vec![source.clone()];
+	while let Some(path) = queue.pop() {
+		if files.contains_key(path.as_str()) {
+			continue;
+		}
+		let code = fs::read_to_string(&path).await?;
+		let mut includes = HashMap::new();
+		for name in local_includes(&code) {
+			// Headers that were not found are left as they are, because they may be system headers.
+			if let Some(header) = resolve(&path, name).await? {
+				includes.insert(name.to_owned(), header.as_str().to_owned());
+				queue.push(header);
+			}
+		}
+		files.insert(path.into_string(), File { code, includes });
+	}
+	Ok(files)
+}
+
+struct Bundler<'a> {
+	files: &'a HashMap<String, File>,
+	pasted: HashSet<&'a str>,
+	stack: Vec<&'a str>,
+	output: String,
+}
+
+impl<'a> Bundler<'a> {
+	fn paste(&mut self, path: &'a str) {
+		let file = &self.files[path];
+		let include_once = has_pragma_once(&file.code) || has_include_guard(&file.code);
+		// Headers that include each other without a guard would not compile anyway, but should not hang ICIE.
+		if (include_once && self.pasted.contains(path)) || self.stack.contains(&path) {
+			return;
+		}
+		self.pasted.insert(path);
+		self.stack.push(path);
+		for line in file.code.lines() {
+			if let Some(header) = include_directive(line).and_then(|name| file.includes.get(name)) {
+				self.paste(header);
+			} else if !is_pragma_once(line) {
+				self.output += line;
+				self.output += "\n";
+			}
+		}
+		self.stack.pop();
+	}
+}
+
+pub fn is_pragma_once(line: &str) -> bool {
+	static PRAGMA_ONCE: Lazy<Regex> = Lazy::new(|| Regex::new("^\\s*#\\s*pragma\\s+once\\s*$").unwrap());
+	PRAGMA_ONCE.is_match(line)
+}
+
+fn has_pragma_once(code: &str) -> bool {
+	code.lines().any(is_pragma_once)
+}
+
+fn has_include_guard(code: &str) -> bool {
+	static IFNDEF: Lazy<Regex> = Lazy::new(|| Regex::new("^#\\s*ifndef\\s+(\\w+)$").unwrap());
+	static DEFINE: Lazy<Regex> = Lazy::new(|| Regex::new("^#\\s*define\\s+(\\w+)$").unwrap());
+	let mut lines = code.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with("//"));
+	match (lines.next().and_then(|line| IFNDEF.captures(line)), lines.next().and_then(|line| DEFINE.captures(line))) {
+		(Some(ifndef), Some(define)) => ifndef[1] == define[1],
+		_ => false,
+	}
+}
+
+/// Removes line and block comments, skipping over string and character literals. Lines that only contained comments
+/// are removed entirely.
+fn strip_comments(code: &str) -> String {
+	let mut output = String::new();
+	let mut chars = code.chars().peekable();
+	let mut line_had_comment = false;
+	while let Some(c) = chars.next() {
+		match c {
+			'/' if chars.peek() == Some(&'/') => {
+				while chars.peek().map_or(false, |c| *c != '\n') {
+					chars.next();
+				}
+				line_had_comment = true;
+			},
+			'/' if chars.peek() == Some(&'*') => {
+				chars.next();
+				let mut last = ' ';
+				for c in &mut chars {
+					if last == '*' && c == '/' {
+						break;
+					}
+					last = c;
+				}
+				// Comments separate tokens, so "a/**/b" is not the same as "ab".
+				if output.chars().last().map_or(false, |last| !last.is_whitespace()) {
+					output.push(' ');
+				}
+				while chars.peek().map_or(false, |c| *c == ' ' || *c == '\t') {
+					chars.next();
+				}
+				line_had_comment = true;
+			},
+			// Apostrophes are also used as digit separators, like in 1'000'000.
+			'"' | '\'' if c == '"' || !output.chars().last().map_or(false, char::is_alphanumeric) => {
+				output.push(c);
+				while let Some(d) = chars.next() {
+					output.push(d);
+					if d == '\\' {
+						output.extend(chars.next());
+					} else if d == c || d == '\n' {
+						break;
+					}
+				}
+			},
+			'\n' => {
+				if line_had_comment {
+					let trimmed = output.trim_end_matches(|c| c == ' ' || c == '\t').len();
+					output.truncate(trimmed);
+					let line_start = output.rfind('\n').map_or(0, |i| i + 1);
+					if output.len() == line_start {
+						line_had_comment = false;
+						continue;
+					}
+				}
+				output.push('\n');
+				line_had_comment = false;
+			},
+			_ => output.push(c),
+		}
+	}
+	output
+}
+
+#[cfg(test)]
+fn test_files(files: &[(&str, &str, &[(&str, &str)])]) -> HashMap<String, File> {
+	files
+		.iter()
+		.map(|(path, code, includes)| {
+			let includes = includes.iter().map(|(name, path)| ((*name).to_owned(), (*path).to_owned())).collect();
+			((*path).to_owned(), File { code: (*code).to_owned(), includes })
+		})
+		.collect()
+}
+
+#[test]
+fn test_bundle() {
+	let files = test_files(&[
+		("/t/main.cpp", "#include <bits/stdc++.h>\n#include \"lib/a.h\"\n#include \"b.h\"\nint main() {}\n", &[
+			("lib/a.h", "/lib/a.h"),
+			("b.h", "/lib/b.h"),
+		]),
+		("/lib/a.h", "#pragma once\n#include \"b.h\"\nint a;\n", &[("b.h", "/lib/b.h")]),
+		("/lib/b.h", "// B\n#ifndef B_H\n#define B_H\nint b;\n#endif\n", &[]),
+	]);
+	let mut bundler = Bundler { files: &files, pasted: HashSet::new(), stack: Vec::new(), output: String::new() };
+	bundler.paste("/t/main.cpp");
+	assert_eq!(
+		bundler.output,
+		"#include <bits/stdc++.h>\n// B\n#ifndef B_H\n#define B_H\nint b;\n#endif\nint a;\nint main() {}\n"
+	);
+}
+
+#[test]
+fn test_local_includes() {
+	let code = "#include <bits/stdc++.h>\n#include \"lib/fenwick.h\"\n  # include \"dsu.h\"\n// #include \"no.h\"\n";
+	assert_eq!(local_includes(code).collect::<Vec<_>>(), ["lib/fenwick.h", "dsu.h"]);
+}
+
+#[test]
+fn test_strip_comments() {
+	let code = "// Header\nint a = 1'000; // big\nchar c = '/'; /* block\ncomment */ int b;\nauto s = \"// no\";\n";
+	assert_eq!(strip_comments(code), "int a = 1'000;\nchar c = '/'; int b;\nauto s = \"// no\";\n");
+}
@@ -3,6 +3,7 @@ use crate::{
 };
 use async_trait::async_trait;
 use evscode::R;
+use icie_common::checker::equal_bew;
 use std::{fmt, time::Duration};
 
 /// The maximum time a checker executable can run before getting killed, specified in milliseconds. Killing will cause
@@ -12,7 +13,7 @@ use std::{fmt, time::Duration};
 static TIME_LIMIT: evscode::Config<Option<u64>> = Some(1500);
 
 pub async fn get_checker() -> R<Box<dyn Checker+Send+Sync>> {
-	let checker = dir::checker()?;
+	let checker = dir::checker().await?;
 	Ok(if !fs::exists(&checker).await? {
 		let bx: Box<dyn Checker+Send+Sync> = Box::new(FreeWhitespaceChecker);
 		bx
@@ -34,41 +35,7 @@ pub struct FreeWhitespaceChecker;
 #[async_trait(?Send)]
 impl Checker for FreeWhitespaceChecker {
 	async fn judge(&self, _input: &str, desired: &str, out: &str) -> R<bool> {
-		Ok(self.equal_bew(desired, out))
-	}
-}
-
-impl FreeWhitespaceChecker {
-	fn equal_bew(&self, a: &str, b: &str) -> bool {
-		let mut i = a.chars().peekable();
-		let mut j = b.chars().peekable();
-		while i.peek().is_some() && j.peek().is_some() {
-			if i.peek().unwrap().is_whitespace() && j.peek().unwrap().is_whitespace() {
-				while i.peek().map(|c| c.is_whitespace()).unwrap_or(false) {
-					i.next();
-				}
-				while j.peek().map(|c| c.is_whitespace()).unwrap_or(false) {
-					j.next();
-				}
-			} else {
-				if i.peek() != j.peek() {
-					return false;
-				}
-				i.next();
-				j.next();
-			}
-		}
-		for c in i {
-			if !c.is_whitespace() {
-				return false;
-			}
-		}
-		for c in j {
-			if !c.is_whitespace() {
-				return false;
-			}
-		}
-		true
+		Ok(equal_bew(desired, out))
 	}
 }
 
@@ -1,16 +1,27 @@
+mod cache;
 mod clang;
-mod options;
+mod jvm;
+mod language;
+mod pch;
+mod python;
+mod rustc;
 
 use crate::{
-	dir, executable::{Executable, Run}, template, util::{self, fs, path::Path, suggest_open, workspace_root, Tempfile}
+	bundle, dir, executable::{Executable, Run}, manifest::{Manifest, TaskFlags}, template, util::{self, fs, path::Path, suggest_open, tempfile::is_temporary, workspace_root, Tempfile, OS}
 };
 use evscode::{
-	error::Severity, quick_pick, state::Scope, stdlib::output_channel::OutputChannel, Position, QuickPick, State, E, R
+	error::{ResultExt, Severity}, quick_pick, state::Scope, stdlib::{
+		diagnostics::{Diagnostic, Related, Severity as DiagnosticSeverity}, output_channel::OutputChannel
+	}, DiagnosticCollection, Position, QuickPick, Range, State, E, R
 };
 use once_cell::sync::Lazy;
+use regex::Regex;
+use std::{cell::RefCell, collections::HashMap};
 
 use crate::util::SourceTarget;
-pub use options::{Codegen, Standard};
+use icie_common::compile::rustc_flags;
+pub use icie_common::compile::{Codegen, Standard, Toolchain};
+pub use language::Language;
 
 #[derive(Debug)]
 pub struct Location {
@@ -23,6 +34,8 @@ pub struct Location {
 pub struct Message {
 	pub message: String,
 	pub location: Option<Location>,
+	/// Additional messages explaining this one, like "in instantiation of ..." notes.
+	pub notes: Vec<Message>,
 }
 
 #[derive(Debug)]
@@ -42,7 +55,8 @@ pub const SOURCE_EXTENSIONS: &[&str] = &["cpp", "cxx", "cc"];
 static AUTO_MOVE_TO_ERROR: vscode::Config<bool> = true;
 
 /// An extension used to denote executable files. For example, if this entry is set to "xyz",
-/// compiling a source file called main.cpp will create an executable called main.xyz.
+/// compiling a source file called main.cpp will create an executable called main.xyz. Release and
+/// Profile builds are stored separately, in main.release.xyz and main.profile.xyz.
 #[evscode::config]
 static EXECUTABLE_EXTENSION: evscode::Config<String> = "e";
 
@@ -52,6 +66,18 @@ static EXECUTABLE_EXTENSION: evscode::Config<String> = "e";
 #[evscode::config]
 static CPP_STANDARD: evscode::Config<Standard> = Standard::Cpp17;
 
+/// C++ compiler used to build solutions. GCC is what most judges use, so it may be a better choice when relying on
+/// GCC-specific extensions like __gnu_pbds. On Windows, MinGW is always used, and selecting GCC makes it use the same
+/// flags as GCC does on other systems, like -O2 and GNU dialects of C++.
+#[evscode::config]
+static COMPILER: evscode::Config<Toolchain> = Toolchain::Clang;
+
+/// Custom path of the C++ compiler executable, used instead of searching for clang++ or g++ in PATH. The flags passed
+/// to it are selected according to the icie.compile.compiler entry, so make sure it matches the compiler kind. For
+/// example, this can be set to "/usr/bin/g++-10" to use a specific version of GCC.
+#[evscode::config]
+static CUSTOM_COMPILER_PATH: evscode::Config<String> = "";
+
 /// Additional C++ compilation flags. The flags will be appended to the command line after the
 /// standard, warning, debug symbols and optimization flags. These flags will be used both in Debug
 /// and Release profiles.
@@ -76,6 +102,12 @@ static ADDITIONAL_CPP_FLAGS_RELEASE: evscode::Config<String> = "";
 #[evscode::config]
 static ADDITIONAL_CPP_FLAGS_PROFILE: evscode::Config<String> = "";
 
+/// Additional C++ compilation flags used in AddressSanitizer profile. The flags will be appended to
+/// the command line after the standard, warning, debug symbols, sanitizer flags and
+/// profile-independent custom flags.
+#[evscode::config]
+static ADDITIONAL_CPP_FLAGS_ADDRESS_SANITIZER: evscode::Config<String> = "";
+
 /// Custom path of your MinGW installation. If not set, ICIE will try, in order, "C:\MinGW" and
 /// "C:\MinGW\mingw32".
 #[evscode::config]
@@ -93,11 +125,46 @@ async fn manual() -> R<()> {
 	Ok(())
 }
 
+#[evscode::command(title = "ICIE Task compiler flags")]
+async fn edit_task_flags() -> R<()> {
+	let mut manifest = Manifest::load().await?;
+	let defines = evscode::InputBox::new()
+		.prompt("Macros defined when compiling this task, separated by spaces")
+		.placeholder("LOCAL N=100")
+		.value(&manifest.flags.defines.join(" "))
+		.ignore_focus_out()
+		.show()
+		.await
+		.ok_or_else(E::cancel)?;
+	let standard = QuickPick::new()
+		.ignore_focus_out()
+		.placeholder("C++ standard used for this task")
+		.item(quick_pick::Item::new(None, format!("Default ({})", CPP_STANDARD.get().name())))
+		.items(Standard::LIST.iter().map(|standard| quick_pick::Item::new(Some(*standard), standard.name().to_owned())))
+		.show()
+		.await
+		.ok_or_else(E::cancel)?;
+	let stack_size = evscode::InputBox::new()
+		.prompt("Stack size in megabytes, or leave empty to use the default")
+		.value(&manifest.flags.stack_size.map_or(String::new(), |stack_size| stack_size.to_string()))
+		.ignore_focus_out()
+		.show()
+		.await
+		.ok_or_else(E::cancel)?;
+	let stack_size = match stack_size.trim() {
+		"" => None,
+		stack_size => Some(stack_size.parse().wrap(format!("{} is not a valid number of megabytes", stack_size))?),
+	};
+	let defines = defines.split_whitespace().map(str::to_owned).collect();
+	manifest.flags = TaskFlags { defines, standard, stack_size };
+	manifest.save(&workspace_root()?).await
+}
+
 async fn collect_possible_sources() -> R<Vec<Path>> {
 	Ok(fs::read_dir(&workspace_root()?)
 		.await?
 		.into_iter()
-		.filter(|path| SOURCE_EXTENSIONS.iter().any(|ext| Some(*ext) == path.extension().as_deref()))
+		.filter(|path| Language::from_path(path).is_some())
 		.collect())
 }
 
@@ -110,7 +177,7 @@ async fn select_source(sources: &[Path]) -> R<Path> {
 async fn select_codegen() -> R<Codegen> {
 	let items = Codegen::LIST.iter().map(|codegen| {
 		let label = format!("{:?}", codegen);
-		let description = codegen.flags_clang().join(" ");
+		let description = codegen.flags(toolchain()).join(" ");
 		quick_pick::Item::new(*codegen, label).description(description)
 	});
 	let codegen = QuickPick::new().ignore_focus_out().match_on_all().items(items).show().await.ok_or_else(E::cancel)?;
@@ -121,42 +188,147 @@ pub async fn compile(source: &SourceTarget, codegen: Codegen, force: bool) -> R<
 	let _status = crate::STATUS.push(util::fmt::verb_on_source("Compiling", source));
 	evscode::save_all().await?;
 	check_source_exists(source).await?;
-	let source = source.to_path()?;
-	let output_path = source.with_extension(&*EXECUTABLE_EXTENSION.get());
-	if !force && should_cache(&source, &output_path).await? {
-		return Ok(Executable::new(output_path));
-	}
-	let sources = [&source];
-	let standard = CPP_STANDARD.get();
-	let custom_flags = get_custom_flags(codegen);
-	let status = clang::compile(&sources, &output_path, standard, codegen, &custom_flags).await?;
+	let source = source.to_path().await?;
+	let language = Language::from_path(&source)
+		.wrap(format!("{} is not written in any of the supported languages", source.fmt_workspace()))?;
+	let output_path = language.artifact(&source, &EXECUTABLE_EXTENSION.get(), codegen).await?;
+	let task = task_flags().await;
+	// Temporary sources, like the one used to check whether the compiler works, are only compiled once, so their
+	// fingerprints would only pile up in the workspace state.
+	let fingerprint = if language.is_compiled() && !is_temporary(&source) {
+		let identity = compiler_identity(language, codegen, &task).await?;
+		let fingerprint = cache::fingerprint(&source, language == Language::Cpp, &identity).await?;
+		if !force && cache::is_fresh(&output_path, &fingerprint).await? {
+			return language.executable(&source, &output_path).await;
+		}
+		Some(fingerprint)
+	} else {
+		None
+	};
+	let status = match language {
+		Language::Cpp => {
+			let sources = [&source];
+			let standard = task.standard.unwrap_or_else(|| CPP_STANDARD.get());
+			let custom_flags = get_custom_flags(codegen, &task)?;
+			clang::compile(&sources, &output_path, standard, codegen, &custom_flags).await?
+		},
+		Language::Python => python::compile(&source).await?,
+		Language::Rust => rustc::compile(&source, &output_path, codegen).await?,
+		Language::Java => jvm::compile_java(&source, &output_path).await?,
+		Language::Kotlin => jvm::compile_kotlin(&source, &output_path).await?,
+	};
 	display_compiler_stderr(&status.run.stderr);
+	publish_diagnostics(&source, &status);
 	check_compiler_errors(&status).await?;
 	COMPILER_INSTALL_CONFIRMED.set(&true).await;
+	if let Some(fingerprint) = fingerprint {
+		cache::save(&output_path, &fingerprint).await?;
+	}
 	Ok(status.executable)
 }
 
-async fn should_cache(source: &Path, out: &Path) -> R<bool> {
-	Ok(fs::exists(out).await? && !is_newer(source, out).await?)
+/// Compiles the solution and sets the stack size configured for the task. Only the solution runs with the raised limit,
+/// checkers, brute force solutions and test generators keep the default one.
+pub async fn compile_solution(source: &SourceTarget, codegen: Codegen) -> R<Executable> {
+	let executable = compile(source, codegen, false).await?;
+	Ok(executable.with_stack_size(stack_size().await))
+}
+
+/// Returns the stack size configured for the task in bytes.
+pub async fn stack_size() -> Option<u64> {
+	task_flags().await.stack_size.map(|megabytes| megabytes * 1024 * 1024)
 }
 
-async fn is_newer(new: &Path, old: &Path) -> R<bool> {
-	let new_meta = fs::metadata(new).await?;
-	let old_meta = fs::metadata(old).await?;
-	Ok(new_meta.modified > old_meta.modified)
+/// Describes everything apart from the source code that affects the compiled artifact, so that changing the compiler
+/// or any of the flags causes a recompilation.
+async fn compiler_identity(language: Language, codegen: Codegen, task: &TaskFlags) -> R<Vec<String>> {
+	Ok(match language {
+		Language::Cpp => {
+			let toolchain = toolchain();
+			let standard = task.standard.unwrap_or_else(|| CPP_STANDARD.get());
+			let mut identity = vec![clang::version().await?, standard.flag(toolchain).to_owned()];
+			identity.extend(codegen.flags(toolchain).iter().map(|flag| (*flag).to_owned()));
+			identity.extend(get_custom_flags(codegen, task)?);
+			identity
+		},
+		Language::Rust => {
+			let mut identity = vec![rustc::version().await?];
+			identity.extend(rustc_flags(codegen).iter().map(|flag| (*flag).to_owned()));
+			identity
+		},
+		Language::Java => vec![jvm::javac_version().await?],
+		Language::Kotlin => vec![jvm::kotlinc_version().await?],
+		Language::Python => Vec::new(),
+	})
 }
 
-fn get_custom_flags(codegen: Codegen) -> Vec<String> {
+fn get_custom_flags(codegen: Codegen, task: &TaskFlags) -> R<Vec<String>> {
 	let flags = format!("{} {}", ADDITIONAL_CPP_FLAGS.get(), match codegen {
 		Codegen::Debug => ADDITIONAL_CPP_FLAGS_DEBUG.get(),
 		Codegen::Release => ADDITIONAL_CPP_FLAGS_RELEASE.get(),
 		Codegen::Profile => ADDITIONAL_CPP_FLAGS_PROFILE.get(),
+		Codegen::AddressSanitizer => ADDITIONAL_CPP_FLAGS_ADDRESS_SANITIZER.get(),
 	});
-	flags.split(' ').map(|flag| flag.trim().to_owned()).filter(|flag| !flag.is_empty()).collect::<Vec<_>>()
+	let mut custom_flags = bundle::include_flags();
+	custom_flags.extend(flags.split(' ').map(|flag| flag.trim().to_owned()).filter(|flag| !flag.is_empty()));
+	custom_flags.extend(task.defines.iter().map(|define| format!("-D{}", define)));
+	if let Some(stack_size) = task.stack_size {
+		custom_flags.extend(stack_size_flag(stack_size * 1024 * 1024)?);
+	}
+	Ok(custom_flags)
+}
+
+/// Loads the compiler settings of the currently open task. Sources compiled outside of a task use the global settings.
+async fn task_flags() -> TaskFlags {
+	Manifest::load().await.map(|manifest| manifest.flags).unwrap_or_default()
+}
+
+/// Returns a linker flag that sets the stack size of the main thread. Linux ignores the size stored in the executable
+/// and uses the limit set with ulimit -s instead, so there it is set when running the program, see
+/// [`Executable::stack_size`].
+fn stack_size_flag(bytes: u64) -> R<Option<String>> {
+	Ok(match OS::query()? {
+		OS::Windows => Some(format!("-Wl,--stack,{}", bytes)),
+		OS::MacOS => Some(format!("-Wl,-stack_size,{:#x}", bytes)),
+		OS::Linux => None,
+	})
+}
+
+/// Parses diagnostics in the `path:line:column: severity: message` format, used by GCC, Clang, javac and kotlinc. The
+/// regex must capture the path, line, optional column, severity and message, in this order. Notes are attached to the
+/// error or warning that precedes them.
+fn parse_located_messages(stderr: &str, regex: &Regex) -> (Vec<Message>, Vec<Message>) {
+	let mut errors = Vec::new();
+	let mut warnings = Vec::new();
+	let mut last_is_error = None;
+	for cap in regex.captures_iter(stderr) {
+		let path = Path::from_native(cap[1].to_owned());
+		let line = cap[2].parse().unwrap();
+		let column = cap.get(3).map_or(1, |column| column.as_str().parse().unwrap());
+		let severity = &cap[4];
+		let location = Some(Location { path, line, column });
+		let message = Message { message: cap[5].to_owned(), location, notes: Vec::new() };
+		if severity == "note" {
+			let last = match last_is_error {
+				Some(true) => errors.last_mut(),
+				Some(false) => warnings.last_mut(),
+				None => None,
+			};
+			if let Some(last) = last {
+				last.notes.push(message);
+			}
+			continue;
+		}
+		let is_error = severity == "error" || severity == "fatal error";
+		let severity_list = if is_error { &mut errors } else { &mut warnings };
+		severity_list.push(message);
+		last_is_error = Some(is_error);
+	}
+	(errors, warnings)
 }
 
 async fn check_source_exists(source: &SourceTarget) -> R<()> {
-	let path = source.to_path()?;
+	let path = source.to_path().await?;
 	if fs::exists(&path).await? {
 		Ok(())
 	} else {
@@ -165,10 +337,10 @@ async fn check_source_exists(source: &SourceTarget) -> R<()> {
 		error = match source {
 			SourceTarget::Main => suggest_open(error),
 			SourceTarget::BruteForce => error.action("Create brute force (Alt++)", async move {
-				template::write(&dir::brute_force()?, &template::load_brute_force().await?).await
+				template::write(&dir::brute_force().await?, &template::load_brute_force().await?).await
 			}),
 			SourceTarget::TestGenerator => error.action("Create test generator (Alt++)", async move {
-				template::write(&dir::test_generator()?, &template::load_test_generator().await?).await
+				template::write(&dir::test_generator().await?, &template::load_test_generator().await?).await
 			}),
 			SourceTarget::Custom(_) => error.action("Create (Alt++)", crate::template::instantiate()),
 		};
@@ -191,6 +363,54 @@ fn display_compiler_stderr(stderr: &str) {
 	});
 }
 
+/// Shows errors and warnings in the editor and the Problems panel, replacing the ones from previous compilations of the
+/// same source. Diagnostics of other sources are kept, so that e.g. running a stress test does not hide the warnings in
+/// the solution.
+fn publish_diagnostics(source: &Path, status: &Status) {
+	thread_local! {
+		static DIAGNOSTICS: Lazy<DiagnosticCollection> = Lazy::new(|| DiagnosticCollection::new("ICIE"));
+		/// Files that got diagnostics from the last compilation of each source, including the headers it includes.
+		static PUBLISHED: RefCell<HashMap<String, Vec<String>>> = RefCell::new(HashMap::new());
+	}
+	let mut files: HashMap<&str, Vec<Diagnostic>> = HashMap::new();
+	let all = status.errors.iter().map(|error| (error, DiagnosticSeverity::Error));
+	let all = all.chain(status.warnings.iter().map(|warning| (warning, DiagnosticSeverity::Warning)));
+	for (message, severity) in all {
+		if let Some(location) = &message.location {
+			let related = message
+				.notes
+				.iter()
+				.filter_map(|note| {
+					let location = note.location.as_ref()?;
+					let range = location_range(location);
+					Some(Related { path: location.path.as_str().to_owned(), range, message: note.message.clone() })
+				})
+				.collect();
+			let range = location_range(location);
+			let diagnostic = Diagnostic { range, message: message.message.clone(), severity, related };
+			files.entry(location.path.as_str()).or_default().push(diagnostic);
+		}
+	}
+	let published = files.keys().map(|path| (*path).to_owned()).collect();
+	let mut previous = PUBLISHED
+		.with(|sources| sources.borrow_mut().insert(source.as_str().to_owned(), published))
+		.unwrap_or_default();
+	previous.push(source.as_str().to_owned());
+	DIAGNOSTICS.with(|collection| {
+		for path in previous.iter().filter(|path| !files.contains_key(path.as_str())) {
+			collection.delete(path);
+		}
+		for (path, diagnostics) in &files {
+			collection.set(path, diagnostics);
+		}
+	});
+}
+
+fn location_range(location: &Location) -> Range {
+	let position = || Position { line: location.line.saturating_sub(1), column: location.column.saturating_sub(1) };
+	Range { start: position(), end: position() }
+}
+
 async fn check_compiler_errors(status: &Status) -> R<()> {
 	if status.run.success() {
 		Ok(())
@@ -206,7 +426,7 @@ async fn try_move_cursor_to_error(error: &Message) -> R<()> {
 	if let Some(location) = &error.location {
 		if AUTO_MOVE_TO_ERROR.get() {
 			evscode::open_editor(location.path.as_str())
-				.cursor(Position { line: location.line - 1, column: location.column - 1 })
+				.cursor(Position { line: location.line.saturating_sub(1), column: location.column.saturating_sub(1) })
 				.open()
 				.await?;
 		}
@@ -237,6 +457,13 @@ async fn dummy_compiler_run() -> R<()> {
 	Ok(())
 }
 
-pub fn executable_path(source: SourceTarget) -> R<Path> {
-	Ok(source.to_path()?.with_extension(&*EXECUTABLE_EXTENSION.get()))
+/// Returns the toolchain whose flags and error messages are used when compiling. MinGW used on Windows is a version of
+/// GCC, but it keeps getting the same flags as Clang unless GCC is selected explicitly, so that existing setups do not
+/// change their behavior.
+pub fn toolchain() -> Toolchain {
+	COMPILER.get()
+}
+
+pub async fn executable_path(source: SourceTarget, codegen: Codegen) -> R<Path> {
+	Ok(source.to_path().await?.with_extension(&codegen.executable_extension(&EXECUTABLE_EXTENSION.get())))
 }
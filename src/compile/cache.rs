@@ -0,0 +1,70 @@
+use crate::{
+	bundle, executable::{Environment, Executable}, util::{fs, path::Path}
+};
+use evscode::{state::Scope, State, R};
+use icie_common::cache::Fingerprint;
+use std::{
+	cell::RefCell, collections::{HashMap, HashSet}
+};
+
+/// Fingerprints of the inputs used to build each artifact, keyed by the artifact path.
+static FINGERPRINTS: State<HashMap<String, String>> = State::new("icie.compile.fingerprints", Scope::Workspace);
+
+/// Hashes the source, the local headers it transitively includes and the compiler identity, which should contain
+/// everything else that affects the artifact, like the compiler version and flags.
+pub async fn fingerprint(source: &Path, follow_includes: bool, compiler_identity: &[String]) -> R<String> {
+	let mut fingerprint = Fingerprint::new(compiler_identity);
+	let mut visited = HashSet::new();
+	let mut queue = vec![source.clone()];
+	while let Some(path) = queue.pop() {
+		if !visited.insert(path.as_str().to_owned()) {
+			continue;
+		}
+		let code = fs::read_to_string(&path).await?;
+		fingerprint.add_file(path.as_str(), &code);
+		if follow_includes {
+			for include in bundle::local_includes(&code) {
+				// Headers that were not found are most likely system headers, which change along with the compiler.
+				if let Some(include) = bundle::resolve(&path, include).await? {
+					queue.push(include);
+				}
+			}
+		}
+	}
+	Ok(fingerprint.finish())
+}
+
+/// Checks whether the artifact exists and was built from inputs with the given fingerprint.
+pub async fn is_fresh(artifact: &Path, fingerprint: &str) -> R<bool> {
+	let saved = FINGERPRINTS.get()?.unwrap_or_default().remove(artifact.as_str());
+	Ok(saved.as_deref() == Some(fingerprint) && fs::exists(artifact).await?)
+}
+
+/// Saves the fingerprint of a new artifact, dropping the ones of artifacts that no longer exist, like the ones deleted by
+/// hand or built from sources that were removed since.
+pub async fn save(artifact: &Path, fingerprint: &str) -> R<()> {
+	let mut fingerprints = HashMap::new();
+	for (saved, saved_fingerprint) in FINGERPRINTS.get()?.unwrap_or_default() {
+		if fs::exists(&Path::from_native(saved.clone())).await? {
+			fingerprints.insert(saved, saved_fingerprint);
+		}
+	}
+	fingerprints.insert(artifact.as_str().to_owned(), fingerprint.to_owned());
+	FINGERPRINTS.set(&fingerprints).await;
+	Ok(())
+}
+
+/// Returns the version description printed by the compiler. Running the compiler takes a noticeable amount of time,
+/// especially for JVM-based ones, so the result is remembered until VS Code is restarted.
+pub async fn compiler_version(compiler: &Executable, flag: &str, environment: &Environment) -> R<String> {
+	thread_local! {
+		static VERSIONS: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+	}
+	if let Some(version) = VERSIONS.with(|versions| versions.borrow().get(&compiler.command).cloned()) {
+		return Ok(version);
+	}
+	let run = compiler.run("", &[flag], environment).await?;
+	let version = format!("{}{}", run.stdout, run.stderr);
+	VERSIONS.with(|versions| versions.borrow_mut().insert(compiler.command.clone(), version.clone()));
+	Ok(version)
+}
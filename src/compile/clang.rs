@@ -1,13 +1,15 @@
 use crate::{
-	compile::{Codegen, Location, Message, Standard, Status, WINDOWS_MINGW_PATH}, executable::{Environment, Executable}, service::Service, util, util::{fs, OS}
+	compile::{cache, parse_located_messages, pch, toolchain, Codegen, Message, Standard, Status, Toolchain, CUSTOM_COMPILER_PATH, WINDOWS_MINGW_PATH}, executable::{Environment, Executable}, service::Service, util, util::{fs, OS}
 };
 use evscode::R;
+use icie_common::compile::compiler_flags;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use util::path::Path;
 
 struct Compiler {
 	executable: Executable,
+	toolchain: Toolchain,
 	mingw_path: Option<Path>,
 }
 
@@ -27,6 +29,21 @@ const CLANG: Service = Service {
 	supports_macos: true,
 };
 
+const GCC: Service = Service {
+	human_name: "GCC",
+	exec_linuxmac: Some("g++"),
+	exec_windows: None,
+	package_apt: Some("g++"),
+	// Homebrew installs GCC as g++-<version>, while g++ on macOS is an alias for Apple Clang. Users who want GCC on
+	// macOS will have to set a custom compiler path anyway.
+	package_brew: None,
+	package_pacman: Some("gcc"),
+	tutorial_url_windows: None,
+	supports_linux: true,
+	supports_windows: false,
+	supports_macos: true,
+};
+
 // Searching for MinGW is more complex than searching for Linux/macOS executables, so this is just
 // to display a nice error message with a tutorial link.
 const MINGW: Service = Service {
@@ -51,25 +68,55 @@ pub async fn compile(
 ) -> R<Status> {
 	let compiler = find_compiler().await?;
 	let executable = Executable::new(output_path.to_owned());
-	let args = collect_compiler_flags(sources, output_path, standard, codegen, custom_flags);
+	let flags = collect_compiler_flags(standard, codegen, compiler.toolchain, custom_flags);
 	let environment = get_compiler_environment(&compiler);
-	let run = compiler.executable.run("", &args, &environment).await?;
-	let (errors, warnings) = parse_clang_output(&run.stderr);
+	let header = pch::prepare(sources, &compiler.executable, &environment, compiler.toolchain, &flags).await?;
+	let mut targets: Vec<&str> = sources.iter().copied().map(Path::as_str).collect();
+	targets.extend(&["-o", output_path.as_str()]);
+	let header_args = header.iter().flat_map(pch::Header::args);
+	let args: Vec<&str> = flags.iter().copied().chain(header_args).chain(targets.iter().copied()).collect();
+	let mut run = compiler.executable.run("", &args, &environment).await?;
+	if let Some(header) = &header {
+		if !run.success() && pch::is_pch_error(&run.stderr) {
+			header.discard().await?;
+			let args: Vec<&str> = flags.iter().chain(&targets).copied().collect();
+			run = compiler.executable.run("", &args, &environment).await?;
+		}
+	}
+	let (errors, warnings) = match compiler.toolchain {
+		Toolchain::Clang => parse_clang_output(&run.stderr),
+		Toolchain::Gcc => parse_gcc_output(&run.stderr),
+	};
 	check_macos_not_installed(&run.stderr).await?;
 	Ok(Status { run, executable, errors, warnings })
 }
 
+pub async fn version() -> R<String> {
+	let compiler = find_compiler().await?;
+	cache::compiler_version(&compiler.executable, "--version", &get_compiler_environment(&compiler)).await
+}
+
 async fn find_compiler() -> R<Compiler> {
-	match OS::query()? {
-		OS::Linux | OS::MacOS => {
+	let toolchain = toolchain();
+	let custom_path = CUSTOM_COMPILER_PATH.get();
+	if !custom_path.is_empty() {
+		let executable = Executable::new(util::expand_path(&custom_path));
+		return Ok(Compiler { executable, toolchain, mingw_path: None });
+	}
+	match (OS::query()?, toolchain) {
+		(OS::Linux, Toolchain::Clang) | (OS::MacOS, Toolchain::Clang) => {
 			let executable = CLANG.find_executable().await?;
-			Ok(Compiler { executable, mingw_path: None })
+			Ok(Compiler { executable, toolchain, mingw_path: None })
+		},
+		(OS::Linux, Toolchain::Gcc) | (OS::MacOS, Toolchain::Gcc) => {
+			let executable = GCC.find_executable().await?;
+			Ok(Compiler { executable, toolchain, mingw_path: None })
 		},
-		OS::Windows => find_compiler_mingw().await,
+		(OS::Windows, _) => find_compiler_mingw(toolchain).await,
 	}
 }
 
-async fn find_compiler_mingw() -> R<Compiler> {
+async fn find_compiler_mingw(toolchain: Toolchain) -> R<Compiler> {
 	let mingw_custom_path = WINDOWS_MINGW_PATH.get();
 	// Various MinGW installers install this in various paths. CodeBlocks installs it in
 	// "C:\Program Files (x64)\CodeBlocks\MinGW", but it's version does not work anyway so
@@ -85,44 +132,25 @@ async fn find_compiler_mingw() -> R<Compiler> {
 		let mingw = Path::from_native(mingw.to_owned());
 		let location = mingw.join("bin").join("g++.exe");
 		if fs::exists(&location).await? {
-			return Ok(Compiler { executable: Executable::new(location), mingw_path: Some(mingw) });
+			let executable = Executable::new(location);
+			return Ok(Compiler { executable, toolchain, mingw_path: Some(mingw) });
 		}
 	}
 	Err(MINGW.not_installed().await?)
 }
 
-fn collect_compiler_flags<'a>(
-	sources: &[&'a Path],
-	output_path: &'a Path,
+/// Collects the flags that affect how the sources are compiled, which are also used to build the precompiled header.
+fn collect_compiler_flags(
 	standard: Standard,
 	codegen: Codegen,
-	custom_flags: &'a [String],
-) -> Vec<&'a str> {
-	let mut args = Vec::new();
-	args.push(standard.flag_clang());
-	// -Wconversion displays warnings on lossy implicit conversions between i32/i64, u32/u64 and
-	// others. These are awful to debug because no small tests trigger them, and using exclusively
-	// i64 can hurt performance too much. -Wno-sign-conversions disables warnings on i32 to u32
-	// conversions, because that happens every time a vector is indexed with an int.
-	args.extend(&["-Wall", "-Wextra", "-Wconversion", "-Wshadow", "-Wno-sign-conversion"]);
-	args.extend(codegen.flags_clang());
-	args.extend(flags_os_specific());
+	toolchain: Toolchain,
+	custom_flags: &[String],
+) -> Vec<&str> {
+	let mut args = compiler_flags(standard, codegen, toolchain, matches!(OS::query(), Ok(OS::Windows)));
 	args.extend(custom_flags.iter().map(String::as_str));
-	args.extend(sources.iter().copied().map(Path::as_str));
-	args.push("-o");
-	args.push(output_path);
 	args
 }
 
-fn flags_os_specific() -> &'static [&'static str] {
-	match OS::query() {
-		// Sanitizers don't work because -lubsan is not found. There does not seem to be a fix.
-		// Static linking makes it possible to avoid adding MinGW DLLs to PATH.
-		Ok(OS::Windows) => &["-fno-sanitize=all", "-static"],
-		_ => &[],
-	}
-}
-
 fn get_compiler_environment(compiler: &Compiler) -> Environment {
 	Environment {
 		time_limit: None,
@@ -136,25 +164,30 @@ fn get_compiler_environment(compiler: &Compiler) -> Environment {
 
 fn parse_clang_output(stderr: &str) -> (Vec<Message>, Vec<Message>) {
 	static COMPILATION_ERROR: Lazy<Regex> =
-		Lazy::new(|| Regex::new("(.*):([0-9]+):([0-9]+): (error|warning|fatal error): (.*)\\n").unwrap());
+		Lazy::new(|| Regex::new("(.*):([0-9]+):([0-9]+): (error|warning|fatal error|note): (.*)\\n").unwrap());
 	static LINKING_ERROR: Lazy<Regex> = Lazy::new(|| Regex::new(".*(undefined reference to .*)").unwrap());
 
-	let mut errors = Vec::new();
-	let mut warnings = Vec::new();
-	for cap in COMPILATION_ERROR.captures_iter(stderr) {
-		let path = Path::from_native(cap[1].to_owned());
-		let line = cap[2].parse().unwrap();
-		let column = cap[3].parse().unwrap();
-		let severity = &cap[4];
-		let message = cap[5].to_owned();
-		let location = Some(Location { path, line, column });
-		let is_error = severity == "error" || severity == "fatal error";
-		let severity_list = if is_error { &mut errors } else { &mut warnings };
-		severity_list.push(Message { message, location });
+	let (mut errors, warnings) = parse_located_messages(stderr, &COMPILATION_ERROR);
+	for cap in LINKING_ERROR.captures_iter(stderr) {
+		let message = cap[1].to_owned();
+		errors.push(Message { message, location: None, notes: Vec::new() });
 	}
+	(errors, warnings)
+}
+
+fn parse_gcc_output(stderr: &str) -> (Vec<Message>, Vec<Message>) {
+	// GCC omits the column if it is not known, and reports linker errors through collect2 with either the symbol or
+	// the missing library, e.g. "cannot find -lubsan" when sanitizer runtime libraries are not installed.
+	static COMPILATION_ERROR: Lazy<Regex> = Lazy::new(|| {
+		Regex::new("(?m)^(.*?):([0-9]+):(?:([0-9]+):)? (error|warning|fatal error|note): (.*)$").unwrap()
+	});
+	static LINKING_ERROR: Lazy<Regex> =
+		Lazy::new(|| Regex::new("(?m)(undefined reference to .*|cannot find -l.*)$").unwrap());
+
+	let (mut errors, warnings) = parse_located_messages(stderr, &COMPILATION_ERROR);
 	for cap in LINKING_ERROR.captures_iter(stderr) {
 		let message = cap[1].to_owned();
-		errors.push(Message { message, location: None });
+		errors.push(Message { message, location: None, notes: Vec::new() });
 	}
 	(errors, warnings)
 }
@@ -166,3 +199,31 @@ async fn check_macos_not_installed(stderr: &str) -> R<()> {
 		Ok(())
 	}
 }
+
+#[test]
+fn test_parse_gcc_output() {
+	let stderr = "main.cpp: In function 'int main()':
+main.cpp:5:9: warning: unused variable 'x' [-Wunused-variable]
+    5 |     int x;
+      |         ^
+main.cpp:7:2: error: 'foo' was not declared in this scope
+main.cpp:9: error: expected ';' at end of input
+/usr/bin/ld: /tmp/ccxyz.o: in function `main':
+main.cpp:(.text+0x1f): undefined reference to `bar()'
+/usr/bin/ld: cannot find -lubsan
+collect2: error: ld returned 1 exit status
+";
+	let (errors, warnings) = parse_gcc_output(stderr);
+	let errors = errors
+		.iter()
+		.map(|error| (error.message.as_str(), error.location.as_ref().map(|location| (location.line, location.column))))
+		.collect::<Vec<_>>();
+	assert_eq!(errors, [
+		("'foo' was not declared in this scope", Some((7, 2))),
+		("expected ';' at end of input", Some((9, 1))),
+		("undefined reference to `bar()'", None),
+		("cannot find -lubsan", None),
+	]);
+	assert_eq!(warnings.len(), 1);
+	assert_eq!(warnings[0].message, "unused variable 'x' [-Wunused-variable]");
+}
@@ -0,0 +1,107 @@
+use crate::{
+	compile::{cache, parse_located_messages, Message, Status}, executable::{Environment, Executable}, service::Service, util::{fs, path::Path}
+};
+use evscode::{error::ResultExt, R};
+use icie_common::compile::find_main_class;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+const JAVAC: Service = Service {
+	human_name: "Java compiler",
+	exec_linuxmac: Some("javac"),
+	exec_windows: Some("javac.exe"),
+	package_apt: Some("default-jdk"),
+	package_brew: Some("openjdk"),
+	package_pacman: Some("jdk-openjdk"),
+	tutorial_url_windows: Some("https://adoptopenjdk.net/installation.html"),
+	supports_linux: true,
+	supports_windows: true,
+	supports_macos: true,
+};
+
+const JAVA: Service = Service {
+	human_name: "Java",
+	exec_linuxmac: Some("java"),
+	exec_windows: Some("java.exe"),
+	package_apt: Some("default-jre"),
+	package_brew: Some("openjdk"),
+	package_pacman: Some("jre-openjdk"),
+	tutorial_url_windows: Some("https://adoptopenjdk.net/installation.html"),
+	supports_linux: true,
+	supports_windows: true,
+	supports_macos: true,
+};
+
+const KOTLINC: Service = Service {
+	human_name: "Kotlin compiler",
+	exec_linuxmac: Some("kotlinc"),
+	exec_windows: Some("kotlinc.bat"),
+	package_apt: None,
+	package_brew: Some("kotlin"),
+	package_pacman: Some("kotlin"),
+	tutorial_url_windows: Some("https://kotlinlang.org/docs/tutorials/command-line.html"),
+	supports_linux: true,
+	supports_windows: true,
+	supports_macos: true,
+};
+
+pub async fn compile_java(source: &Path, class_file: &Path) -> R<Status> {
+	let javac = JAVAC.find_executable().await?;
+	let class_dir = class_file.parent();
+	fs::create_dir_all(&class_dir).await?;
+	let args = ["-encoding", "UTF-8", "-d", class_dir.as_str(), source.as_str()];
+	let run = javac.run("", &args, &Environment { time_limit: None, cwd: None }).await?;
+	let (errors, warnings) = parse_jvm_output(&run.stderr);
+	let executable = java_executable(source).await?;
+	Ok(Status { run, executable, errors, warnings })
+}
+
+pub async fn compile_kotlin(source: &Path, jar: &Path) -> R<Status> {
+	let kotlinc = KOTLINC.find_executable().await?;
+	let args = [source.as_str(), "-include-runtime", "-d", jar.as_str()];
+	let run = kotlinc.run("", &args, &Environment { time_limit: None, cwd: None }).await?;
+	let (errors, warnings) = parse_jvm_output(&run.stderr);
+	let executable = kotlin_executable(jar).await?;
+	Ok(Status { run, executable, errors, warnings })
+}
+
+pub async fn javac_version() -> R<String> {
+	let javac = JAVAC.find_executable().await?;
+	cache::compiler_version(&javac, "-version", &Environment { time_limit: None, cwd: None }).await
+}
+
+pub async fn kotlinc_version() -> R<String> {
+	let kotlinc = KOTLINC.find_executable().await?;
+	cache::compiler_version(&kotlinc, "-version", &Environment { time_limit: None, cwd: None }).await
+}
+
+/// Returns the path of the compiled main class. Classes are stored in a directory next to the source, because Java
+/// requires one file per class and the main class name does not have to match the source file name.
+pub async fn java_class_file(source: &Path) -> R<Path> {
+	let class = find_main_class(&fs::read_to_string(source).await?)
+		.wrap(format!("no class with a main method found in {}", source.fmt_workspace()))?;
+	Ok(source.with_extension("classes").join(format!("{}.class", class)))
+}
+
+pub fn kotlin_jar(source: &Path) -> Path {
+	source.with_extension("jar")
+}
+
+pub async fn java_executable(source: &Path) -> R<Executable> {
+	let java = JAVA.find_command().await?;
+	let class_file = java_class_file(source).await?;
+	let class_path = class_file.parent().into_string();
+	Ok(Executable::new_interpreted(java, vec!["-cp".to_owned(), class_path, class_file.file_stem()]))
+}
+
+pub async fn kotlin_executable(jar: &Path) -> R<Executable> {
+	let java = JAVA.find_command().await?;
+	Ok(Executable::new_interpreted(java, vec!["-jar".to_owned(), jar.as_str().to_owned()]))
+}
+
+fn parse_jvm_output(stderr: &str) -> (Vec<Message>, Vec<Message>) {
+	// javac does not print columns, while kotlinc does.
+	static DIAGNOSTIC: Lazy<Regex> =
+		Lazy::new(|| Regex::new("(?m)^(.*?):([0-9]+):(?:([0-9]+):)? (error|warning): (.*)$").unwrap());
+	parse_located_messages(stderr, &DIAGNOSTIC)
+}
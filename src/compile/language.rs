@@ -0,0 +1,83 @@
+use crate::{
+	compile::{jvm, python, Codegen, SOURCE_EXTENSIONS}, dir, executable::Executable, util::path::Path
+};
+use evscode::R;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, evscode::Configurable)]
+pub enum Language {
+	#[evscode(name = "C++")]
+	Cpp,
+	#[evscode(name = "Python")]
+	Python,
+	#[evscode(name = "Rust")]
+	Rust,
+	#[evscode(name = "Java")]
+	Java,
+	#[evscode(name = "Kotlin")]
+	Kotlin,
+}
+
+impl Language {
+	pub const LIST: &'static [Language] =
+		&[Language::Cpp, Language::Python, Language::Rust, Language::Java, Language::Kotlin];
+
+	pub fn from_path(path: &Path) -> Option<Language> {
+		let extension = path.extension()?;
+		Language::LIST.iter().copied().find(|language| match language {
+			Language::Cpp => extension == dir::CPP_EXTENSION.get() || SOURCE_EXTENSIONS.contains(&extension.as_str()),
+			_ => extension == language.extension(),
+		})
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Language::Cpp => "C++",
+			Language::Python => "Python",
+			Language::Rust => "Rust",
+			Language::Java => "Java",
+			Language::Kotlin => "Kotlin",
+		}
+	}
+
+	pub fn extension(self) -> String {
+		match self {
+			Language::Cpp => dir::CPP_EXTENSION.get(),
+			Language::Python => "py".to_owned(),
+			Language::Rust => "rs".to_owned(),
+			Language::Java => "java".to_owned(),
+			Language::Kotlin => "kt".to_owned(),
+		}
+	}
+
+	/// Whether the source is compiled to a native executable, which debuggers and profilers can run directly.
+	pub fn is_native(self) -> bool {
+		self == Language::Cpp || self == Language::Rust
+	}
+
+	/// Whether the compilation produces an artifact that can be reused as long as its inputs do not change.
+	pub fn is_compiled(self) -> bool {
+		self != Language::Python
+	}
+
+	/// Returns the file that will be created by compiling the source with the given profile.
+	pub async fn artifact(self, source: &Path, executable_extension: &str, codegen: Codegen) -> R<Path> {
+		match self {
+			Language::Cpp | Language::Rust => {
+				Ok(source.with_extension(&codegen.executable_extension(executable_extension)))
+			},
+			Language::Java => jvm::java_class_file(source).await,
+			Language::Kotlin => Ok(jvm::kotlin_jar(source)),
+			Language::Python => Ok(source.clone()),
+		}
+	}
+
+	/// Returns how to run a source that has already been compiled to the given artifact.
+	pub async fn executable(self, source: &Path, artifact: &Path) -> R<Executable> {
+		match self {
+			Language::Cpp | Language::Rust => Ok(Executable::new(artifact.clone())),
+			Language::Java => jvm::java_executable(source).await,
+			Language::Kotlin => jvm::kotlin_executable(artifact).await,
+			Language::Python => python::executable(source).await,
+		}
+	}
+}
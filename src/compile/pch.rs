@@ -0,0 +1,162 @@
+use crate::{
+	compile::{cache, Toolchain}, executable::{Environment, Executable}, util::{fs, path::Path, time_now}
+};
+use evscode::R;
+use icie_common::cache::Fingerprint;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::{cell::RefCell, collections::HashSet, time::UNIX_EPOCH};
+
+/// Precompile the bits/stdc++.h header and use it when compiling sources that include it. Parsing the whole standard
+/// library takes most of the time needed to compile a typical solution, so this makes compilation several times
+/// faster. Precompiled headers are shared between all tasks, and a separate one is built for every compiler, C++
+/// standard and set of flags.
+#[evscode::config]
+static ENABLED: evscode::Config<bool> = true;
+
+pub struct Header {
+	directory: Path,
+	args: Vec<String>,
+}
+
+impl Header {
+	pub fn args(&self) -> impl Iterator<Item=&str> {
+		self.args.iter().map(String::as_str)
+	}
+
+	/// Removes the precompiled header, so that it will be built again on the next compilation.
+	pub async fn discard(&self) -> R<()> {
+		for file in fs::read_dir(&self.directory).await? {
+			fs::remove_file(&file).await?;
+		}
+		Ok(())
+	}
+}
+
+/// Returns a precompiled bits/stdc++.h header built with the given flags, building it first if necessary. Returns
+/// nothing if the sources do not start with including bits/stdc++.h or the header could not be precompiled, in which
+/// case the compilation should proceed as usual.
+pub async fn prepare(
+	sources: &[&Path],
+	compiler: &Executable,
+	environment: &Environment,
+	toolchain: Toolchain,
+	flags: &[&str],
+) -> R<Option<Header>> {
+	thread_local! {
+		static FAILED: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+	}
+	if !ENABLED.get() || !includes_stdcpp(sources).await? {
+		return Ok(None);
+	}
+	let version = cache::compiler_version(compiler, "--version", environment).await?;
+	let mut identity = vec![compiler.command.clone(), version];
+	identity.extend(flags.iter().map(|flag| (*flag).to_owned()));
+	let key = Fingerprint::new(&identity).finish();
+	if FAILED.with(|failed| failed.borrow().contains(&key)) {
+		return Ok(None);
+	}
+	let directory = Path::from_native(evscode::global_storage_root()).join("pch").join(&key);
+	let wrapper = directory.join("stdc++.h");
+	// GCC finds the precompiled header automatically when the wrapper is included, and falls back to parsing the
+	// wrapper if it is not valid. Clang has to be given the precompiled header explicitly, and treats invalid ones as
+	// errors, so the compilation has to be retried without it.
+	let (artifact, args) = match toolchain {
+		Toolchain::Gcc => {
+			let artifact = directory.join("stdc++.h.gch");
+			let args = vec!["-include".to_owned(), wrapper.as_str().to_owned()];
+			(artifact, args)
+		},
+		Toolchain::Clang => {
+			let artifact = directory.join("stdc++.h.pch");
+			let args = vec!["-include-pch".to_owned(), artifact.as_str().to_owned()];
+			(artifact, args)
+		},
+	};
+	if !fs::exists(&artifact).await? {
+		let _status = crate::STATUS.push("Precompiling bits/stdc++.h");
+		fs::create_dir_all(&directory).await?;
+		// Other compilations with the same flags may be using or building the header at the same time, so the files
+		// are written under unique names and then atomically moved into place. The wrapper is never replaced once it
+		// exists, because Clang rejects precompiled headers whose source file was modified.
+		let id = time_now().duration_since(UNIX_EPOCH).unwrap().as_micros();
+		if !fs::exists(&wrapper).await? {
+			let temporary_wrapper = directory.join(format!("stdc++.h.{}.tmp", id));
+			fs::write(&temporary_wrapper, "#include <bits/stdc++.h>\n").await?;
+			fs::rename(&temporary_wrapper, &wrapper).await?;
+		}
+		let temporary_artifact = directory.join(format!("{}.{}.tmp", artifact.file_name(), id));
+		let mut build_args = flags.to_vec();
+		build_args.extend(&["-x", "c++-header", wrapper.as_str(), "-o", temporary_artifact.as_str()]);
+		let run = compiler.run("", &build_args, environment).await?;
+		if run.success() {
+			fs::rename(&temporary_artifact, &artifact).await?;
+		} else {
+			if fs::exists(&temporary_artifact).await? {
+				fs::remove_file(&temporary_artifact).await?;
+			}
+			// Some standard libraries, like libc++ used by default on macOS, do not have bits/stdc++.h at all. The
+			// compilation itself will report a better error if that is the case.
+			log::warn!("failed to precompile bits/stdc++.h, {:?}", run);
+			FAILED.with(|failed| failed.borrow_mut().insert(key));
+			return Ok(None);
+		}
+	}
+	Ok(Some(Header { directory, args }))
+}
+
+/// Checks whether the compilation failed because the precompiled header could not be used, for example because the
+/// standard library headers were updated after it was built.
+pub fn is_pch_error(stderr: &str) -> bool {
+	static PCH_ERROR: Lazy<Regex> = Lazy::new(|| Regex::new("precompiled header|PCH file").unwrap());
+	PCH_ERROR.is_match(stderr)
+}
+
+/// Checks whether all sources start with including bits/stdc++.h. The precompiled header is included before the first
+/// line of the source, so anything above the include, like `#pragma GCC optimize` or `#define _GLIBCXX_DEBUG`, would
+/// no longer affect the standard library and the program could behave differently than on the judge.
+async fn includes_stdcpp(sources: &[&Path]) -> R<bool> {
+	for source in sources {
+		if !starts_with_stdcpp_include(&fs::read_to_string(source).await?) {
+			return Ok(false);
+		}
+	}
+	Ok(!sources.is_empty())
+}
+
+fn starts_with_stdcpp_include(mut code: &str) -> bool {
+	static INCLUDE: Lazy<Regex> = Lazy::new(|| Regex::new("^#\\s*include\\s*<bits/stdc\\+\\+\\.h>").unwrap());
+	loop {
+		code = code.trim_start();
+		if code.starts_with("//") {
+			code = code.find('\n').map_or("", |end| &code[end..]);
+		} else if code.starts_with("/*") {
+			match code[2..].find("*/") {
+				Some(end) => code = &code[2 + end + 2..],
+				None => return false,
+			}
+		} else {
+			return INCLUDE.is_match(code);
+		}
+	}
+}
+
+#[test]
+fn test_starts_with_stdcpp_include() {
+	assert!(starts_with_stdcpp_include("#include <bits/stdc++.h>\nusing namespace std;\n"));
+	assert!(starts_with_stdcpp_include("// Template\n\n  #  include<bits/stdc++.h>\n"));
+	assert!(starts_with_stdcpp_include("/* Author: me\n */ #include <bits/stdc++.h>\n"));
+	assert!(!starts_with_stdcpp_include("#include <iostream>\n// #include <bits/stdc++.h>\n"));
+	assert!(!starts_with_stdcpp_include("#include <iostream>\n#include <bits/stdc++.h>\n"));
+	assert!(!starts_with_stdcpp_include("#pragma GCC optimize(\"O3\")\n#include <bits/stdc++.h>\n"));
+	assert!(!starts_with_stdcpp_include("#define _GLIBCXX_DEBUG\n#include <bits/stdc++.h>\n"));
+	assert!(!starts_with_stdcpp_include("/* unterminated\n#include <bits/stdc++.h>\n"));
+	assert!(!starts_with_stdcpp_include(""));
+}
+
+#[test]
+fn test_is_pch_error() {
+	assert!(is_pch_error("file 'vector' has been modified since the precompiled header 'a.pch' was built"));
+	assert!(is_pch_error("error: PCH file uses an older PCH format that is no longer supported"));
+	assert!(!is_pch_error("error: 'vector' was not declared in this scope"));
+}
@@ -0,0 +1,66 @@
+use crate::{
+	compile::{Location, Message, Status}, executable::{Environment, Executable}, service::Service, util::path::Path
+};
+use evscode::R;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+const PYTHON: Service = Service {
+	human_name: "Python",
+	exec_linuxmac: Some("python3"),
+	exec_windows: Some("python.exe"),
+	package_apt: Some("python3"),
+	package_brew: Some("python"),
+	package_pacman: Some("python"),
+	tutorial_url_windows: Some("https://docs.python.org/3/using/windows.html"),
+	supports_linux: true,
+	supports_windows: true,
+	supports_macos: true,
+};
+
+// Python sources are not compiled ahead of time, but checking the syntax makes it possible to report errors the same
+// way as for other languages. This does not use py_compile, so no __pycache__ directory appears in the task directory.
+const SYNTAX_CHECK: &str = "import sys; compile(open(sys.argv[1], encoding='utf-8').read(), sys.argv[1], 'exec')";
+
+pub async fn compile(source: &Path) -> R<Status> {
+	let python = PYTHON.find_executable().await?;
+	let environment = Environment { time_limit: None, cwd: None };
+	let run = python.run("", &["-c", SYNTAX_CHECK, source.as_str()], &environment).await?;
+	let errors = parse_python_output(&run.stderr).into_iter().collect();
+	let executable = executable(source).await?;
+	Ok(Status { run, executable, errors, warnings: Vec::new() })
+}
+
+pub async fn executable(source: &Path) -> R<Executable> {
+	let python = PYTHON.find_command().await?;
+	Ok(Executable::new_interpreted(python, vec![source.as_str().to_owned()]))
+}
+
+fn parse_python_output(stderr: &str) -> Option<Message> {
+	static LOCATION: Lazy<Regex> = Lazy::new(|| Regex::new("(?m)^\\s*File \"(.*)\", line ([0-9]+)").unwrap());
+	static ERROR: Lazy<Regex> = Lazy::new(|| Regex::new("(?m)^([A-Za-z]*Error: .*)$").unwrap());
+	let message = ERROR.captures_iter(stderr).last()?[1].to_owned();
+	// The traceback also mentions the syntax checking script itself, which is passed as "<string>".
+	let location = LOCATION.captures_iter(stderr).filter(|cap| &cap[1] != "<string>").last().map(|cap| Location {
+		path: Path::from_native(cap[1].to_owned()),
+		line: cap[2].parse().unwrap(),
+		column: 1,
+	});
+	Some(Message { message, location, notes: Vec::new() })
+}
+
+#[test]
+fn test_parse_python_output() {
+	let stderr = "Traceback (most recent call last):
+  File \"<string>\", line 1, in <module>
+  File \"main.py\", line 3
+    x = = 1
+        ^
+SyntaxError: invalid syntax
+";
+	let message = parse_python_output(stderr).unwrap();
+	assert_eq!(message.message, "SyntaxError: invalid syntax");
+	let location = message.location.unwrap();
+	assert_eq!((location.path.as_str(), location.line), ("main.py", 3));
+	assert!(parse_python_output("").is_none());
+}
@@ -0,0 +1,81 @@
+use crate::{
+	compile::{cache, Codegen, Location, Message, Status}, executable::{Environment, Executable}, service::Service, util::path::Path
+};
+use evscode::R;
+use icie_common::compile::rustc_flags;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+const RUSTC: Service = Service {
+	human_name: "Rust",
+	exec_linuxmac: Some("rustc"),
+	exec_windows: Some("rustc.exe"),
+	package_apt: Some("rustc"),
+	package_brew: Some("rust"),
+	package_pacman: Some("rust"),
+	tutorial_url_windows: Some("https://www.rust-lang.org/tools/install"),
+	supports_linux: true,
+	supports_windows: true,
+	supports_macos: true,
+};
+
+pub async fn compile(source: &Path, output_path: &Path, codegen: Codegen) -> R<Status> {
+	let rustc = RUSTC.find_executable().await?;
+	let executable = Executable::new(output_path.to_owned());
+	let mut args = vec!["--edition=2018"];
+	args.extend(rustc_flags(codegen));
+	args.extend(&[source.as_str(), "-o", output_path.as_str()]);
+	let environment = Environment { time_limit: None, cwd: None };
+	let run = rustc.run("", &args, &environment).await?;
+	let (errors, warnings) = parse_rustc_output(&run.stderr);
+	Ok(Status { run, executable, errors, warnings })
+}
+
+pub async fn version() -> R<String> {
+	let rustc = RUSTC.find_executable().await?;
+	cache::compiler_version(&rustc, "--version", &Environment { time_limit: None, cwd: None }).await
+}
+
+fn parse_rustc_output(stderr: &str) -> (Vec<Message>, Vec<Message>) {
+	// Messages without a location, like "aborting due to previous error", only summarize the other ones.
+	static DIAGNOSTIC: Lazy<Regex> = Lazy::new(|| {
+		Regex::new("(?m)^(error|warning)(?:\\[[A-Z0-9]+\\])?: (.*)\\n\\s*--> (.*):([0-9]+):([0-9]+)$").unwrap()
+	});
+	let mut errors = Vec::new();
+	let mut warnings = Vec::new();
+	for cap in DIAGNOSTIC.captures_iter(stderr) {
+		let message = cap[2].to_owned();
+		let path = Path::from_native(cap[3].to_owned());
+		let line = cap[4].parse().unwrap();
+		let column = cap[5].parse().unwrap();
+		let location = Some(Location { path, line, column });
+		let severity_list = if &cap[1] == "error" { &mut errors } else { &mut warnings };
+		severity_list.push(Message { message, location, notes: Vec::new() });
+	}
+	(errors, warnings)
+}
+
+#[test]
+fn test_parse_rustc_output() {
+	let stderr = "warning: unused variable: `x`
+ --> main.rs:2:9
+  |
+2 |     let x = 5;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_x`
+
+error[E0425]: cannot find value `y` in this scope
+ --> main.rs:3:20
+  |
+3 |     println!(\"{}\", y);
+  |                    ^ not found in this scope
+
+error: aborting due to previous error
+";
+	let (errors, warnings) = parse_rustc_output(stderr);
+	assert_eq!(errors.len(), 1);
+	assert_eq!(errors[0].message, "cannot find value `y` in this scope");
+	let location = errors[0].location.as_ref().unwrap();
+	assert_eq!((location.path.as_str(), location.line, location.column), ("main.rs", 3, 20));
+	assert_eq!(warnings.len(), 1);
+	assert_eq!(warnings[0].message, "unused variable: `x`");
+}
@@ -1,7 +1,37 @@
+mod memory;
+
 use crate::{
-	compile, executable::{Environment, Executable}, service::Service, terminal, terminal::BashTerminal, test, util, util::{fs, path::Path, SourceTarget}
+	compile, compile::{Codegen, Language}, executable::{Environment, Executable}, service::Service, terminal, terminal::BashTerminal, test, util, util::{fs, path::Path, workspace_root, SourceTarget, Tempfile, OS}
 };
 use evscode::{E, R};
+use serde_json::json;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, evscode::Configurable)]
+pub enum Debugger {
+	#[evscode(name = "GDB in a terminal")]
+	Terminal,
+	#[evscode(name = "VS Code with the C/C++ extension")]
+	CppTools,
+	#[evscode(name = "VS Code with the CodeLLDB extension")]
+	CodeLldb,
+}
+
+/// Debugger launched from the test view, also used for replaying RR recordings. VS Code debuggers support breakpoints
+/// set in the editor, but require installing the C/C++ (ms-vscode.cpptools) or CodeLLDB (vadimcn.vscode-lldb)
+/// extension.
+#[evscode::config]
+static DEBUGGER: evscode::Config<Debugger> = Debugger::Terminal;
+
+/// Port used by the GDB server started with rr replay, which the VS Code debugger connects to.
+#[evscode::config]
+static RR_PORT: evscode::Config<u64> = 50505u64;
+
+/// Number of times the output of rr replay is checked, every 100 milliseconds, before giving up on connecting to it.
+const RR_STARTUP_POLLS: usize = 300;
+
+const CPPTOOLS_EXTENSION: &str = "ms-vscode.cpptools";
+const CODELLDB_EXTENSION: &str = "vadimcn.vscode-lldb";
 
 pub const GDB: Service = Service {
 	human_name: "GDB",
@@ -29,23 +59,72 @@ pub const RR: Service = Service {
 	supports_macos: false,
 };
 
+pub const VALGRIND: Service = Service {
+	human_name: "Valgrind",
+	exec_linuxmac: Some("valgrind"),
+	exec_windows: None,
+	package_apt: Some("valgrind"),
+	package_brew: None,
+	package_pacman: Some("valgrind"),
+	tutorial_url_windows: None,
+	supports_linux: true,
+	supports_windows: false,
+	supports_macos: false,
+};
+
 pub async fn gdb(in_path: &Path, source: SourceTarget) -> R<()> {
-	let gdb = GDB.find_command().await?;
-	terminal::debugger("GDB", in_path, &[
-		&gdb,
-		"-q",
-		compile::executable_path(source)?.as_str(),
-		"-ex",
-		&format!("set args < {}", util::bash_escape(in_path.as_str())),
-	])
-	.await
+	require_native(&source, "GDB").await?;
+	let executable = compile::executable_path(source, Codegen::Debug).await?;
+	match DEBUGGER.get() {
+		Debugger::Terminal => {
+			let gdb = GDB.find_command().await?;
+			terminal::debugger("GDB", in_path, &[
+				&gdb,
+				"-q",
+				executable.as_str(),
+				"-ex",
+				&format!("set args < {}", util::bash_escape(in_path.as_str())),
+			])
+			.await
+		},
+		Debugger::CppTools => {
+			let mi_mode = match OS::query()? {
+				OS::MacOS => "lldb",
+				OS::Linux | OS::Windows => "gdb",
+			};
+			let configuration = json!({
+				"type": "cppdbg",
+				"request": "launch",
+				"name": session_name("Debug", in_path),
+				"program": executable.as_str(),
+				// The arguments are passed to the shell that starts the program, so input redirection works.
+				"args": ["<", in_path.as_str()],
+				"cwd": workspace_root()?.as_str(),
+				"MIMode": mi_mode,
+				"setupCommands": [{ "text": "-enable-pretty-printing", "ignoreFailures": true }],
+			});
+			start_session(configuration, CPPTOOLS_EXTENSION).await
+		},
+		Debugger::CodeLldb => {
+			let configuration = json!({
+				"type": "lldb",
+				"request": "launch",
+				"name": session_name("Debug", in_path),
+				"program": executable.as_str(),
+				"cwd": workspace_root()?.as_str(),
+				"stdio": [in_path.as_str(), null, null],
+			});
+			start_session(configuration, CODELLDB_EXTENSION).await
+		},
+	}
 }
 
 pub async fn rr(in_path: &Path, source: SourceTarget) -> R<()> {
+	require_native(&source, "RR").await?;
 	let rr = RR.find_command().await?;
-	let rr_exec = Executable::new_name(rr.clone());
+	let rr_exec = Executable::new_name(rr.clone()).with_stack_size(compile::stack_size().await);
 	let input = fs::read_to_string(in_path).await?;
-	let exec_path = compile::executable_path(source)?;
+	let exec_path = compile::executable_path(source, Codegen::Debug).await?;
 	let args = ["record", exec_path.as_str()];
 	let environment = Environment { time_limit: test::time_limit(), cwd: None };
 	let record_out = rr_exec.run(&input, &args, &environment).await?;
@@ -55,10 +134,122 @@ pub async fn rr(in_path: &Path, source: SourceTarget) -> R<()> {
 		)
 		.action("🔐 Auto-configure", configure_kernel_perf_event_paranoid()));
 	}
-	terminal::debugger("RR", in_path, &[&rr, "replay", "--", "-q"]).await
+	match DEBUGGER.get() {
+		Debugger::Terminal => terminal::debugger("RR", in_path, &[&rr, "replay", "--", "-q"]).await,
+		Debugger::CppTools | Debugger::CodeLldb => rr_replay_session(&rr, &exec_path, in_path).await,
+	}
+}
+
+/// Starts rr replay as a GDB server and connects the VS Code debugger to it. Only CodeLLDB supports the reverse
+/// execution buttons, but with the C/C++ extension commands like "-exec reverse-next" can be used in the debug console.
+async fn rr_replay_session(rr: &str, executable: &Path, in_path: &Path) -> R<()> {
+	let port = RR_PORT.get();
+	let log = Tempfile::new("rrreplay", ".log", "").await?;
+	let replay = terminal::bash_escape_command(&[rr, "replay", "-s", &port.to_string()]);
+	let command = format!("{} 2>&1 | tee {}", replay, util::bash_escape(log.path().as_str()));
+	terminal::Internal.spawn_bash("ICIE RR replay", &command).await?;
+	wait_for_gdb_server(log.path()).await?;
+	if DEBUGGER.get() == Debugger::CodeLldb {
+		let configuration = json!({
+			"type": "lldb",
+			"request": "custom",
+			"name": session_name("RR", in_path),
+			"targetCreateCommands": [format!("target create {}", util::bash_escape(executable.as_str()))],
+			"processCreateCommands": [format!("gdb-remote 127.0.0.1:{}", port)],
+			"reverseDebugging": true,
+		});
+		start_session(configuration, CODELLDB_EXTENSION).await
+	} else {
+		let configuration = json!({
+			"type": "cppdbg",
+			"request": "launch",
+			"name": session_name("RR", in_path),
+			"program": executable.as_str(),
+			"miDebuggerServerAddress": format!("127.0.0.1:{}", port),
+			"cwd": workspace_root()?.as_str(),
+			"MIMode": "gdb",
+			"setupCommands": [
+				{ "text": "set sysroot /", "ignoreFailures": false },
+				{ "text": "-enable-pretty-printing", "ignoreFailures": true },
+			],
+		});
+		start_session(configuration, CPPTOOLS_EXTENSION).await
+	}
+}
+
+/// Waits until rr loads the recording and the GDB server starts listening, which rr announces by printing the command
+/// that connects GDB to it.
+async fn wait_for_gdb_server(log: &Path) -> R<()> {
+	for _ in 0..RR_STARTUP_POLLS {
+		if fs::read_to_string(log).await?.contains("target extended-remote") {
+			return Ok(());
+		}
+		util::sleep(Duration::from_millis(100)).await;
+	}
+	Err(E::error("RR did not start the GDB server, see the ICIE RR replay terminal for details"))
+}
+
+async fn start_session(configuration: serde_json::Value, extension: &'static str) -> R<()> {
+	evscode::start_debugging(&configuration).await.map_err(|e| {
+		e.action("Install debugger extension", async move {
+			evscode::open_external(&format!("vscode:extension/{}", extension)).await
+		})
+		.action("Use GDB in a terminal", async {
+			DEBUGGER.update_global(&Debugger::Terminal).await;
+			Ok(())
+		})
+	})
+}
+
+fn session_name(kind: &str, in_path: &Path) -> String {
+	format!("ICIE {} - {}", kind, in_path.without_extension().fmt_workspace())
+}
+
+pub async fn address_sanitizer(in_path: &Path, source: SourceTarget) -> R<()> {
+	let _status = crate::STATUS.push("Checking memory");
+	let path = source.to_path().await?;
+	if Language::from_path(&path) != Some(Language::Cpp) {
+		return Err(E::error(format!("AddressSanitizer only supports C++, {} is not C++", path.fmt_workspace())));
+	}
+	let executable = compile::compile_solution(&source, Codegen::AddressSanitizer).await?;
+	let input = fs::read_to_string(in_path).await?;
+	// Sanitized programs are usually a few times slower, so the normal time limit would cause spurious failures.
+	let environment = Environment { time_limit: test::time_limit().map(|limit| limit * 4), cwd: None };
+	let run = executable.run(&input, &[], &environment).await?;
+	memory::show("AddressSanitizer", memory::parse_sanitizer(&run.stderr), &run).await
+}
+
+pub async fn valgrind(in_path: &Path, source: SourceTarget) -> R<()> {
+	let _status = crate::STATUS.push("Checking memory");
+	require_native(&source, "Valgrind").await?;
+	let valgrind = VALGRIND.find_executable().await?;
+	let executable = compile::compile_solution(&source, Codegen::Debug).await?;
+	let input = fs::read_to_string(in_path).await?;
+	let mut args = vec!["--leak-check=full", "--fullpath-after="];
+	args.push(&executable.command);
+	args.extend(executable.args.iter().map(String::as_str));
+	let environment = Environment { time_limit: test::time_limit().map(|limit| limit * 50), cwd: None };
+	let run = valgrind.with_stack_size(executable.stack_size).run(&input, &args, &environment).await?;
+	memory::show("Valgrind", memory::parse_valgrind(&run.stderr), &run).await
+}
+
+/// Checks whether the source is compiled to a native executable, because the tools running it would otherwise debug or
+/// profile the interpreter or the JVM instead of the solution.
+pub async fn require_native(source: &SourceTarget, tool: &str) -> R<()> {
+	let path = source.to_path().await?;
+	match Language::from_path(&path) {
+		Some(language) if language.is_native() => Ok(()),
+		Some(language) => Err(E::error(format!(
+			"{} only supports C++ and Rust, {} is written in {}",
+			tool,
+			path.fmt_workspace(),
+			language.name()
+		))),
+		None => Err(E::error(format!("{} is not written in any of the supported languages", path.fmt_workspace()))),
+	}
 }
 
-async fn configure_kernel_perf_event_paranoid() -> R<()> {
+pub async fn configure_kernel_perf_event_paranoid() -> R<()> {
 	terminal::Internal
 		.spawn_bash(
 			"ICIE Auto-configure RR",
@@ -0,0 +1,215 @@
+use crate::{
+	executable::Run, util::{path::Path, workspace_root}
+};
+use evscode::{
+	stdlib::{
+		diagnostics::{Diagnostic, Related, Severity}, output_channel::OutputChannel
+	}, DiagnosticCollection, Position, Range, R
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Memory error found by AddressSanitizer or Valgrind, with the stack traces that explain it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Report {
+	pub message: String,
+	/// The first stack trace shows where the error happened, and the other ones show e.g. where the memory was
+	/// allocated or freed.
+	pub stacks: Vec<Stack>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Stack {
+	pub title: String,
+	pub frames: Vec<Frame>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Frame {
+	pub function: String,
+	pub path: String,
+	pub line: usize,
+}
+
+impl Frame {
+	fn new(function: &str, path: &str, line: usize) -> Frame {
+		Frame { function: function.to_owned(), path: path.to_owned(), line }
+	}
+}
+
+/// Parses the first error reported by AddressSanitizer, LeakSanitizer or UndefinedBehaviorSanitizer.
+pub fn parse_sanitizer(stderr: &str) -> Option<Report> {
+	static ERROR: Lazy<Regex> = Lazy::new(|| Regex::new("^==[0-9]+==ERROR: (.*?)(?: at pc .*)?$").unwrap());
+	static RUNTIME_ERROR: Lazy<Regex> =
+		Lazy::new(|| Regex::new("^(.*?):([0-9]+):[0-9]+: runtime error: (.*)$").unwrap());
+	static FRAME: Lazy<Regex> = Lazy::new(|| Regex::new("^\\s*#[0-9]+ 0x[0-9a-f]+").unwrap());
+	static LOCATION: Lazy<Regex> = Lazy::new(|| Regex::new(" in (.*) (\\S+?):([0-9]+)(?::[0-9]+)?$").unwrap());
+	let mut lines = stderr.lines();
+	let message = loop {
+		let line = lines.next()?;
+		if let Some(cap) = RUNTIME_ERROR.captures(line) {
+			let frame = Frame::new("", &cap[1], cap[2].parse().unwrap());
+			let stacks = vec![Stack { title: cap[3].to_owned(), frames: vec![frame] }];
+			return Some(Report { message: cap[3].to_owned(), stacks });
+		} else if let Some(cap) = ERROR.captures(line) {
+			break cap[1].to_owned();
+		}
+	};
+	let mut stacks = Vec::new();
+	let mut title = message.clone();
+	let mut current: Option<Stack> = None;
+	for line in lines.take_while(|line| !line.starts_with("SUMMARY:")) {
+		if FRAME.is_match(line) {
+			let stack = current.get_or_insert_with(|| Stack { title: title.clone(), frames: Vec::new() });
+			// Frames in system libraries without debug information do not have a source location.
+			if let Some(cap) = LOCATION.captures(line) {
+				stack.frames.push(Frame::new(&cap[1], &cap[2], cap[3].parse().unwrap()));
+			}
+		} else {
+			stacks.extend(current.take());
+			if !line.trim().is_empty() {
+				title = line.trim().trim_end_matches(':').to_owned();
+			}
+		}
+	}
+	stacks.extend(current);
+	Some(Report { message, stacks })
+}
+
+/// Parses the first error reported by Valgrind Memcheck, which must be run with --fullpath-after= so that the frames
+/// contain full paths.
+pub fn parse_valgrind(stderr: &str) -> Option<Report> {
+	static LINE: Lazy<Regex> = Lazy::new(|| Regex::new("^==[0-9]+==(.*)$").unwrap());
+	static FRAME: Lazy<Regex> = Lazy::new(|| Regex::new("^\\s+(?:at|by) 0x[0-9A-F]+: ").unwrap());
+	static LOCATION: Lazy<Regex> = Lazy::new(|| Regex::new(": (.*) \\((\\S+):([0-9]+)\\)$").unwrap());
+	let mut stacks: Vec<Stack> = Vec::new();
+	let mut title = String::new();
+	let mut current: Option<Stack> = None;
+	for line in stderr.lines().filter_map(|line| LINE.captures(line)) {
+		let line = line.get(1).unwrap().as_str();
+		if FRAME.is_match(line) {
+			let stack = current.get_or_insert_with(|| Stack { title: title.clone(), frames: Vec::new() });
+			if let Some(cap) = LOCATION.captures(line) {
+				stack.frames.push(Frame::new(&cap[1], &cap[2], cap[3].parse().unwrap()));
+			}
+		} else {
+			stacks.extend(current.take());
+			// Errors are separated by empty lines, and only the first one is interesting.
+			if line.trim().is_empty() && !stacks.is_empty() {
+				break;
+			}
+			title = line.trim().to_owned();
+		}
+	}
+	stacks.extend(current);
+	let message = stacks.first()?.title.clone();
+	Some(Report { message, stacks })
+}
+
+/// Displays the raw tool output and marks the report's stack frames in the workspace source files.
+pub async fn show(tool: &str, report: Option<Report>, run: &Run) -> R<()> {
+	thread_local! {
+		static OUTPUT_CHANNEL: Lazy<OutputChannel> = Lazy::new(|| OutputChannel::new("ICIE Memory Check"));
+		static DIAGNOSTICS: Lazy<DiagnosticCollection> = Lazy::new(|| DiagnosticCollection::new("ICIE Memory Check"));
+	}
+	OUTPUT_CHANNEL.with(|output| {
+		output.clear();
+		output.append(&run.stderr);
+	});
+	DIAGNOSTICS.with(|diagnostics| diagnostics.clear());
+	let report = match report {
+		Some(report) => report,
+		// The program can also fail for other reasons, like the checks enabled by _GLIBCXX_DEBUG.
+		None if !run.success() => {
+			let message = format!("{} did not report any errors, but the program failed", tool);
+			return Err(evscode::E::error(message).extended(&run.stderr));
+		},
+		None => {
+			evscode::Message::new::<()>(&format!("{} did not find any errors on this test", tool)).show().await;
+			return Ok(());
+		},
+	};
+	OUTPUT_CHANNEL.with(|output| output.show(true));
+	let root = workspace_root()?;
+	let in_workspace = |frame: &&Frame| Path::from_native(frame.path.clone()).strip_prefix(&root).is_ok();
+	let mut stacks = report.stacks.iter();
+	let top = stacks.next().and_then(|stack| {
+		let mut frames = stack.frames.iter().filter(in_workspace);
+		frames.next().map(|top| (top, frames))
+	});
+	let (top, callers) = match top {
+		Some(top) => top,
+		None => return Err(evscode::E::error(format!("{}: {}", tool, report.message)).extended(&run.stderr)),
+	};
+	let mut related: Vec<Related> =
+		callers.map(|frame| related_frame(frame, format!("called from {}", frame.function))).collect();
+	related.extend(stacks.filter_map(|stack| {
+		let frame = stack.frames.iter().find(in_workspace)?;
+		Some(related_frame(frame, stack.title.clone()))
+	}));
+	let message = format!("{}: {}", tool, report.message);
+	let diagnostic = Diagnostic { range: line_range(top.line), message, severity: Severity::Error, related };
+	DIAGNOSTICS.with(|diagnostics| diagnostics.set(&top.path, &[diagnostic]));
+	evscode::open_editor(&top.path).cursor(line_range(top.line).start).open().await
+}
+
+fn related_frame(frame: &Frame, message: String) -> Related {
+	Related { path: frame.path.clone(), range: line_range(frame.line), message }
+}
+
+fn line_range(line: usize) -> Range {
+	let position = || Position { line: line.saturating_sub(1), column: 0 };
+	Range { start: position(), end: position() }
+}
+
+#[test]
+fn test_parse_sanitizer() {
+	let stderr = "=================================================================
+==6255==ERROR: AddressSanitizer: heap-buffer-overflow on address 0x60300000005c at pc 0x55cba8b733bb bp 0x7ffc58fb91f0
+WRITE of size 4 at 0x60300000005c thread T0
+    #0 0x55cba8b733ba in f(std::vector<int, std::allocator<int> >&, int) /tmp/a.cpp:5
+    #1 0x55cba8b734af in main /tmp/a.cpp:9:3
+    #2 0x7f1124645249  (/lib/x86_64-linux-gnu/libc.so.6+0x27249)
+
+0x60300000005c is located 8 bytes to the right of 20-byte region [0x603000000040,0x603000000054)
+allocated by thread T0 here:
+    #0 0x7f11254b94c8 in operator new(unsigned long) ../../../../src/libsanitizer/asan/asan_new_delete.cpp:95
+    #1 0x55cba8b7347e in main /tmp/a.cpp:8
+
+SUMMARY: AddressSanitizer: heap-buffer-overflow /tmp/a.cpp:5 in f(std::vector<int, std::allocator<int> >&, int)
+";
+	let report = parse_sanitizer(stderr).unwrap();
+	assert_eq!(report.message, "AddressSanitizer: heap-buffer-overflow on address 0x60300000005c");
+	assert_eq!(report.stacks.len(), 2);
+	assert_eq!(report.stacks[0].title, "WRITE of size 4 at 0x60300000005c thread T0");
+	assert_eq!(report.stacks[0].frames[1], Frame::new("main", "/tmp/a.cpp", 9));
+	assert_eq!(report.stacks[1].title, "allocated by thread T0 here");
+	assert_eq!(report.stacks[1].frames.len(), 2);
+	let report = parse_sanitizer("a.cpp:3:7: runtime error: signed integer overflow\n").unwrap();
+	assert_eq!(report.stacks[0].frames[0].line, 3);
+	assert!(parse_sanitizer("").is_none());
+}
+
+#[test]
+fn test_parse_valgrind() {
+	let stderr = "==123== Memcheck, a memory error detector
+==123== Command: ./a
+==123==
+==123== Invalid write of size 4
+==123==    at 0x1091A6: f(std::vector<int, std::allocator<int> >&, int) (/tmp/a.cpp:5)
+==123==    by 0x109213: main (/tmp/a.cpp:9)
+==123==  Address 0x4dd4c9c is 8 bytes after a block of size 20 alloc'd
+==123==    at 0x4843F93: operator new(unsigned long) (in /usr/libexec/valgrind/vgpreload_memcheck-amd64-linux.so)
+==123==    by 0x109A5B: main (/tmp/a.cpp:8)
+==123==
+==123== Invalid read of size 4
+==123==    at 0x1091B0: main (/tmp/a.cpp:10)
+";
+	let report = parse_valgrind(stderr).unwrap();
+	assert_eq!(report.message, "Invalid write of size 4");
+	assert_eq!(report.stacks.len(), 2);
+	assert_eq!(report.stacks[0].frames.len(), 2);
+	assert_eq!(report.stacks[1].title, "Address 0x4dd4c9c is 8 bytes after a block of size 20 alloc'd");
+	assert_eq!(report.stacks[1].frames, [Frame::new("main", "/tmp/a.cpp", 8)]);
+	assert!(parse_valgrind("==123== All heap blocks were freed -- no leaks are possible\n").is_none());
+}
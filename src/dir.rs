@@ -1,4 +1,6 @@
-use crate::util::{path::Path, workspace_root};
+use crate::{
+	compile::Language, util::{fs, path::Path, workspace_root}
+};
 use evscode::R;
 
 /// File stem of the mail source file. This is the optimal solution, which should be tested, sent to judging systems for
@@ -35,6 +37,11 @@ pub static CHECKER_STEM: evscode::Config<String> = "checker";
 #[evscode::config]
 pub static CPP_EXTENSION: evscode::Config<String> = "cpp";
 
+/// The programming language used for new solutions and brute force solutions. Sources that already exist are
+/// recognized by their extension regardless of this setting, so e.g. main.py will be used even if this is set to C++.
+#[evscode::config]
+pub static LANGUAGE: evscode::Config<Language> = Language::Cpp;
+
 /// The directory used for storing test cases. Usually, the directory will contain other subdirectories with files
 /// called ID.in or ID.out. For example, if this is set to "tests", test paths may look like tests/example/1.in or
 /// tests/user/3.out.
@@ -51,20 +58,34 @@ static CUSTOM_TESTS_SUBDIRECTORY: evscode::Config<String> = "user";
 #[evscode::config]
 pub static PROJECT_DIRECTORY: evscode::Config<Path> = "~";
 
-pub fn solution() -> R<Path> {
-	Ok(workspace_root()?.join(&*SOLUTION_STEM.get()).with_extension(&*CPP_EXTENSION.get()))
+pub async fn solution() -> R<Path> {
+	find_source(&workspace_root()?, &SOLUTION_STEM.get(), LANGUAGE.get()).await
+}
+
+pub async fn brute_force() -> R<Path> {
+	find_source(&workspace_root()?, &BRUTE_FORCE_STEM.get(), LANGUAGE.get()).await
 }
 
-pub fn brute_force() -> R<Path> {
-	Ok(workspace_root()?.join(&*BRUTE_FORCE_STEM.get()).with_extension(&*CPP_EXTENSION.get()))
+pub async fn test_generator() -> R<Path> {
+	find_source(&workspace_root()?, &TEST_GENERATOR_STEM.get(), Language::Cpp).await
 }
 
-pub fn test_generator() -> R<Path> {
-	Ok(workspace_root()?.join(&*TEST_GENERATOR_STEM.get()).with_extension(&*CPP_EXTENSION.get()))
+pub async fn checker() -> R<Path> {
+	find_source(&workspace_root()?, &CHECKER_STEM.get(), Language::Cpp).await
 }
 
-pub fn checker() -> R<Path> {
-	Ok(workspace_root()?.join(&*CHECKER_STEM.get()).with_extension(&*CPP_EXTENSION.get()))
+/// Finds a source with the given stem written in any supported language. If none exists, returns the path it should be
+/// created at in the default language.
+pub async fn find_source(directory: &Path, stem: &str, default: Language) -> R<Path> {
+	let path = directory.join(stem);
+	let others = Language::LIST.iter().copied().filter(|language| *language != default);
+	for language in std::iter::once(default).chain(others) {
+		let source = path.with_extension(&language.extension());
+		if fs::exists(&source).await? {
+			return Ok(source);
+		}
+	}
+	Ok(path.with_extension(&default.extension()))
 }
 
 pub fn tests() -> R<Path> {
@@ -1,4 +1,4 @@
-use crate::util::{node_hrtime, path::Path, sleep, workspace_root};
+use crate::util::{node_hrtime, path::Path, sleep, workspace_root, OS};
 use evscode::{E, R};
 use futures::{
 	channel::{mpsc, oneshot}, future::join3, FutureExt, StreamExt
@@ -9,6 +9,9 @@ use std::{
 };
 use wasm_bindgen::{closure::Closure, JsCast, JsValue, __rt::core::pin::Pin};
 
+/// Printed by the shell wrapper setting the stack size when ulimit fails, to tell it apart from the program failing.
+const ULIMIT_FAILED: &str = "icie: ulimit -s failed";
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum ExitKind {
 	Normal,
@@ -38,25 +41,54 @@ pub struct Environment {
 #[derive(Debug, Clone)]
 pub struct Executable {
 	pub command: String,
+	/// Arguments passed before the ones given to [`Executable::run`], used by interpreted languages to pass the source
+	/// or class path to the interpreter.
+	pub args: Vec<String>,
+	/// Stack size in bytes, set before running the program on Linux, which ignores the size stored in the executable.
+	/// Other systems get it from the linker flags instead.
+	pub stack_size: Option<u64>,
 }
 
 impl Executable {
 	pub fn new(path: Path) -> Executable {
-		Executable { command: path.into_string() }
+		Executable { command: path.into_string(), args: Vec::new(), stack_size: None }
 	}
 
 	pub fn new_name(command: String) -> Executable {
-		Executable { command }
+		Executable { command, args: Vec::new(), stack_size: None }
+	}
+
+	pub fn new_interpreted(command: String, args: Vec<String>) -> Executable {
+		Executable { command, args, stack_size: None }
+	}
+
+	/// Returns the same executable running with the given stack size. Tools like Valgrind run the solution as their
+	/// child process, so they have to be started with the stack size of the solution.
+	pub fn with_stack_size(&self, stack_size: Option<u64>) -> Executable {
+		Executable { stack_size, ..self.clone() }
 	}
 
 	pub async fn run(&self, input: &str, args: &[&str], environment: &Environment) -> R<Run> {
+		let mut command = self.command.as_str();
+		let mut wrapper_args = Vec::new();
+		let stack_size = self.stack_size.filter(|_| matches!(OS::query(), Ok(OS::Linux)));
+		if let Some(stack_size) = stack_size {
+			// The shell raises its own limit and then replaces itself with the program, passing it the arguments.
+			command = "sh";
+			let script = format!(
+				"ulimit -s {} 2>/dev/null || {{ echo '{}' >&2; exit 1; }}; exec \"$0\" \"$@\"",
+				stack_size / 1024,
+				ULIMIT_FAILED
+			);
+			wrapper_args.extend(vec!["-c".to_owned(), script, self.command.clone()]);
+		}
 		let js_args = js_sys::Array::new();
-		for arg in args {
+		for arg in wrapper_args.iter().chain(&self.args).map(String::as_str).chain(args.iter().copied()) {
 			js_args.push(&JsValue::from_str(arg));
 		}
 		let input_buffer = node_sys::buffer::Buffer::from(js_sys::Uint8Array::from(input.as_bytes()));
 		let cwd = environment.cwd.clone().or_else(|| workspace_root().ok());
-		let kid = node_sys::child_process::spawn(&self.command, js_args, node_sys::child_process::Options {
+		let kid = node_sys::child_process::spawn(command, js_args, node_sys::child_process::Options {
 			cwd: cwd.as_ref().map(Path::as_str),
 			env: None,
 			argv0: None,
@@ -99,6 +131,14 @@ impl Executable {
 		let exit_kind = if timed_out.load(SeqCst) { ExitKind::TimeLimitExceeded } else { ExitKind::Normal };
 		let stdout = String::from_utf8_lossy(&stdout).into_owned();
 		let stderr = String::from_utf8_lossy(&stderr).into_owned();
+		if let Some(stack_size) = stack_size {
+			if exit_code == Some(1) && stderr.trim_end() == ULIMIT_FAILED {
+				return Err(E::error(format!(
+					"could not raise the stack size limit to {} MB, the hard limit shown by ulimit -Hs is lower",
+					stack_size / 1024 / 1024
+				)));
+			}
+		}
 		Ok(Run { stdout, stderr, exit_code, exit_kind, time: t2 - t1 })
 	}
 }
@@ -1,5 +1,5 @@
 use crate::{
-	assets, dir, logger, manifest::Manifest, net::{interpret_url, require_task}, open, util::{self, fs, workspace_root}
+	assets, dir, logger, manifest::Manifest, net::{interpret_url, require_task}, open, util::{self, fs, path::Path, workspace_root}
 };
 use evscode::{error::ResultExt, quick_pick, webview::WebviewMeta, QuickPick, E, R};
 use futures::StreamExt;
@@ -24,20 +24,25 @@ pub async fn deactivate() -> R<()> {
 
 pub async fn layout_setup() -> R<()> {
 	let _status = crate::STATUS.push("Opening");
-	if let Ok(manifest) = Manifest::load().await {
-		place_cursor_in_code().await;
+	if let Ok(mut manifest) = Manifest::load().await {
+		// The cursor position from the template is only valid until the solution is edited.
+		let cursor = manifest.cursor.take();
+		place_cursor_in_code(cursor).await;
 		if manifest.statement.is_some() {
 			statement().await?;
 		}
 		// Refocus the cursor, because apparently preserve_focus is useless.
-		place_cursor_in_code().await;
+		place_cursor_in_code(cursor).await;
+		if cursor.is_some() {
+			manifest.save(&workspace_root()?).await?;
+		}
 	}
 	Ok(())
 }
 
-async fn place_cursor_in_code() {
-	if let Ok(solution) = dir::solution() {
-		let _ = util::open_source(&solution).await;
+async fn place_cursor_in_code(cursor: Option<(usize, usize)>) {
+	if let Ok(solution) = dir::solution().await {
+		let _ = util::open_source_at(&solution, cursor).await;
 	}
 }
 
@@ -84,6 +89,10 @@ async fn statement() -> R<()> {
 
 #[evscode::command(title = "ICIE Launch nearby", key = "alt+backspace")]
 async fn nearby() -> R<()> {
+	let folders = evscode::workspace_folders();
+	if folders.len() > 1 {
+		return nearby_in_workspace(folders).await;
+	}
 	let parent = workspace_root()?.parent();
 	let mut nearby =
 		fs::read_dir(&parent).await?.into_iter().map(|path| (path.fmt_relative(&parent), path)).collect::<Vec<_>>();
@@ -97,6 +106,23 @@ async fn nearby() -> R<()> {
 	Ok(())
 }
 
+/// Switches to another task from a multi-root workspace by opening its solution, which does not reload the window.
+async fn nearby_in_workspace(folders: Vec<String>) -> R<()> {
+	// Without an active editor the current task is unknown, so all of them are listed.
+	let current = workspace_root().ok();
+	let mut nearby =
+		folders.into_iter().map(Path::from_native).filter(|path| current.as_ref() != Some(path)).collect::<Vec<_>>();
+	nearby.sort();
+	let path = QuickPick::new()
+		.items(nearby.into_iter().map(|path| quick_pick::Item::new(path.clone(), path.file_name())))
+		.show()
+		.await
+		.ok_or_else(E::cancel)?;
+	let solution = dir::find_source(&path, &dir::SOLUTION_STEM.get(), dir::LANGUAGE.get()).await?;
+	util::open_source(&solution).await?;
+	layout_setup().await
+}
+
 #[evscode::command(title = "ICIE Web Task")]
 async fn web_task() -> R<()> {
 	let manifest = Manifest::load().await?;
@@ -6,6 +6,7 @@ use once_cell::sync::Lazy;
 
 mod assets;
 mod auth;
+mod bundle;
 mod checker;
 mod compile;
 mod debug;
@@ -18,6 +19,7 @@ mod net;
 mod newsletter;
 mod open;
 mod paste;
+mod profile;
 mod service;
 mod stress;
 mod submit;
@@ -3,12 +3,19 @@ use evscode::{error::ResultExt, R};
 use serde::{Deserialize, Serialize};
 use unijudge::Statement;
 
+pub use icie_common::compile::TaskFlags;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Manifest {
 	#[serde(default)]
 	pub task_url: Option<String>,
 	#[serde(default)]
 	pub statement: Option<Statement>,
+	#[serde(default)]
+	pub flags: TaskFlags,
+	/// Line and column where the cursor is placed when the task is opened for the first time, marked in the template.
+	#[serde(default)]
+	pub cursor: Option<(usize, usize)>,
 }
 
 impl Manifest {
@@ -27,6 +34,13 @@ impl Manifest {
 		Ok(manifest)
 	}
 
+	/// Loads the manifest of a task in a given directory, which does not have to be open.
+	pub async fn load_in(workspace: &Path) -> R<Manifest> {
+		let s = fs::read_to_string(&workspace.join(".icie")).await?;
+		let manifest = serde_json::from_str(&s).wrap(".icie is not a valid icie::manifest::Manifest")?;
+		Ok(manifest)
+	}
+
 	pub fn req_statement(&self) -> R<&Statement> {
 		self.statement.as_ref().wrap("this folder has no downloaded task description").map_err(suggest_open)
 	}
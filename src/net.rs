@@ -1,4 +1,4 @@
-use crate::{auth, util::retries::Retries};
+use crate::{auth, compile::Language, util::retries::Retries};
 use evscode::{
 	error::{ResultExt, Severity}, E, R
 };
@@ -12,11 +12,51 @@ const RETRY_LIMIT: usize = 4;
 const RETRY_DELAY: Duration = Duration::from_secs(10);
 
 pub static BACKENDS: [BackendMeta; 5] = [
-	BackendMeta { backend: &unijudge_atcoder::AtCoder, cpp: &["C++ (GCC 9.2.1)", "C++14 (GCC 5.4.1)"] },
-	BackendMeta { backend: &unijudge_codechef::CodeChef, cpp: &["C++14(gcc 6.3)"] },
-	BackendMeta { backend: &unijudge_codeforces::Codeforces, cpp: &["GNU G++17 7.3.0"] },
-	BackendMeta { backend: &unijudge_sio2::Sio2, cpp: &["C++", "C++ (g++:8.3 std=c++17)"] },
-	BackendMeta { backend: &unijudge_spoj::SPOJ, cpp: &["C++14 (clang 8.0)"] },
+	BackendMeta {
+		backend: &unijudge_atcoder::AtCoder,
+		cpp: &["C++ (GCC 9.2.1)", "C++14 (GCC 5.4.1)"],
+		python: &["Python (3.8.2)", "Python3 (3.4.3)"],
+		rust: &["Rust (1.42.0)", "Rust (1.15.1)"],
+		java: &["Java (OpenJDK 11.0.6)", "Java8 (OpenJDK 1.8.0)"],
+		kotlin: &["Kotlin (1.3.71)", "Kotlin (1.0.0)"],
+		source_limit: 524288,
+	},
+	BackendMeta {
+		backend: &unijudge_codechef::CodeChef,
+		cpp: &["C++14(gcc 6.3)"],
+		python: &["PYTH 3.6(python 3.6)"],
+		rust: &["RUST(rust 1.14.0)"],
+		java: &["JAVA(HotSpot 8u112)"],
+		kotlin: &["KTLN(kotlin 1.1.3)"],
+		source_limit: 50000,
+	},
+	BackendMeta {
+		backend: &unijudge_codeforces::Codeforces,
+		cpp: &["GNU G++17 7.3.0"],
+		python: &["Python 3.7.2", "Python 3.8"],
+		rust: &["Rust 1.42.0", "Rust 1.35.0"],
+		java: &["Java 11.0.6", "Java 1.8.0_162"],
+		kotlin: &["Kotlin 1.3.70", "Kotlin 1.3.10"],
+		source_limit: 65536,
+	},
+	BackendMeta {
+		backend: &unijudge_sio2::Sio2,
+		cpp: &["C++", "C++ (g++:8.3 std=c++17)"],
+		python: &["Python"],
+		rust: &[],
+		java: &["Java"],
+		kotlin: &[],
+		source_limit: 102400,
+	},
+	BackendMeta {
+		backend: &unijudge_spoj::SPOJ,
+		cpp: &["C++14 (clang 8.0)"],
+		python: &["Python 3 (python 3.7.3)"],
+		rust: &["Rust (rust 1.33.0)"],
+		java: &["Java (HotSpot 12)"],
+		kotlin: &["Kotlin (kotlin 1.0.6)"],
+		source_limit: 50000,
+	},
 ];
 
 pub struct Session {
@@ -25,10 +65,46 @@ pub struct Session {
 	site: String,
 }
 
+/// Judge-specific metadata. The language names must match the ones returned by [`Backend::task_languages`], apart
+/// from whitespace, see [`BackendMeta::find_language`].
 #[derive(Debug)]
 pub struct BackendMeta {
 	pub backend: &'static dyn DynamicBackend,
 	pub cpp: &'static [&'static str],
+	pub python: &'static [&'static str],
+	pub rust: &'static [&'static str],
+	pub java: &'static [&'static str],
+	pub kotlin: &'static [&'static str],
+	/// Maximum size of the submitted source code in bytes.
+	pub source_limit: usize,
+}
+
+impl BackendMeta {
+	pub fn language_names(&self, language: Language) -> &'static [&'static str] {
+		match language {
+			Language::Cpp => self.cpp,
+			Language::Python => self.python,
+			Language::Rust => self.rust,
+			Language::Java => self.java,
+			Language::Kotlin => self.kotlin,
+		}
+	}
+
+	/// Finds the judge language used for submitting solutions written in a given language. If the judge offers more
+	/// than one of the known names, the first one on the judge's list is used, like it would be by picking the language
+	/// on the website. Whitespace is collapsed before comparing, because judges sometimes put multiple spaces in names.
+	pub fn find_language<'a>(
+		&self,
+		languages: &'a [unijudge::Language],
+		language: Language,
+	) -> Option<&'a unijudge::Language> {
+		let names = self.language_names(language);
+		languages.iter().find(|lang| names.iter().any(|name| collapse_whitespace(&lang.name) == *name))
+	}
+}
+
+fn collapse_whitespace(name: &str) -> String {
+	name.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
 pub fn interpret_url(url: &str) -> R<(BoxedURL, &'static BackendMeta)> {
@@ -176,3 +252,14 @@ async fn help_urls() -> R<()> {
 	evscode::open_external("https://github.com/pustaczek/icie/blob/master/docs/URLS.md").await?;
 	Ok(())
 }
+
+#[test]
+fn test_language_names_collapsed() {
+	for backend in &BACKENDS {
+		for language in Language::LIST {
+			for name in backend.language_names(*language) {
+				assert_eq!(collapse_whitespace(name), *name);
+			}
+		}
+	}
+}
@@ -1,5 +1,5 @@
 use crate::{
-	dir::PROJECT_DIRECTORY, net::{self, BackendMeta}, open::scan::ContestMeta, util::fs
+	net::{self, BackendMeta}, open::scan::ContestMeta, util::fs
 };
 use evscode::{quick_pick, QuickPick, E, R};
 use std::sync::Arc;
@@ -10,6 +10,7 @@ use unijudge::{
 pub mod contest;
 mod files;
 pub mod names;
+mod refresh;
 mod scan;
 
 enum Command {
@@ -71,8 +72,7 @@ pub async fn url() -> R<()> {
 	match Command::from_url(raw_url.as_ref())? {
 		Command::Task(url) => {
 			let details = fetch_task_details(&url).await?;
-			let projects_dir = PROJECT_DIRECTORY.get();
-			let workspace = names::design_task_name(&projects_dir, details.as_ref()).await?;
+			let workspace = names::design_task_name(details.as_ref(), raw_url.as_deref(), None).await?;
 			fs::create_dir_all(&workspace).await?;
 			files::open_task(&workspace, raw_url, details).await?;
 			evscode::open_folder(workspace.as_str(), false).await;
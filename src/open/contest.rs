@@ -1,11 +1,14 @@
 use crate::{
-	auth, compile::suggest_install_compiler, net::{interpret_url, require_contest, Session}, open::{
-		files, names::{design_contest_name, design_task_name}
-	}, util::{self, fs, path::Path, sleep, time_now, workspace_root}
+	auth, compile::suggest_install_compiler, net::{interpret_url, require_contest, Session}, open::{files, names::design_task_name}, util::{
+		self, fs, letter_case::Case, path::Path, retries::Retries, sleep, time_now, workspace_root
+	}
+};
+use evscode::{
+	error::{ResultExt, Severity}, E, R
 };
-use evscode::{error::ResultExt, E, R};
 use futures::{select, FutureExt};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::{
 	cmp::min, sync::Arc, time::{Duration, SystemTime}
 };
@@ -19,10 +22,25 @@ use unijudge::{
 #[derive(Deserialize, Serialize)]
 struct Manifest {
 	contest_url: String,
+	#[serde(default)]
+	contest_title: String,
+	#[serde(default)]
+	multi_root: bool,
 }
 
+/// Open all tasks of a contest at once, in a multi-root workspace saved to a .code-workspace file next to the task
+/// directories. Switching between tasks with Alt+Backspace will then not reload the window, and commands will apply to
+/// the task of the file in the active editor.
+#[evscode::config]
+static MULTI_ROOT: evscode::Config<bool> = false;
+
 const NOT_YET_STARTED_RETRY_LIMIT: usize = 15;
 const NOT_YET_STARTED_RETRY_DELAY: Duration = Duration::from_secs(1);
+const CONCURRENT_FETCH_LIMIT: usize = 4;
+const TASK_RETRY_LIMIT: usize = 2;
+const TASK_RETRY_DELAY: Duration = Duration::from_secs(1);
+const WORKSPACE_UPDATE_RETRY_LIMIT: usize = 20;
+const WORKSPACE_UPDATE_RETRY_DELAY: Duration = Duration::from_millis(100);
 
 /// Wait for the contest, set up the first task, save a contest manifest and switch to the directory. This will likely
 /// kill the extension process, so do not do anything important after calling this function. Post-setup steps will
@@ -30,20 +48,40 @@ const NOT_YET_STARTED_RETRY_DELAY: Duration = Duration::from_secs(1);
 pub async fn sprint(sess: Arc<Session>, contest: &BoxedContest, contest_title: Option<&str>) -> R<()> {
 	let _status = crate::STATUS.push("Opening");
 	let contest_title = fetch_contest_title(&sess, contest, contest_title).await?;
-	let projects = design_contest_name(&contest_title).await?;
-	fs::create_dir_all(&projects).await?;
 	let url_raw = sess.backend.backend.contest_url(contest);
 	let url = require_contest(interpret_url(&url_raw)?.0)?;
 	wait_for_contest(&url_raw, &url.site, &sess).await?;
 	let Resource::Contest(contest) = url.resource;
 	let tasks = fetch_tasks(&sess, &contest).await?;
+	if MULTI_ROOT.get() {
+		return open_multi_root(&tasks, &url_raw, &contest_title, &sess).await;
+	}
 	let task0 = tasks.get(0).wrap("could not find any tasks in contest")?;
-	let task0_path = open_task(task0, 0, tasks.len(), &projects, &sess).await?;
-	create_contest_manifest(&task0_path, &url_raw).await?;
+	let task0_path = open_task(task0, 0, tasks.len(), &contest_title, &sess).await?;
+	create_contest_manifest(&task0_path, &url_raw, &contest_title, false).await?;
 	evscode::open_folder(task0_path.as_str(), false).await;
 	Ok(())
 }
 
+/// Sets up the first task and opens it in a multi-root workspace. The remaining tasks are added to the workspace one by
+/// one as they are set up, which does not reload the window as long as the first folder stays the same.
+async fn open_multi_root(tasks: &[BoxedTask], contest_url: &str, contest_title: &str, sess: &Session) -> R<()> {
+	let task0 = tasks.get(0).wrap("could not find any tasks in contest")?;
+	let task0_path = open_task(task0, 0, tasks.len(), contest_title, sess).await?;
+	create_contest_manifest(&task0_path, contest_url, contest_title, true).await?;
+	let directory = task0_path.parent();
+	let folders = [json!({ "path": task0_path.fmt_relative(&directory) })];
+	let content = serde_json::to_string_pretty(&json!({ "folders": folders })).unwrap();
+	let name = match Case::Kebab.apply(contest_title) {
+		name if name.is_empty() => "contest".to_owned(),
+		name => name,
+	};
+	let path = directory.join(format!("{}.code-workspace", name));
+	fs::write(&path, content).await?;
+	evscode::open_folder(path.as_str(), false).await;
+	Ok(())
+}
+
 async fn fetch_contest_title(sess: &Session, contest: &BoxedContest, title: Option<&str>) -> R<String> {
 	let title = match title {
 		Some(title) => title.to_owned(),
@@ -116,23 +154,57 @@ fn spawn_suggest_install_compiler() {
 	evscode::spawn(suggest_install_compiler());
 }
 
-async fn open_task(task: &BoxedTask, index: usize, count: usize, projects: &Path, sess: &Session) -> R<Path> {
+async fn open_task(task: &BoxedTask, index: usize, count: usize, contest_title: &str, sess: &Session) -> R<Path> {
 	let name = format!("{}/{}", index + 1, count);
 	let details = fetch_task(task, &name, sess).await?;
 	let url = sess.run(|backend, sess| async move { backend.task_url(sess, task) }).await?;
-	let workspace = design_task_name(projects, Some(&details)).await?;
+	let workspace = design_task_name(Some(&details), Some(&url), Some(contest_title)).await?;
 	files::open_task(&workspace, Some(url), Some(details)).await?;
 	Ok(workspace)
 }
 
+/// Adds a task directory at the end of the workspace. VS Code refuses to do this while the previous update is still in
+/// progress, which happens when several tasks are set up at the same time, so it is retried for a while.
+async fn add_to_workspace(workspace: &Path) -> R<()> {
+	let mut retries = Retries::new(WORKSPACE_UPDATE_RETRY_LIMIT, WORKSPACE_UPDATE_RETRY_DELAY);
+	while !evscode::add_workspace_folders(&[workspace.as_str().to_owned()]) {
+		if !retries.wait().await {
+			return Err(E::error(format!("could not add {} to the workspace", workspace.fmt_workspace())));
+		}
+	}
+	Ok(())
+}
+
+/// Sets up a task, retrying a few times so that a temporary problem with one task does not require opening it manually.
+async fn open_task_retrying(
+	task: &BoxedTask,
+	index: usize,
+	count: usize,
+	contest_title: &str,
+	sess: &Session,
+) -> R<Path> {
+	let mut retries = Retries::new(TASK_RETRY_LIMIT, TASK_RETRY_DELAY);
+	loop {
+		let result = open_task(task, index, count, contest_title, sess).await;
+		let retry = match &result {
+			Ok(_) => false,
+			Err(e) => e.0.severity != Severity::Cancel,
+		};
+		if !retry || !retries.wait().await {
+			break result;
+		}
+	}
+}
+
 async fn fetch_task(task: &BoxedTask, name: &str, sess: &Session) -> R<TaskDetails> {
 	let _status = crate::STATUS.push(format!("Fetching task {}", name));
 	sess.run(|backend, sess| backend.task_details(sess, task)).await
 }
 
-async fn create_contest_manifest(workspace: &Path, contest_url: &str) -> R<()> {
+async fn create_contest_manifest(workspace: &Path, contest_url: &str, contest_title: &str, multi_root: bool) -> R<()> {
 	let path = workspace.join(".icie-contest");
-	let manifest_data = Manifest { contest_url: contest_url.to_owned() };
+	let manifest_data =
+		Manifest { contest_url: contest_url.to_owned(), contest_title: contest_title.to_owned(), multi_root };
 	let manifest = serde_json::to_string(&manifest_data).wrap("serialization of contest manifest failed")?;
 	fs::write(&path, manifest).await?;
 	Ok(())
@@ -149,7 +221,8 @@ pub async fn check_for_manifest() -> R<()> {
 	Ok(())
 }
 
-/// Do the setup for the rest of the contest tasks.
+/// Do the setup for the rest of the contest tasks. Tasks are fetched concurrently, and a task that could not be opened
+/// does not stop the others from being set up. In a multi-root workspace, each task is added as soon as it is ready.
 async fn open_remaining_tasks(manifest: &Path) -> R<()> {
 	let _status = crate::STATUS.push("Opening");
 	let manifest = pop_manifest(manifest).await?;
@@ -158,11 +231,39 @@ async fn open_remaining_tasks(manifest: &Path) -> R<()> {
 	let sess = Session::connect(&url.domain, backend).await?;
 	let Resource::Contest(contest) = url.resource;
 	let tasks = fetch_tasks(&sess, &contest).await?;
-	let projects = workspace_root()?.parent();
-	for (i, task) in tasks.iter().enumerate() {
-		if i > 0 {
-			open_task(task, i, tasks.len(), &projects, &sess).await?;
-		}
+	let results = util::join_all_with_progress_bounded(
+		"Opening remaining tasks",
+		CONCURRENT_FETCH_LIMIT,
+		tasks
+			.iter()
+			.enumerate()
+			.skip(1)
+			.map(|(i, task)| open_remaining_task(task, i, tasks.len(), &manifest, &sess)),
+	)
+	.await;
+	let failures = results
+		.into_iter()
+		.enumerate()
+		.filter_map(|(i, result)| Some(format!("Task {}: {}", i + 2, result.err()?.human())))
+		.collect::<Vec<_>>();
+	if !failures.is_empty() {
+		return Err(E::error(format!("could not open {} of {} remaining tasks", failures.len(), tasks.len() - 1))
+			.extended(failures.join("\n")));
+	}
+	Ok(())
+}
+
+/// Sets up one of the remaining tasks, and adds it to the workspace when the contest is opened as a multi-root one.
+async fn open_remaining_task(
+	task: &BoxedTask,
+	index: usize,
+	count: usize,
+	manifest: &Manifest,
+	sess: &Session,
+) -> R<()> {
+	let workspace = open_task_retrying(task, index, count, &manifest.contest_title, sess).await?;
+	if manifest.multi_root {
+		add_to_workspace(&workspace).await?;
 	}
 	Ok(())
 }
@@ -10,29 +10,42 @@ pub async fn open_task(workspace: &Path, url: Option<String>, meta: Option<TaskD
 	let examples =
 		meta.as_ref().and_then(|meta| meta.examples.as_ref()).map(|examples| examples.as_slice()).unwrap_or(&[]);
 	let statement = meta.as_ref().and_then(|meta| meta.statement.clone());
-	create_manifest(workspace, &url, statement).await?;
-	create_template(workspace).await?;
+	let cursor = create_template(workspace, meta.as_ref()).await?;
+	create_manifest(workspace, &url, statement, cursor).await?;
 	create_examples(workspace, examples).await?;
 	Ok(())
 }
 
-async fn create_manifest(workspace: &Path, url: &Option<String>, statement: Option<Statement>) -> R<()> {
-	let manifest = crate::manifest::Manifest { task_url: url.clone(), statement };
+async fn create_manifest(
+	workspace: &Path,
+	url: &Option<String>,
+	statement: Option<Statement>,
+	cursor: Option<(usize, usize)>,
+) -> R<()> {
+	let manifest = crate::manifest::Manifest { task_url: url.clone(), statement, flags: Default::default(), cursor };
 	manifest.save(workspace).await?;
 	Ok(())
 }
 
-async fn create_template(workspace: &Path) -> R<()> {
-	let solution = workspace.join(format!("{}.{}", dir::SOLUTION_STEM.get(), dir::CPP_EXTENSION.get()));
-	if !fs::exists(&solution).await? {
-		let template = template::load_solution().await?;
-		fs::write(&solution, template.code).await?;
+/// Creates the solution from a template, returning where the cursor should be placed if the template specifies it.
+async fn create_template(workspace: &Path, meta: Option<&TaskDetails>) -> R<Option<(usize, usize)>> {
+	let solution = dir::find_source(workspace, &dir::SOLUTION_STEM.get(), dir::LANGUAGE.get()).await?;
+	if fs::exists(&solution).await? {
+		return Ok(None);
 	}
-	Ok(())
+	let template = template::load_solution(meta.map(|meta| meta.site_short.as_str())).await?;
+	let (code, cursor) = template::fill(&template.code, &template::Variables::new(meta));
+	fs::write(&solution, code).await?;
+	Ok(cursor)
+}
+
+/// Directory where the examples downloaded from the judge are saved.
+pub fn examples_directory(workspace: &Path) -> Path {
+	workspace.join("tests").join("example")
 }
 
 async fn create_examples(workspace: &Path, examples: &[Example]) -> R<()> {
-	let examples_dir = workspace.join("tests").join("example");
+	let examples_dir = examples_directory(workspace);
 	fs::create_dir_all(&examples_dir).await?;
 	for (i, test) in examples.iter().enumerate() {
 		let in_path = examples_dir.join(format!("{}.in", i + 1));
@@ -1,18 +1,70 @@
 use crate::{
-	dir, util::{letter_case::Case, path::Path}
+	dir, manifest::Manifest, util::{fs, letter_case::Case, path::Path}
 };
 use evscode::{E, R};
-use unijudge::TaskDetails;
+use unijudge::{chrono::Local, TaskDetails};
 
-pub async fn design_task_name(projects: &Path, meta: Option<&TaskDetails>) -> R<Path> {
-	Ok(match meta {
-		Some(meta) => projects.join(&format!("{}-{}", Case::Upper.apply(&meta.id), Case::Kebab.apply(&meta.title))),
-		None => query(projects).await?,
+/// Pattern of the paths of new task directories, relative to the project directory. The {site}, {contest},
+/// {contest_id}, {id}, {title} and {date} placeholders are replaced with kebab-case values, and {SITE}, {CONTEST} etc.
+/// with UPPER_CASE ones. {contest} is the contest title, which is empty when opening a single task; parts of the path
+/// that end up empty are skipped, and so are separators like "-" left at their ends. If the directory is already used
+/// by a different task, a number is added to its name. For example, set this to "{site}/{contest_id}/{ID}" to keep all
+/// tasks from a site in one place.
+#[evscode::config]
+static PATTERN: evscode::Config<String> = "{contest}/{ID}-{title}";
+
+/// Chooses the directory for a task, given the URL which will be saved in it and the contest title if opened as a part
+/// of a contest. Without task details, the user is asked to choose the directory.
+pub async fn design_task_name(meta: Option<&TaskDetails>, url: Option<&str>, contest: Option<&str>) -> R<Path> {
+	let projects = dir::PROJECT_DIRECTORY.get();
+	let meta = match meta {
+		Some(meta) => meta,
+		None => return query(&projects).await,
+	};
+	let date = Local::now().format("%Y-%m-%d").to_string();
+	let variables = [
+		("site", meta.site_short.as_str()),
+		("contest", contest.unwrap_or("")),
+		("contest_id", meta.contest_id.as_str()),
+		("id", meta.id.as_str()),
+		("title", meta.title.as_str()),
+		("date", date.as_str()),
+	];
+	let path = projects.join(expand(&PATTERN.get(), &variables));
+	let mut candidate = path.clone();
+	for i in 2.. {
+		if is_free_for(&candidate, &[url, Some(meta.url.as_str())]).await? {
+			break;
+		}
+		candidate = Path::from_native(format!("{}-{}", path.as_str(), i));
+	}
+	Ok(candidate)
+}
+
+/// Checks whether a directory does not exist yet or already contains a task with one of the given URLs.
+async fn is_free_for(path: &Path, urls: &[Option<&str>]) -> R<bool> {
+	if !fs::exists(path).await? {
+		return Ok(true);
+	}
+	Ok(match Manifest::load_in(path).await {
+		Ok(manifest) => manifest.task_url.is_some() && urls.contains(&manifest.task_url.as_deref()),
+		Err(_) => false,
 	})
 }
 
-pub async fn design_contest_name(contest_title: &str) -> R<Path> {
-	Ok(dir::PROJECT_DIRECTORY.get().join(&Case::Kebab.apply(contest_title)))
+/// Replaces the placeholders in a directory pattern, trimming the separators left around empty values and skipping the
+/// path components that end up empty.
+fn expand(pattern: &str, variables: &[(&str, &str)]) -> String {
+	let mut path = pattern.to_owned();
+	for (name, value) in variables {
+		path = path.replace(&format!("{{{}}}", name), &Case::Kebab.apply(value));
+		path = path.replace(&format!("{{{}}}", name.to_uppercase()), &Case::Upper.apply(value));
+	}
+	path.split(&['/', '\\'][..])
+		.map(|part| part.trim_matches(|c: char| c == '-' || c == '_' || c == '.' || c.is_whitespace()))
+		.filter(|part| !part.is_empty())
+		.collect::<Vec<_>>()
+		.join("/")
 }
 
 async fn query(basic: &Path) -> R<Path> {
@@ -27,3 +79,22 @@ async fn query(basic: &Path) -> R<Path> {
 			.ok_or_else(E::cancel)?,
 	))
 }
+
+#[test]
+fn test_expand() {
+	let variables = [
+		("site", "codeforces"),
+		("contest", "Codeforces Round #600 (Div. 2)"),
+		("contest_id", "1253"),
+		("id", "b"),
+		("title", "Silly Mistake"),
+		("date", "2019-11-16"),
+	];
+	assert_eq!(expand("{contest}/{ID}-{title}", &variables), "codeforces-round-600-div-2/B-silly-mistake");
+	assert_eq!(expand("{site}/{contest_id}/{id}", &variables), "codeforces/1253/b");
+	assert_eq!(expand("{date}-{CONTEST_ID}/{ID}", &variables), "2019-11-16-1253/B");
+	assert_eq!(expand("{contest}/{ID}-{title}", &[("contest", ""), ("id", "a"), ("title", "Sum")]), "A-sum");
+	assert_eq!(expand("{contest}/{ID}-{title}", &[("contest", ""), ("id", ""), ("title", "Sum")]), "sum");
+	assert_eq!(expand("{site}/{title}_{ID}", &[("site", "spoj"), ("id", ""), ("title", "Sum")]), "spoj/sum");
+	assert_eq!(expand("{unknown}/{id}", &[("id", "a")]), "{unknown}/a");
+}
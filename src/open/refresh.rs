@@ -0,0 +1,131 @@
+use crate::{
+	manifest::Manifest, net::{self, require_task}, open::files::examples_directory, util::{fs, path::Path, workspace_root}
+};
+use evscode::R;
+use unijudge::{
+	boxed::{BoxedContest, BoxedTask}, Example, Resource, TaskDetails
+};
+
+/// Differences between the examples saved in the task directory and the ones currently on the judge, as example
+/// numbers starting from 1.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ExampleChanges {
+	added: Vec<usize>,
+	changed: Vec<usize>,
+	removed: Vec<usize>,
+}
+
+#[evscode::command(title = "ICIE Refresh task")]
+async fn refresh() -> R<()> {
+	let _status = crate::STATUS.push("Refreshing");
+	let workspace = workspace_root()?;
+	let mut manifest = Manifest::load().await?;
+	let details = fetch_details(&manifest).await?;
+	let mut report = Vec::new();
+	// Sites that do not provide examples return None, in which case the ones entered earlier are left alone.
+	if let Some(examples) = &details.examples {
+		let examples_dir = examples_directory(&workspace);
+		let changes = compare_examples(&load_examples(&examples_dir).await?, examples);
+		save_examples(&examples_dir, examples, &changes).await?;
+		report.extend(describe(&changes));
+	}
+	if details.statement.is_some() && details.statement != manifest.statement {
+		manifest.statement = details.statement;
+		manifest.save(&workspace).await?;
+		report.push("statement updated".to_owned());
+	}
+	let message = if report.is_empty() {
+		"Task is already up to date".to_owned()
+	} else {
+		format!("Task refreshed, {}", report.join(", "))
+	};
+	evscode::Message::new::<()>(&message).show().await;
+	Ok(())
+}
+
+async fn fetch_details(manifest: &Manifest) -> R<TaskDetails> {
+	let (url, backend) = net::interpret_url(manifest.req_task_url()?)?;
+	let url = require_task::<BoxedContest, BoxedTask>(url)?;
+	let Resource::Task(task) = &url.resource;
+	let sess = net::Session::connect(&url.domain, backend).await?;
+	let _status = crate::STATUS.push("Fetching task");
+	sess.run(|backend, sess| backend.task_details(sess, task)).await
+}
+
+/// Loads the examples saved in the task directory, which are numbered consecutively starting from 1.
+async fn load_examples(examples_dir: &Path) -> R<Vec<Example>> {
+	let mut examples = Vec::new();
+	loop {
+		let in_path = examples_dir.join(format!("{}.in", examples.len() + 1));
+		let out_path = examples_dir.join(format!("{}.out", examples.len() + 1));
+		if !fs::exists(&in_path).await? {
+			break;
+		}
+		let input = fs::read_to_string(&in_path).await?;
+		let output = if fs::exists(&out_path).await? { fs::read_to_string(&out_path).await? } else { String::new() };
+		examples.push(Example { input, output });
+	}
+	Ok(examples)
+}
+
+/// Writes the added and changed examples and deletes the removed ones. Other test directories are never touched.
+async fn save_examples(examples_dir: &Path, examples: &[Example], changes: &ExampleChanges) -> R<()> {
+	fs::create_dir_all(examples_dir).await?;
+	for &i in changes.added.iter().chain(&changes.changed) {
+		fs::write(&examples_dir.join(format!("{}.in", i)), &examples[i - 1].input).await?;
+		fs::write(&examples_dir.join(format!("{}.out", i)), &examples[i - 1].output).await?;
+	}
+	for &i in &changes.removed {
+		for extension in &["in", "out"] {
+			let path = examples_dir.join(format!("{}.{}", i, extension));
+			if fs::exists(&path).await? {
+				fs::remove_file(&path).await?;
+			}
+		}
+	}
+	Ok(())
+}
+
+fn compare_examples(old: &[Example], new: &[Example]) -> ExampleChanges {
+	let mut changes = ExampleChanges::default();
+	for i in 0..old.len().max(new.len()) {
+		match (old.get(i), new.get(i)) {
+			(Some(old), Some(new)) if old != new => changes.changed.push(i + 1),
+			(None, Some(_)) => changes.added.push(i + 1),
+			(Some(_), None) => changes.removed.push(i + 1),
+			_ => (),
+		}
+	}
+	changes
+}
+
+fn describe(changes: &ExampleChanges) -> Vec<String> {
+	let groups = [(&changes.added, "added"), (&changes.changed, "changed"), (&changes.removed, "removed")];
+	groups
+		.iter()
+		.filter(|(numbers, _)| !numbers.is_empty())
+		.map(|(numbers, verb)| {
+			let noun = if numbers.len() == 1 { "example" } else { "examples" };
+			let numbers = numbers.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", ");
+			format!("{} {} {}", noun, numbers, verb)
+		})
+		.collect()
+}
+
+#[test]
+fn test_compare_examples() {
+	let example = |input: &str, output: &str| Example { input: input.to_owned(), output: output.to_owned() };
+	let old = [example("1 2\n", "3\n"), example("2 2\n", "4\n"), example("5 5\n", "10\n")];
+	let new = [example("1 2\n", "3\n"), example("2 2\n", "5\n")];
+	assert_eq!(compare_examples(&old, &new), ExampleChanges { added: vec![], changed: vec![2], removed: vec![3] });
+	assert_eq!(compare_examples(&new, &old), ExampleChanges { added: vec![3], changed: vec![2], removed: vec![] });
+	assert_eq!(compare_examples(&[], &new), ExampleChanges { added: vec![1, 2], changed: vec![], removed: vec![] });
+	assert_eq!(compare_examples(&old, &old), ExampleChanges::default());
+}
+
+#[test]
+fn test_describe() {
+	let changes = ExampleChanges { added: vec![3, 4], changed: vec![1], removed: vec![] };
+	assert_eq!(describe(&changes), ["examples 3, 4 added", "example 1 changed"]);
+	assert!(describe(&ExampleChanges::default()).is_empty());
+}
@@ -1,9 +1,12 @@
+mod input_format;
 mod library;
 mod logic;
 mod piece_parse;
+mod picker;
+mod test;
 
 use crate::{
-	dir, paste::logic::{Library, Piece}, util::time_now
+	dir, paste::logic::{Library, PasteContext, Piece}, util::{path::Path, time_now}
 };
 use async_trait::async_trait;
 use evscode::{error::ResultExt, E, R};
@@ -18,25 +21,101 @@ pub struct VscodePaste<'a> {
 async fn quick() -> R<()> {
 	let _status = crate::STATUS.push("Copy-pasting");
 	let library = library::CACHED_LIBRARY.update().await?;
-	let piece_id = select_piece(&library).await?;
+	let piece_id = picker::select_piece(&library).await?;
 	let context = query_context(&library).await?;
 	library.walk_graph(&piece_id, context).await?;
+	picker::record_use(&piece_id).await?;
 	Ok(())
 }
 
-async fn select_piece(library: &Library) -> R<String> {
-	let mut pieces = library.pieces.iter().collect::<Vec<_>>();
-	pieces.sort_by_key(|piece| &piece.1.name);
+#[evscode::command(title = "ICIE Quick Unpaste")]
+async fn unpaste() -> R<()> {
+	let _status = crate::STATUS.push("Removing pasted pieces");
+	let library = library::CACHED_LIBRARY.update().await?;
+	let mut context = query_context(&library).await?;
+	let mut pasted = library.pieces.iter().filter(|(id, _)| context.has(id)).collect::<Vec<_>>();
+	if pasted.is_empty() {
+		return Err(E::error("no library pieces are pasted in the solution"));
+	}
+	pasted.sort_by_key(|piece| &piece.1.name);
+	let piece_id = evscode::QuickPick::new()
+		.match_on_all()
+		.items(pasted.into_iter().map(|(id, piece)| picker::piece_item(id, piece)))
+		.show()
+		.await
+		.ok_or_else(E::cancel)?;
+	for piece_id in library.find_unused(&piece_id, |id| context.has(id)) {
+		remove_piece(&library, piece_id).await?;
+	}
+	Ok(())
+}
+
+async fn remove_piece(library: &Library, piece_id: &str) -> R<()> {
+	let solution = dir::solution().await?;
+	let text = evscode::query_document_text(&solution).await?;
+	let piece = &library.pieces[piece_id];
+	// Nested pieces disappear when their parent is removed first.
+	if !text.contains(&piece.guarantee) {
+		return Ok(());
+	}
+	let lines = library.locate(piece_id, &text).ok_or_else(|| {
+		E::error(format!("could not find the code of {}, it was probably modified after pasting", piece.name))
+	})?;
+	evscode::edit_replace(solution.as_str(), "", (lines.start, 0), (lines.end, 0)).await
+}
+
+#[evscode::command(title = "ICIE Quick Paste upgrade")]
+async fn upgrade() -> R<()> {
+	let _status = crate::STATUS.push("Upgrading pasted pieces");
+	let library = library::CACHED_LIBRARY.update().await?;
+	let context = query_context(&library).await?;
+	let mut outdated = library.find_outdated(&context.text).into_iter().map(|pasted| pasted.id).collect::<Vec<_>>();
+	outdated.sort();
+	outdated.dedup();
+	let unlocatable = library.find_unlocatable(&context.text);
+	if outdated.is_empty() {
+		if !unlocatable.is_empty() {
+			return Err(unlocatable_error(&library, &unlocatable));
+		}
+		evscode::Message::new::<()>("All pasted library pieces are up to date").show().await;
+		return Ok(());
+	}
+	let piece_ids = match select_outdated(&library, &outdated).await? {
+		Some(piece_id) => outdated_dependencies(&library, &piece_id, &outdated),
+		None => outdated,
+	};
+	for piece_id in &piece_ids {
+		upgrade_piece(&library, piece_id).await?;
+	}
+	// New versions can have new dependencies, which are pasted like when pasting the piece for the first time.
+	for piece_id in &piece_ids {
+		let context = query_context(&library).await?;
+		library.walk_graph(piece_id, context).await?;
+	}
+	if !unlocatable.is_empty() {
+		return Err(unlocatable_error(&library, &unlocatable));
+	}
+	Ok(())
+}
+
+fn unlocatable_error(library: &Library, unlocatable: &[String]) -> E {
+	let names = unlocatable.iter().map(|id| library.pieces[id].name.as_str()).collect::<Vec<_>>();
+	E::error(format!(
+		"could not upgrade {}, the \"// qpaste end\" markers were probably removed after pasting",
+		names.join(", ")
+	))
+}
+
+async fn select_outdated(library: &Library, outdated: &[String]) -> R<Option<String>> {
 	Ok(evscode::QuickPick::new()
 		.match_on_all()
-		.items(pieces.into_iter().map(|(id, piece)| {
-			let mut item = evscode::quick_pick::Item::new(id.clone(), piece.name.clone());
+		.item(evscode::quick_pick::Item::new(None, "All outdated pieces".to_owned()).description(outdated.join(", ")))
+		.items(outdated.iter().map(|id| {
+			let piece = &library.pieces[id];
+			let mut item = evscode::quick_pick::Item::new(Some(id.clone()), piece.name.clone());
 			if let Some(description) = &piece.description {
 				item = item.description(description.clone());
 			}
-			if let Some(detail) = &piece.detail {
-				item = item.detail(detail.clone());
-			}
 			item
 		}))
 		.show()
@@ -44,6 +123,33 @@ async fn select_piece(library: &Library) -> R<String> {
 		.ok_or_else(E::cancel)?)
 }
 
+/// Returns the piece with all of its' (in)direct dependencies that are outdated.
+fn outdated_dependencies(library: &Library, piece_id: &str, outdated: &[String]) -> Vec<String> {
+	let mut visited = vec![piece_id.to_owned()];
+	let mut i = 0;
+	while i < visited.len() {
+		for dep in &library.pieces[&visited[i]].dependencies {
+			if !visited.contains(dep) {
+				visited.push(dep.clone());
+			}
+		}
+		i += 1;
+	}
+	visited.retain(|id| outdated.contains(id));
+	visited
+}
+
+async fn upgrade_piece(library: &Library, piece_id: &str) -> R<()> {
+	let solution = dir::solution().await?;
+	// Every replacement moves the lines below it, so the markers have to be found again each time.
+	let text = evscode::query_document_text(&solution).await?;
+	if let Some(pasted) = library.find_outdated(&text).into_iter().find(|pasted| pasted.id == piece_id) {
+		let (start, end, code) = library.replace(&pasted, &text);
+		evscode::edit_replace(solution.as_str(), &code, start, end).await?;
+	}
+	Ok(())
+}
+
 #[evscode::command(title = "ICIE Quick Input struct", key = "alt+i")]
 async fn qistruct() -> R<()> {
 	let _status = crate::STATUS.push("Qistructing");
@@ -51,8 +157,8 @@ async fn qistruct() -> R<()> {
 		evscode::InputBox::new().prompt("Qistruct name").placeholder("Person").show().await.ok_or_else(E::cancel)?;
 	let members = input_members().await?;
 	let code = generate_cpp_qistruct(&name, &members);
-	let piece = make_hidden_struct_piece(&name, code);
-	paste_standalone(piece).await?;
+	let piece = make_hidden_piece(code, format!("struct {} {{", name));
+	paste_standalone(piece, &dir::solution().await?).await?;
 	Ok(())
 }
 
@@ -86,43 +192,50 @@ fn generate_cpp_qistruct(name: &str, members: &[(String, String)]) -> String {
 	code
 }
 
-fn make_hidden_struct_piece(name: &str, code: String) -> Piece {
+fn make_hidden_piece(code: String, guarantee: String) -> Piece {
 	Piece {
 		name: String::new(),
 		description: None,
 		detail: None,
+		tags: Vec::new(),
+		complexity: None,
 		code,
-		guarantee: format!("struct {} {{", name),
+		guarantee,
 		dependencies: Vec::new(),
 		parent: None,
+		version: None,
+		test: None,
 		modified: time_now(),
 	}
 }
 
-async fn paste_standalone(piece: Piece) -> R<()> {
+async fn paste_standalone(piece: Piece, path: &Path) -> R<()> {
 	let mut library = Library::new_empty();
 	library.pieces.insert(String::from("__fake_id"), piece);
-	let context = query_context(&library).await?;
+	let context = query_context_in(&library, path).await?;
 	library.walk_graph("__fake_id", context).await?;
 	Ok(())
 }
 
 async fn query_context(library: &Library) -> R<VscodePaste<'_>> {
-	let solution = dir::solution()?;
-	let text = evscode::query_document_text(&solution).await?;
-	let context = VscodePaste { solution: solution.into_string(), text, library };
+	query_context_in(library, &dir::solution().await?).await
+}
+
+async fn query_context_in<'a>(library: &'a Library, path: &Path) -> R<VscodePaste<'a>> {
+	let text = evscode::query_document_text(path.as_str()).await?;
+	let context = VscodePaste { solution: path.as_str().to_owned(), text, library };
 	Ok(context)
 }
 
 #[async_trait(?Send)]
-impl logic::PasteContext for VscodePaste<'_> {
+impl PasteContext for VscodePaste<'_> {
 	fn has(&mut self, piece_id: &str) -> bool {
 		let piece = &self.library.pieces[piece_id];
 		self.text.contains(&piece.guarantee)
 	}
 
 	async fn paste(&mut self, piece_id: &str) -> R<()> {
-		let (position, snippet) = self.library.place(piece_id, &self.text);
+		let (position, snippet) = self.library.place(piece_id, &self.text)?;
 		evscode::edit_paste(&self.solution, &snippet, position).await?;
 		self.text = evscode::query_document_text(&self.solution).await?;
 		Ok(())
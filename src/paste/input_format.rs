@@ -0,0 +1,549 @@
+use crate::{
+	dir, paste::{make_hidden_piece, paste_standalone}, template, util::{fs, path::Path}
+};
+use evscode::{E, R};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Generates the code reading the input described by a compact format, and the code printing a random input in the same
+/// format for the test generator. The format lists the variables line by line, separated with semicolons, like
+/// `n m; a[n]; edges[m]{u@n v@n w:ll}`. Variables are ints by default; add `:ll`, `:double`, `:char` or `:str` to
+/// change the type, and `@n` to read a 1-based index from 1 to n as 0-based.
+#[evscode::command(title = "ICIE Quick Input format", key = "alt+shift+i")]
+async fn input_format() -> R<()> {
+	let _status = crate::STATUS.push("Generating input code");
+	let format = evscode::InputBox::new()
+		.prompt("Input format")
+		.placeholder("n m; a[n]; edges[m]{u@n v@n w}")
+		.ignore_focus_out()
+		.show()
+		.await
+		.ok_or_else(E::cancel)?;
+	let format = parse(&format)?;
+	let test_generator = dir::test_generator().await?;
+	if !fs::exists(&test_generator).await? {
+		template::write(&test_generator, &template::load_test_generator().await?).await?;
+	}
+	paste_printer(&generate_printer(&format), &test_generator).await?;
+	let reader = make_hidden_piece(generate_reader(&format), "void read_input() {".to_owned());
+	paste_standalone(reader, &dir::solution().await?).await?;
+	Ok(())
+}
+
+/// Pastes the printer right before main, as it uses the randint helper that the default test generator defines below
+/// `using namespace std`, where library pieces would go.
+async fn paste_printer(printer: &str, test_generator: &Path) -> R<()> {
+	let source = evscode::query_document_text(test_generator.as_str()).await?;
+	if source.contains("void print_input() {") {
+		return Ok(());
+	}
+	let (position, snippet) = place_before_main(printer, &source);
+	evscode::edit_paste(test_generator.as_str(), &snippet, position).await
+}
+
+fn place_before_main(code: &str, source: &str) -> ((usize, usize), String) {
+	static MAIN: Lazy<Regex> = Lazy::new(|| Regex::new("(?m)^int\\s+main\\s*\\(").unwrap());
+	let index = MAIN.find(source).map_or(source.len(), |main| main.start());
+	let line = source[..index].matches('\n').count();
+	let prefix = if index == 0 || source[..index].ends_with("\n\n") { "" } else { "\n" };
+	((line, 0), format!("{}{}\n\n", prefix, code))
+}
+
+/// Variables read from each line of the input.
+#[derive(Debug, PartialEq)]
+struct Format {
+	lines: Vec<Vec<Variable>>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Variable {
+	name: String,
+	kind: Kind,
+}
+
+#[derive(Debug, PartialEq)]
+enum Kind {
+	Scalar(Value),
+	Array { length: String, element: Value },
+	Records { length: String, fields: Vec<(String, Value)> },
+}
+
+#[derive(Debug, PartialEq)]
+struct Value {
+	typ: Type,
+	/// Upper bound of a 1-based index, which is converted to 0-based after reading.
+	index_of: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Type {
+	Int,
+	Long,
+	Double,
+	Char,
+	String,
+}
+
+impl Type {
+	fn from_name(name: &str) -> R<Type> {
+		match name {
+			"int" => Ok(Type::Int),
+			"ll" => Ok(Type::Long),
+			"double" => Ok(Type::Double),
+			"char" => Ok(Type::Char),
+			"str" => Ok(Type::String),
+			_ => Err(E::error(format!("unknown type {}, should be int, ll, double, char or str", name))),
+		}
+	}
+
+	fn cpp(self) -> &'static str {
+		match self {
+			Type::Int => "int",
+			Type::Long => "long long",
+			Type::Double => "double",
+			Type::Char => "char",
+			Type::String => "string",
+		}
+	}
+
+	fn is_integer(self) -> bool {
+		matches!(self, Type::Int | Type::Long)
+	}
+}
+
+fn parse(format: &str) -> R<Format> {
+	let mut lines = Vec::new();
+	let mut parser = Parser { tokens: tokenize(format)?, position: 0 };
+	loop {
+		let mut line = Vec::new();
+		while !matches!(parser.peek(), None | Some(";")) {
+			line.push(parser.variable()?);
+		}
+		if !line.is_empty() {
+			lines.push(line);
+		}
+		if parser.next().is_none() {
+			break;
+		}
+	}
+	let format = Format { lines };
+	// The reading code uses sizes and index bounds right away, so they have to be integers read earlier.
+	let mut scalars = Vec::new();
+	let mut names = Vec::new();
+	for line in &format.lines {
+		for variable in line {
+			let check_bound = |bound: &str, scalars: &[(&str, Type)]| {
+				let is_integer = |(name, typ): &(&str, Type)| *name == bound && typ.is_integer();
+				if bound.parse::<u64>().is_ok() || scalars.iter().any(is_integer) {
+					Ok(())
+				} else {
+					Err(E::error(format!("{} used in {} is not an integer read before it", bound, variable.name)))
+				}
+			};
+			let check_value = |value: &Value, scalars: &[(&str, Type)]| match &value.index_of {
+				Some(_) if !value.typ.is_integer() => {
+					Err(E::error(format!("index in {} must be an integer", variable.name)))
+				},
+				Some(bound) => check_bound(bound, scalars),
+				None => Ok(()),
+			};
+			match &variable.kind {
+				Kind::Scalar(value) => check_value(value, &scalars)?,
+				Kind::Array { length, element } => {
+					check_bound(length, &scalars)?;
+					check_value(element, &scalars)?;
+				},
+				Kind::Records { length, fields } => {
+					check_bound(length, &scalars)?;
+					if line.len() > 1 {
+						return Err(E::error(format!("{} must be on a separate line, followed by ;", variable.name)));
+					}
+					for (j, (field, value)) in fields.iter().enumerate() {
+						check_value(value, &scalars)?;
+						if fields[..j].iter().any(|(other, _)| other == field) {
+							return Err(E::error(format!("field {} appears twice in {}", field, variable.name)));
+						}
+					}
+				},
+			}
+			if names.contains(&variable.name.as_str()) {
+				return Err(E::error(format!("variable {} appears twice", variable.name)));
+			}
+			names.push(variable.name.as_str());
+			if let Kind::Scalar(value) = &variable.kind {
+				scalars.push((variable.name.as_str(), value.typ));
+			}
+		}
+	}
+	Ok(format)
+}
+
+fn tokenize(format: &str) -> R<Vec<String>> {
+	let mut tokens = Vec::new();
+	let mut chars = format.chars().peekable();
+	while let Some(c) = chars.next() {
+		if c.is_ascii_alphanumeric() || c == '_' {
+			let mut token = c.to_string();
+			while let Some(c) = chars.peek().filter(|c| c.is_ascii_alphanumeric() || **c == '_') {
+				token.push(*c);
+				chars.next();
+			}
+			tokens.push(token);
+		} else if "[]{}:@;".contains(c) {
+			tokens.push(c.to_string());
+		} else if !c.is_whitespace() {
+			return Err(E::error(format!("unexpected {:?} in input format", c)));
+		}
+	}
+	Ok(tokens)
+}
+
+struct Parser {
+	tokens: Vec<String>,
+	position: usize,
+}
+
+impl Parser {
+	fn variable(&mut self) -> R<Variable> {
+		let name = self.identifier()?;
+		let kind = if self.eat("[") {
+			let length = self.word()?;
+			self.expect("]")?;
+			if self.eat("{") {
+				let mut fields = Vec::new();
+				while !self.eat("}") {
+					let field = self.identifier()?;
+					fields.push((field, self.value()?));
+				}
+				if fields.is_empty() {
+					return Err(E::error(format!("{} has no fields", name)));
+				}
+				Kind::Records { length, fields }
+			} else {
+				Kind::Array { length, element: self.value()? }
+			}
+		} else {
+			Kind::Scalar(self.value()?)
+		};
+		Ok(Variable { name, kind })
+	}
+
+	fn value(&mut self) -> R<Value> {
+		let typ = if self.eat(":") { Type::from_name(&self.word()?)? } else { Type::Int };
+		let index_of = if self.eat("@") { Some(self.word()?) } else { None };
+		Ok(Value { typ, index_of })
+	}
+
+	fn identifier(&mut self) -> R<String> {
+		let word = self.word()?;
+		if word.starts_with(|c: char| c.is_ascii_digit()) {
+			return Err(E::error(format!("expected a variable name, found {}", word)));
+		}
+		Ok(word)
+	}
+
+	fn word(&mut self) -> R<String> {
+		match self.next() {
+			Some(token) if token.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_') => Ok(token.to_owned()),
+			Some(token) => Err(E::error(format!("expected a name or a number, found {}", token))),
+			None => Err(E::error("unexpected end of input format")),
+		}
+	}
+
+	fn expect(&mut self, token: &str) -> R<()> {
+		if self.eat(token) { Ok(()) } else { Err(E::error(format!("expected {} in input format", token))) }
+	}
+
+	fn eat(&mut self, token: &str) -> bool {
+		if self.peek() == Some(token) {
+			self.position += 1;
+			true
+		} else {
+			false
+		}
+	}
+
+	fn peek(&self) -> Option<&str> {
+		self.tokens.get(self.position).map(String::as_str)
+	}
+
+	fn next(&mut self) -> Option<&str> {
+		self.position += 1;
+		self.tokens.get(self.position - 1).map(String::as_str)
+	}
+}
+
+/// Generates global variables and a read_input function, which reads them from cin.
+fn generate_reader(format: &Format) -> String {
+	let mut declarations = String::new();
+	let mut reading = String::new();
+	for line in &format.lines {
+		for group in group_scalars(line) {
+			match group {
+				Group::Scalars(typ, variables) => {
+					let names = variables.iter().map(|variable| variable.name.as_str()).collect::<Vec<_>>();
+					declarations += &format!("{} {};\n", typ.cpp(), names.join(", "));
+					reading += &format!("\tcin >> {};\n", names.join(" >> "));
+					let indices = variables.iter().filter(|variable| is_index(variable)).map(|variable| &variable.name);
+					let decrements = indices.map(|name| format!("--{}", name)).collect::<Vec<_>>();
+					if !decrements.is_empty() {
+						reading += &format!("\t{};\n", decrements.join(", "));
+					}
+				},
+				Group::Array(Variable { name, kind: Kind::Array { length, element } }) => {
+					declarations += &format!("vector<{}> {};\n", element.typ.cpp(), name);
+					reading += &format!("\t{}.resize({});\n", name, length);
+					let decrement = if element.index_of.is_some() { ", --x" } else { "" };
+					reading += &format!("\tfor (auto& x : {}) cin >> x{};\n", name, decrement);
+				},
+				Group::Array(Variable { name, kind: Kind::Records { length, fields } }) => {
+					let record = record_name(name);
+					declarations += &format!("struct {} {{\n", record);
+					for (field, value) in fields {
+						declarations += &format!("\t{} {};\n", value.typ.cpp(), field);
+					}
+					declarations += &format!("}};\nvector<{}> {};\n", record, name);
+					let fields_read = fields.iter().map(|(field, _)| format!("r.{}", field)).collect::<Vec<_>>();
+					let decrements = fields
+						.iter()
+						.filter(|(_, value)| value.index_of.is_some())
+						.map(|(field, _)| format!(", --r.{}", field))
+						.collect::<String>();
+					reading += &format!("\t{}.resize({});\n", name, length);
+					let fields_read = fields_read.join(" >> ");
+					reading += &format!("\tfor (auto& r : {}) cin >> {}{};\n", name, fields_read, decrements);
+				},
+				Group::Array(_) => unreachable!(),
+			}
+		}
+	}
+	format!("{}\nvoid read_input() {{\n{}}}", declarations, reading)
+}
+
+/// Generates a print_input function, which prints a random input to cout using the functions from the default test
+/// generator template.
+fn generate_printer(format: &Format) -> String {
+	let mut printing = String::new();
+	let bounds = format
+		.lines
+		.iter()
+		.flatten()
+		.flat_map(|variable| match &variable.kind {
+			Kind::Scalar(value) => value.index_of.iter().collect::<Vec<_>>(),
+			Kind::Array { length, element } => std::iter::once(length).chain(&element.index_of).collect(),
+			Kind::Records { length, fields } => {
+				std::iter::once(length).chain(fields.iter().flat_map(|(_, value)| &value.index_of)).collect()
+			},
+		})
+		.collect::<Vec<_>>();
+	let uses_strings = format.lines.iter().flatten().any(|variable| match &variable.kind {
+		Kind::Scalar(value) | Kind::Array { element: value, .. } => value.typ == Type::String,
+		Kind::Records { fields, .. } => fields.iter().any(|(_, value)| value.typ == Type::String),
+	});
+	for line in &format.lines {
+		let mut outputs = Vec::new();
+		for group in group_scalars(line) {
+			match group {
+				Group::Scalars(typ, variables) => {
+					let definitions = variables
+						.iter()
+						.map(|variable| {
+							let is_bound = bounds.contains(&&variable.name);
+							let value = match &variable.kind {
+								Kind::Scalar(value) => random_value(value, is_bound),
+								_ => unreachable!(),
+							};
+							format!("{} = {}", variable.name, value)
+						})
+						.collect::<Vec<_>>();
+					printing += &format!("\t{} {};\n", typ.cpp(), definitions.join(", "));
+					outputs.push(Output::Scalars(variables.iter().map(|variable| variable.name.as_str()).collect()));
+				},
+				Group::Array(variable) => outputs.push(Output::Array(variable)),
+			}
+		}
+		printing += &print_line(&outputs);
+	}
+	let helper = if uses_strings { RANDOM_STRING } else { "" };
+	format!("{}void print_input() {{\n{}}}", helper, printing)
+}
+
+const RANDOM_STRING: &str = r#"string random_string(int n) {
+	string s(n, 'a');
+	for (auto& c : s) c = randint<int>('a', 'z');
+	return s;
+}
+
+"#;
+
+enum Output<'a> {
+	Scalars(Vec<&'a str>),
+	Array(&'a Variable),
+}
+
+fn print_line(outputs: &[Output]) -> String {
+	match outputs {
+		[Output::Scalars(names)] => format!("\tcout << {} << '\\n';\n", names.join(" << ' ' << ")),
+		[Output::Array(Variable { kind: Kind::Array { length, element }, .. })] => {
+			let value = random_value(element, false);
+			format!("\tfor (int i = 0; i < {}; ++i) cout << {} << \" \\n\"[i + 1 == {}];\n", length, value, length)
+		},
+		[Output::Array(Variable { kind: Kind::Records { length, fields }, .. })] => {
+			let values = fields.iter().map(|(_, value)| random_value(value, false)).collect::<Vec<_>>();
+			format!("\tfor (int i = 0; i < {}; ++i) cout << {} << '\\n';\n", length, values.join(" << ' ' << "))
+		},
+		_ => {
+			let mut code = String::new();
+			for (i, output) in outputs.iter().enumerate() {
+				let separator = if i == 0 { "" } else { "' ' << " };
+				match output {
+					Output::Scalars(names) => {
+						code += &format!("\tcout << {}{};\n", separator, names.join(" << ' ' << "))
+					},
+					Output::Array(Variable { kind: Kind::Array { length, element }, .. }) => {
+						let separator = if i == 0 { "(i ? \" \" : \"\") << " } else { "' ' << " };
+						let value = random_value(element, false);
+						code += &format!("\tfor (int i = 0; i < {}; ++i) cout << {}{};\n", length, separator, value);
+					},
+					Output::Array(_) => unreachable!(),
+				}
+			}
+			code + "\tcout << '\\n';\n"
+		},
+	}
+}
+
+/// Returns an expression with a random value, small if it is used as a size or a bound.
+fn random_value(value: &Value, is_bound: bool) -> String {
+	match (&value.index_of, value.typ) {
+		(Some(bound), typ) => format!("randint<{}>(1, {})", typ.cpp(), bound),
+		(None, typ) if is_bound => format!("randint<{}>(1, 10)", typ.cpp()),
+		(None, Type::Int) => "randint<int>(1, 100)".to_owned(),
+		(None, Type::Long) => "randint<long long>(1, 1000000000)".to_owned(),
+		(None, Type::Double) => "uniform<double>(0., 1.)".to_owned(),
+		(None, Type::Char) => "char(randint<int>('a', 'z'))".to_owned(),
+		(None, Type::String) => "random_string(randint<int>(1, 10))".to_owned(),
+	}
+}
+
+enum Group<'a> {
+	Scalars(Type, Vec<&'a Variable>),
+	Array(&'a Variable),
+}
+
+/// Groups the adjacent scalars of the same type, so that they can be declared together.
+fn group_scalars(line: &[Variable]) -> Vec<Group<'_>> {
+	let mut groups = Vec::new();
+	for variable in line {
+		match (&variable.kind, groups.last_mut()) {
+			(Kind::Scalar(value), Some(Group::Scalars(typ, variables))) if *typ == value.typ => {
+				variables.push(variable)
+			},
+			(Kind::Scalar(value), _) => groups.push(Group::Scalars(value.typ, vec![variable])),
+			_ => groups.push(Group::Array(variable)),
+		}
+	}
+	groups
+}
+
+fn is_index(variable: &Variable) -> bool {
+	matches!(&variable.kind, Kind::Scalar(Value { index_of: Some(_), .. }))
+}
+
+/// Makes a struct name out of an array name, like Edge from edges or Query from queries.
+fn record_name(array: &str) -> String {
+	let singular = if let Some(stem) = array.strip_suffix("ies") {
+		format!("{}y", stem)
+	} else if array.len() > 1 && array.ends_with('s') && !array.ends_with("ss") {
+		array[..array.len() - 1].to_owned()
+	} else {
+		array.to_owned()
+	};
+	let mut chars = singular.chars();
+	let first = chars.next().unwrap().to_ascii_uppercase();
+	let name = format!("{}{}", first, chars.as_str());
+	if name == array { format!("{}Record", name) } else { name }
+}
+
+#[test]
+fn test_reader() {
+	let format = parse("n m; a[n]; edges[m]{u@n v@n w:ll}").unwrap();
+	assert_eq!(
+		generate_reader(&format),
+		r#"int n, m;
+vector<int> a;
+struct Edge {
+	int u;
+	int v;
+	long long w;
+};
+vector<Edge> edges;
+
+void read_input() {
+	cin >> n >> m;
+	a.resize(n);
+	for (auto& x : a) cin >> x;
+	edges.resize(m);
+	for (auto& r : edges) cin >> r.u >> r.v >> r.w, --r.u, --r.v;
+}"#
+	);
+}
+
+#[test]
+fn test_printer() {
+	let format = parse("n m; a[n]; edges[m]{u@n v@n w:ll}").unwrap();
+	assert_eq!(
+		generate_printer(&format),
+		r#"void print_input() {
+	int n = randint<int>(1, 10), m = randint<int>(1, 10);
+	cout << n << ' ' << m << '\n';
+	for (int i = 0; i < n; ++i) cout << randint<int>(1, 100) << " \n"[i + 1 == n];
+	for (int i = 0; i < m; ++i) cout << randint<int>(1, n) << ' ' << randint<int>(1, n) << ' ' << randint<long long>(1, 1000000000) << '\n';
+}"#
+	);
+	let format = parse("k p[k]@k; s:str").unwrap();
+	assert_eq!(
+		generate_printer(&format),
+		r#"string random_string(int n) {
+	string s(n, 'a');
+	for (auto& c : s) c = randint<int>('a', 'z');
+	return s;
+}
+
+void print_input() {
+	int k = randint<int>(1, 10);
+	cout << k;
+	for (int i = 0; i < k; ++i) cout << ' ' << randint<int>(1, k);
+	cout << '\n';
+	string s = random_string(randint<int>(1, 10));
+	cout << s << '\n';
+}"#
+	);
+}
+
+#[test]
+fn test_invalid() {
+	assert!(parse("a[n]; n").is_err());
+	assert!(parse("x:double a[x]").is_err());
+	assert!(parse("n; n").is_err());
+	assert!(parse("n e[n]{u v}").is_err());
+	assert!(parse("n; a[n").is_err());
+	assert!(parse("c:char@n").is_err());
+	assert!(parse("n; a[n]{}").is_err());
+	assert_eq!(record_name("queries"), "Query");
+	assert_eq!(record_name("a"), "A");
+	assert_eq!(record_name("Edge"), "EdgeRecord");
+}
+
+#[test]
+fn test_paste_printer() {
+	let source = template::default_test_generator_on(crate::util::OS::Linux);
+	let printer = generate_printer(&parse("n; a[n]").unwrap());
+	let ((line, column), snippet) = place_before_main(&printer, &source);
+	assert_eq!(column, 0);
+	let index = source.lines().take(line).map(|line| line.len() + 1).sum::<usize>();
+	let pasted = format!("{}{}{}", &source[..index], snippet, &source[index..]);
+	assert!(pasted.find("T randint(").unwrap() < pasted.find("void print_input() {").unwrap());
+	assert!(pasted.contains("😕\n\nvoid print_input() {"));
+	assert!(pasted.contains("}\n\nint main() {"));
+}
@@ -1,12 +1,15 @@
 use crate::{
 	paste::{
-		logic::{Library, Piece}, qpaste_doc_error
-	}, util::{fs, path::Path}
+		logic::{Library, Piece}, piece_parse::Format, qpaste_doc_error
+	}, util::{self, fs, path::Path}
 };
 use evscode::{E, R};
 use futures::lock::{Mutex, MutexGuard};
 use once_cell::sync::Lazy;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+/// Suffix of the test programs that are found automatically, like "fu.test.cpp" for the "fu.cpp" piece.
+pub const TEST_SUFFIX: &str = ".test";
 
 pub static CACHED_LIBRARY: Lazy<LibraryCache> = Lazy::new(LibraryCache::new);
 
@@ -16,34 +19,79 @@ pub static CACHED_LIBRARY: Lazy<LibraryCache> = Lazy::new(LibraryCache::new);
 #[evscode::config]
 static PATH: evscode::Config<Path> = "";
 
+/// Paths to other libraries used for quickpasting, like your team's notebook or KACTL. Pieces from these libraries are
+/// identified by the library directory name followed by their path, like "kactl/data-structures/UnionFind". Besides
+/// ICIE headers, the pieces can use KACTL headers or #pragma once with the dependencies included with #include. Paths
+/// starting with ~ are relative to the home directory.
+#[evscode::config]
+static ADDITIONAL_PATHS: evscode::Config<Vec<String>> = Vec::new();
+
+/// Whether to surround pasted pieces with "// qpaste" marker comments, which contain the hash of the piece and let the
+/// "ICIE Quick Paste upgrade" command replace it with a newer version. Pieces pasted without the markers are still
+/// found by their code, but can not be upgraded.
+#[evscode::config]
+static MARKERS: evscode::Config<bool> = true;
+
+/// Extensions of the files that can contain library pieces.
+const EXTENSIONS: [&str; 3] = ["cpp", "h", "hpp"];
+
 pub struct LibraryCache {
 	lock: Mutex<Library>,
 }
 
 impl LibraryCache {
 	pub fn new() -> LibraryCache {
-		LibraryCache {
-			lock: Mutex::new(Library { directory: Path::from_native(String::new()), pieces: HashMap::new() }),
-		}
+		LibraryCache { lock: Mutex::new(Library::new_empty()) }
 	}
 
 	#[allow(clippy::extra_unused_lifetimes)]
 	pub async fn update(&'static self) -> R<MutexGuard<'_, Library>> {
 		let mut lib = self.lock.lock().await;
-		let directory = self.get_directory().await?;
-		if directory != lib.directory {
+		let roots = self.get_roots().await?;
+		let directories = roots.iter().map(|(directory, _)| directory.clone()).collect::<Vec<_>>();
+		if directories != lib.directories {
 			lib.pieces = HashMap::new();
 		}
+		let mut loaded = Vec::new();
+		for (directory, namespace) in &roots {
+			for path in scan(directory).await? {
+				let relative = path.without_extension().fmt_relative(directory).replace('\\', "/");
+				let id = format!("{}{}", namespace, relative);
+				let extension = path.extension().unwrap_or_default();
+				// Test programs of the pieces are not pieces themselves.
+				if !EXTENSIONS.contains(&extension.as_str()) || id.ends_with(TEST_SUFFIX) {
+					continue;
+				}
+				// Only the top level of the main library is expected to contain nothing but pieces.
+				let strict = namespace.is_empty() && !relative.contains('/');
+				let piece = self.maybe_load_piece(&path, &id, strict, &mut lib.pieces).await;
+				loaded.push((id, path, piece));
+			}
+		}
+		// Errors are only reported after the test programs named in the Test: headers are known, as these are not
+		// pieces and can fail to parse as one.
+		let tests = loaded
+			.iter()
+			.filter_map(|(_, path, piece)| Some(path.parent().join(piece.as_ref().ok()?.as_ref()?.test.as_ref()?)))
+			.collect::<HashSet<_>>();
 		let mut new_pieces = HashMap::new();
-		for path in fs::read_dir(&directory).await? {
-			let id = path.without_extension().fmt_relative(&directory);
-			if path.extension() == Some("cpp".to_owned()) {
-				let piece = self.maybe_load_piece(path, &id, &mut lib.pieces).await?;
+		let mut paths = HashMap::new();
+		for (id, path, piece) in loaded {
+			if tests.contains(&path) {
+				continue;
+			}
+			if let Some(piece) = piece? {
+				if let Some(other) = paths.insert(id.clone(), path.clone()) {
+					return Err(E::error(format!("piece {:?} is defined in both {} and {}", id, other, path))
+						.context("malformed library"));
+				}
 				new_pieces.insert(id, piece);
 			}
 		}
-		lib.directory = directory;
+		lib.directories = directories;
 		lib.pieces = new_pieces;
+		lib.paths = paths;
+		lib.markers = MARKERS.get();
 		if lib.pieces.is_empty() {
 			return Err(qpaste_doc_error(E::error("library is empty")));
 		}
@@ -51,26 +99,69 @@ impl LibraryCache {
 		Ok(lib)
 	}
 
-	async fn maybe_load_piece(&self, path: Path, id: &str, cached_pieces: &mut HashMap<String, Piece>) -> R<Piece> {
-		let modified = fs::metadata(&path).await?.modified;
+	/// Loads a piece, unless it has not changed since it was last loaded. Files in none of the supported formats are
+	/// skipped, because libraries often contain other files like templates, unless the library is `strict` about
+	/// .cpp files, in which case they are reported as malformed ICIE pieces.
+	async fn maybe_load_piece(
+		&self,
+		path: &Path,
+		id: &str,
+		strict: bool,
+		cached_pieces: &mut HashMap<String, Piece>,
+	) -> R<Option<Piece>> {
+		let modified = fs::metadata(path).await?.modified;
 		match cached_pieces.remove(id) {
-			Some(cached) if cached.modified == modified => Ok(cached),
+			Some(cached) if cached.modified == modified => Ok(Some(cached)),
 			_ => {
-				let code = fs::read_to_string(&path).await?;
+				let code = fs::read_to_string(path).await?;
+				if !(strict && path.extension().as_deref() == Some("cpp")) && Format::detect(&code).is_none() {
+					return Ok(None);
+				}
 				let piece = Piece::parse(&code, id.to_owned(), modified).map_err(qpaste_doc_error)?;
-				Ok(piece)
+				Ok(Some(piece))
 			},
 		}
 	}
 
-	async fn get_directory(&self) -> R<Path> {
-		let dir = PATH.get();
-		if dir.as_str() == "" {
+	/// Returns the library directories with the prefixes of their piece ids.
+	async fn get_roots(&self) -> R<Vec<(Path, String)>> {
+		let main = PATH.get();
+		let additional = ADDITIONAL_PATHS.get().iter().map(|path| util::expand_path(path)).collect::<Vec<_>>();
+		if main.as_str() == "" && additional.is_empty() {
 			return Err(qpaste_doc_error(E::error("library not found")));
 		}
-		if !fs::exists(&dir).await? {
-			return Err(qpaste_doc_error(E::error(format!("library directory {} does not exist", dir))));
+		let mut roots = Vec::new();
+		if main.as_str() != "" {
+			roots.push((main, String::new()));
+		}
+		roots.extend(additional.into_iter().map(|path| {
+			let namespace = format!("{}/", path.file_name());
+			(path, namespace)
+		}));
+		for (directory, _) in &roots {
+			if !fs::exists(directory).await? {
+				return Err(qpaste_doc_error(E::error(format!("library directory {} does not exist", directory))));
+			}
+		}
+		Ok(roots)
+	}
+}
+
+/// Lists the files in a directory and all its' subdirectories, skipping hidden ones like .git.
+async fn scan(directory: &Path) -> R<Vec<Path>> {
+	let mut files = Vec::new();
+	let mut queue = vec![directory.clone()];
+	while let Some(directory) = queue.pop() {
+		for path in fs::read_dir(&directory).await? {
+			if path.file_name().starts_with('.') {
+				continue;
+			}
+			if fs::metadata(&path).await?.is_directory {
+				queue.push(path);
+			} else {
+				files.push(path);
+			}
 		}
-		Ok(dir)
 	}
+	Ok(files)
 }
@@ -1,12 +1,18 @@
 use crate::util::path::Path;
 use async_trait::async_trait;
 use evscode::{E, R};
-use std::{collections::HashMap, time::SystemTime};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::{collections::HashMap, ops::Range, time::SystemTime};
 
 #[derive(Debug)]
 pub struct Library {
-	pub directory: Path,
+	pub directories: Vec<Path>,
 	pub pieces: HashMap<String, Piece>,
+	/// Paths to the files of the pieces, keyed by their ids.
+	pub paths: HashMap<String, Path>,
+	/// Whether pieces with a version are pasted with the marker comments around them.
+	pub markers: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -14,16 +20,33 @@ pub struct Piece {
 	pub name: String,
 	pub description: Option<String>,
 	pub detail: Option<String>,
+	pub tags: Vec<String>,
+	pub complexity: Option<String>,
 	pub code: String,
 	pub guarantee: String,
 	pub dependencies: Vec<String>,
 	pub parent: Option<String>,
+	/// Hash of the code, written in the marker comments around the pasted piece. Pieces without a version are pasted
+	/// without the markers.
+	pub version: Option<String>,
+	/// Path to the test program of the piece, relative to the directory of the piece.
+	pub test: Option<String>,
 	pub modified: SystemTime,
 }
 
+/// A piece found in the source code thanks to the marker comments around it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Pasted {
+	pub id: String,
+	pub version: String,
+	/// Lines of the piece, including the markers and the nested pieces.
+	pub lines: Range<usize>,
+	pub indent: String,
+}
+
 impl Library {
 	pub fn new_empty() -> Library {
-		Library { directory: Path::from_native(String::new()), pieces: HashMap::new() }
+		Library { directories: Vec::new(), pieces: HashMap::new(), paths: HashMap::new(), markers: true }
 	}
 
 	pub fn verify(&self) -> R<()> {
@@ -33,11 +56,6 @@ impl Library {
 					return Err(E::error(format!("parent of {:?} is {:?}, which does not exist", id, Some(parent)))
 						.context("malformed library"));
 				}
-				if self.pieces[parent].parent.is_some() {
-					return Err(
-						E::error("doubly nested library pieces are not supported yet").context("malformed library")
-					);
-				}
 			}
 			for dep in &piece.dependencies {
 				if !self.pieces.contains_key(dep) {
@@ -45,6 +63,17 @@ impl Library {
 				}
 			}
 		}
+		// Walking the ancestors of a piece with a parenting cycle would never end, so this has to be checked before
+		// building the ordering graph.
+		for id in self.pieces.keys() {
+			let mut ancestor = self.pieces[id].parent.as_ref();
+			for _ in 0..self.pieces.len() {
+				ancestor = ancestor.and_then(|ancestor| self.pieces[ancestor].parent.as_ref());
+			}
+			if ancestor.is_some() {
+				return Err(E::error("parenting cycle detected").context("malformed library"));
+			}
+		}
 		let (dg, t1, _) = self.build_dependency_graph();
 		let og = self.build_ordering_graph(&dg, &t1);
 		if og.toposort().is_none() {
@@ -69,6 +98,51 @@ impl Library {
 		Ok(())
 	}
 
+	/// Returns the piece with all of its' (in)direct dependencies and nested pieces that would not be needed by any
+	/// other present piece after removing it, starting with the piece itself.
+	pub fn find_unused(&self, piece_id: &str, mut has: impl FnMut(&str) -> bool) -> Vec<&str> {
+		let (dg, t1, t2) = self.build_dependency_graph();
+		let rg = dg.transpose();
+		let present = t2.iter().map(|id| has(id)).collect::<Vec<_>>();
+		let mut removed = vec![t1[piece_id]];
+		loop {
+			let next = (0..dg.len()).find(|v| {
+				let nested = self.ancestors(t2[*v]).iter().any(|ancestor| removed.contains(&t1[ancestor]));
+				let needed_by_removed = rg.edges[*v].iter().any(|u| removed.contains(u));
+				let needed_by_present = rg.edges[*v].iter().any(|u| present[*u] && !removed.contains(u));
+				present[*v] && !removed.contains(v) && (nested || (needed_by_removed && !needed_by_present))
+			});
+			match next {
+				Some(v) => removed.push(v),
+				None => break,
+			}
+		}
+		removed.into_iter().map(|v| t2[v]).collect()
+	}
+
+	/// Finds the lines of a piece in the source code, either by the markers around it or by searching for its' code
+	/// if it was pasted without them. A blank line after top-level pieces is included, to keep a single blank line
+	/// between the surrounding code.
+	pub fn locate(&self, piece_id: &str, source: &str) -> Option<Range<usize>> {
+		let lines = source.lines().collect::<Vec<_>>();
+		let mut range = match find_pasted(source).into_iter().find(|pasted| pasted.id == piece_id) {
+			Some(pasted) => pasted.lines,
+			None => {
+				let indent = "\t".repeat(self.ancestors(piece_id).len());
+				let code = self.pieces[piece_id].code.trim_end().lines().map(|line| format!("{}{}", indent, line));
+				let code = code.collect::<Vec<_>>();
+				let start = lines.windows(code.len()).position(|window| window == code.as_slice())?;
+				start..start + code.len()
+			},
+		};
+		let toplevel = self.pieces[piece_id].parent.is_none();
+		let blank_before = range.start == 0 || lines[range.start - 1].is_empty();
+		if toplevel && blank_before && lines.get(range.end) == Some(&"") {
+			range.end += 1;
+		}
+		Some(range)
+	}
+
 	/// Builds a graph where every piece has outgoings edges to all of its' dependencies.
 	fn build_dependency_graph(&self) -> (Graph, HashMap<&str, usize>, Vec<&str>) {
 		let t1: HashMap<&str, usize> = self.pieces.iter().enumerate().map(|(v, (id, _))| (id.as_str(), v)).collect();
@@ -89,24 +163,46 @@ impl Library {
 	/// Builds a graph where every piece has outgoing edges to all of its' (in)direct dependants.
 	fn build_ordering_graph(&self, dg: &Graph, t1: &HashMap<&str, usize>) -> Graph {
 		let mut og = dg.transpose();
-		for data in self.pieces.values() {
+		for (id, data) in &self.pieces {
+			let v = t1[id.as_str()];
 			if let Some(parent) = &data.parent {
-				let p = t1[parent.as_str()];
-				for dep in &data.dependencies {
-					let u = t1[dep.as_str()];
-					if u != p && data.parent != self.pieces[dep].parent {
-						og.add_edge_1(u, p);
-					}
+				og.add_edge_1(t1[parent.as_str()], v);
+			}
+			let ancestors = self.ancestors(id);
+			for dep in &data.dependencies {
+				if ancestors.contains(&dep.as_str()) {
+					continue;
+				}
+				// The dependency has to be placed before the piece in the innermost struct that contains both of them,
+				// so the outermost pieces below that struct have to be ordered instead.
+				let dep_ancestors = self.ancestors(dep);
+				let common = ancestors.iter().find(|ancestor| dep_ancestors.contains(ancestor)).copied();
+				let p = t1[outermost_below(id, &ancestors, common)];
+				let u = t1[outermost_below(dep, &dep_ancestors, common)];
+				if (u, p) != (t1[dep.as_str()], v) {
+					og.add_edge_1(u, p);
 				}
 			}
 		}
 		og
 	}
 
-	pub fn place(&self, piece_id: &str, source: &str) -> ((usize, usize), String) {
-		let index = self.place_index(piece_id, source);
+	/// Returns the parent of a piece, the parent of the parent, and so on, up to a top-level piece.
+	fn ancestors<'a>(&'a self, piece_id: &'a str) -> Vec<&'a str> {
+		let mut ancestors = Vec::new();
+		let mut piece = &self.pieces[piece_id];
+		while let Some(parent) = &piece.parent {
+			ancestors.push(parent.as_str());
+			piece = &self.pieces[parent];
+		}
+		ancestors
+	}
+
+	pub fn place(&self, piece_id: &str, source: &str) -> R<((usize, usize), String)> {
+		let index = self.place_index(piece_id, source)?;
 		let position = index_to_position(index, source);
-		let (pref, suf) = if self.pieces[piece_id].parent.is_some() {
+		let depth = self.ancestors(piece_id).len();
+		let (pref, suf) = if depth > 0 {
 			("", "\n")
 		} else {
 			(
@@ -114,31 +210,100 @@ impl Library {
 				if source[index..].starts_with('\n') { "\n" } else { "\n\n" },
 			)
 		};
-		let code = if self.pieces[piece_id].parent.is_some() {
+		let code = self.marked_code(piece_id);
+		let code = if depth > 0 {
+			let indent = "\t".repeat(depth);
 			let mut buf = String::new();
-			for line in self.pieces[piece_id].code.lines() {
-				buf += "\t";
+			for line in code.lines() {
+				buf += &indent;
 				buf += line;
 				buf += "\n";
 			}
 			buf.trim_end().to_owned()
 		} else {
-			self.pieces[piece_id].code.trim_end().to_owned()
+			code
 		};
-		(position, format!("{}{}{}", pref, code, suf))
+		Ok((position, format!("{}{}{}", pref, code, suf)))
+	}
+
+	/// Returns the pieces pasted in the source code that have changed in the library since then.
+	pub fn find_outdated(&self, source: &str) -> Vec<Pasted> {
+		find_pasted(source)
+			.into_iter()
+			.filter(|pasted| {
+				let version = self.pieces.get(&pasted.id).and_then(|piece| piece.version.as_ref());
+				matches!(version, Some(version) if *version != pasted.version)
+			})
+			.collect()
+	}
+
+	/// Returns the ids of the outdated pieces whose begin marker has no matching end marker, usually because it was
+	/// removed by hand. Their code can not be located, so they can not be upgraded.
+	pub fn find_unlocatable(&self, source: &str) -> Vec<String> {
+		scan_markers(source)
+			.1
+			.into_iter()
+			.filter(|(id, version)| {
+				let current = self.pieces.get(id).and_then(|piece| piece.version.as_ref());
+				matches!(current, Some(current) if current != version)
+			})
+			.map(|(id, _)| id)
+			.collect()
+	}
+
+	/// Replaces a pasted piece with its' current version from the library, returning the replaced range and the new
+	/// text. The pieces nested in it are moved to the end of the new version, like they would be when pasting.
+	pub fn replace(&self, pasted: &Pasted, source: &str) -> ((usize, usize), (usize, usize), String) {
+		let lines = source.lines().collect::<Vec<_>>();
+		// Pieces are found sorted by the first line, so the ones nested directly are those that do not start before
+		// the end of the previous one.
+		let mut child_lines = Vec::new();
+		let mut end = pasted.lines.start + 1;
+		for child in find_pasted(source) {
+			if child.lines.start >= end && child.lines.end < pasted.lines.end {
+				child_lines.extend_from_slice(&lines[child.lines.clone()]);
+				end = child.lines.end;
+			}
+		}
+		let code = self.marked_code(&pasted.id);
+		let mut code = code.lines().map(|line| format!("{}{}", pasted.indent, line)).collect::<Vec<_>>();
+		let closing_brace = format!("{}}}", pasted.indent);
+		let end_marker = code.len() - 1;
+		let closing = code[..end_marker].iter().rposition(|line| line.starts_with(&closing_brace));
+		let closing = closing.unwrap_or(end_marker);
+		code.splice(closing..closing, child_lines.into_iter().map(String::from));
+		let mut text = code.join("\n");
+		text += "\n";
+		((pasted.lines.start, 0), (pasted.lines.end, 0), text)
 	}
 
-	fn place_index(&self, piece_id: &str, source: &str) -> usize {
+	fn marked_code(&self, piece_id: &str) -> String {
+		let piece = &self.pieces[piece_id];
+		let code = piece.code.trim_end();
+		match &piece.version {
+			Some(version) if self.markers => {
+				format!("{} {} {}\n{}\n{} {}", MARKER_BEGIN, piece_id, version, code, MARKER_END, piece_id)
+			},
+			_ => code.to_owned(),
+		}
+	}
+
+	fn place_index(&self, piece_id: &str, source: &str) -> R<usize> {
 		let piece = &self.pieces[piece_id];
 		if let Some(parent) = &piece.parent {
 			// the piece will be placed at the end of the struct definition
 			// while c++ allows declarations in structs to be out of order, it doesn't do so always
 			// specifically, templates and nested structs seem to break it for some reason
+			// the closing brace of the parent is the one matching the first opening brace in or after its' guarantee
 			let parent = &self.pieces[parent];
-			let mut pos = source.find(&parent.guarantee).unwrap();
-			pos += source[pos..].find("\n}").unwrap();
-			pos += 1;
-			pos
+			let start = source.find(&parent.guarantee).ok_or_else(|| {
+				E::error(format!("could not find {} in the source code, needed to paste {}", parent.name, piece.name))
+			})?;
+			let closing = source[start..].find('{').and_then(|open| matching_brace(source, start + open));
+			let closing = closing.ok_or_else(|| {
+				E::error(format!("could not find the closing brace of {}, needed to paste {}", parent.name, piece.name))
+			})?;
+			Ok(source[..closing].rfind('\n').map_or(0, |i| i + 1))
 		} else {
 			let (dg, t1, t2) = self.build_dependency_graph();
 			let og = self.build_ordering_graph(&dg, &t1);
@@ -153,26 +318,120 @@ impl Library {
 			if pos > source.len() && !source.ends_with('\n') {
 				pos -= 1;
 			}
-			pos
+			Ok(pos)
 		}
 	}
 }
 
+/// Returns the outermost of the piece and its' ancestors that is nested directly in the given common ancestor, or is a
+/// top-level piece if there is none.
+fn outermost_below<'a>(piece_id: &'a str, ancestors: &[&'a str], common: Option<&str>) -> &'a str {
+	let below = match common {
+		Some(common) => &ancestors[..ancestors.iter().position(|ancestor| *ancestor == common).unwrap()],
+		None => ancestors,
+	};
+	below.last().copied().unwrap_or(piece_id)
+}
+
+const MARKER_BEGIN: &str = "// qpaste";
+const MARKER_END: &str = "// qpaste end";
+
+/// Finds the pieces pasted in the source code, by matching the begin and end marker comments.
+pub fn find_pasted(source: &str) -> Vec<Pasted> {
+	scan_markers(source).0
+}
+
+/// Matches the begin and end marker comments, returning the pasted pieces and the ids and versions of the begin markers
+/// without a matching end marker.
+fn scan_markers(source: &str) -> (Vec<Pasted>, Vec<(String, String)>) {
+	static BEGIN: Lazy<Regex> = Lazy::new(|| Regex::new("^(\\s*)// qpaste (.+) ([0-9a-f]{16})\\s*$").unwrap());
+	static END: Lazy<Regex> = Lazy::new(|| Regex::new("^\\s*// qpaste end (.+?)\\s*$").unwrap());
+	let mut open: Vec<(String, String, usize, String)> = Vec::new();
+	let mut pasted = Vec::new();
+	let mut unpaired = Vec::new();
+	for (i, line) in source.lines().enumerate() {
+		if let Some(cap) = END.captures(line) {
+			// Markers without a pair were probably edited by hand, so they are skipped.
+			if let Some(j) = open.iter().rposition(|(id, _, _, _)| *id == cap[1]) {
+				let mut closed = open.drain(j..);
+				let (id, version, start, indent) = closed.next().unwrap();
+				pasted.push(Pasted { id, version, lines: start..i + 1, indent });
+				unpaired.extend(closed.map(|(id, version, _, _)| (id, version)));
+			}
+		} else if let Some(cap) = BEGIN.captures(line) {
+			open.push((cap[2].to_owned(), cap[3].to_owned(), i, cap[1].to_owned()));
+		}
+	}
+	unpaired.extend(open.into_iter().map(|(id, version, _, _)| (id, version)));
+	pasted.sort_by_key(|pasted| pasted.lines.start);
+	(pasted, unpaired)
+}
+
+/// Returns the index of the brace closing the one at the given index, skipping the ones in comments and literals.
+fn matching_brace(source: &str, open: usize) -> Option<usize> {
+	let mut depth = 0;
+	let mut chars = source[open..].char_indices().map(|(i, c)| (open + i, c)).peekable();
+	while let Some((i, c)) = chars.next() {
+		match c {
+			'{' => depth += 1,
+			'}' => {
+				depth -= 1;
+				if depth == 0 {
+					return Some(i);
+				}
+			},
+			'"' | '\'' => {
+				while let Some((_, d)) = chars.next() {
+					if d == '\\' {
+						chars.next();
+					} else if d == c || d == '\n' {
+						break;
+					}
+				}
+			},
+			'/' if chars.peek().map(|(_, d)| *d) == Some('/') => {
+				while chars.peek().map_or(false, |(_, d)| *d != '\n') {
+					chars.next();
+				}
+			},
+			'/' if chars.peek().map(|(_, d)| *d) == Some('*') => {
+				chars.next();
+				let mut previous = ' ';
+				for (_, d) in &mut chars {
+					if previous == '*' && d == '/' {
+						break;
+					}
+					previous = d;
+				}
+			},
+			_ => (),
+		}
+	}
+	None
+}
+
 fn index_to_position(index: usize, source: &str) -> (usize, usize) {
 	let line = source[..index].chars().filter(|c| *c == '\n').count();
 	(line, 0)
 }
 
 fn skip_to_toplevel(mut pos: usize, source: &str) -> usize {
+	let end_marker = format!("\n{}", MARKER_END);
 	loop {
 		pos += source[pos..].find('\n').unwrap_or_else(|| source.len());
 		if source[pos..].starts_with("\n}") {
 			pos += 1;
 			pos += source[pos..].find('\n').unwrap_or_else(|| source[pos..].len());
+			// the end marker after the closing brace still belongs to the pasted piece
+			if source[pos..].starts_with(&end_marker) {
+				pos += 1;
+				pos += source[pos..].find('\n').unwrap_or_else(|| source[pos..].len());
+			}
 			break pos + 1;
 		} else if source[pos..].starts_with("\n\n")
 			|| source[pos..].starts_with("\n ")
 			|| source[pos..].starts_with("\n\t")
+			|| source[pos..].starts_with(&end_marker)
 		{
 			pos += 1;
 		} else {
@@ -301,6 +560,14 @@ mod tests {
 		lib.pieces.insert("b".to_owned(), mock_piece("b", &["a"], Some("a")));
 		assert!(lib.verify().is_ok());
 		lib.pieces.insert("c".to_owned(), mock_piece("c", &["b", "a"], Some("b")));
+		assert!(lib.verify().is_ok());
+	}
+
+	#[test]
+	fn parenting_cycle() {
+		let mut lib = Library::new_empty();
+		lib.pieces.insert("a".to_owned(), mock_piece("a", &[], Some("b")));
+		lib.pieces.insert("b".to_owned(), mock_piece("b", &[], Some("a")));
 		assert!(lib.verify().is_err());
 	}
 
@@ -386,10 +653,14 @@ int main() {
 			name: "Graph".to_owned(),
 			description: None,
 			detail: None,
+			tags: Vec::new(),
+			complexity: None,
 			code: "struct Graph {\n};".to_owned(),
 			guarantee: "struct Graph {".to_owned(),
 			dependencies: Vec::new(),
 			parent: None,
+			version: None,
+			test: None,
 			modified: SystemTime::now(),
 		});
 		lib1.pieces.insert("lca".to_owned(), mock_piece("lca", &["graph"], Some("graph")));
@@ -409,6 +680,228 @@ struct Graph {
 		assert_eq!(replace(&lib2, "dominator", "").await, desired);
 	}
 
+	#[tokio::test]
+	async fn placing_nested() {
+		let mut lib = Library::new_empty();
+		lib.pieces.insert("graph".to_owned(), mock_struct("graph", "struct Graph {", &[], None));
+		let weighted = mock_struct("weighted", "struct Weighted {", &["graph"], Some("graph"));
+		lib.pieces.insert("weighted".to_owned(), weighted);
+		lib.pieces.insert("edge".to_owned(), mock_struct("edge", "struct Edge {", &["weighted"], Some("weighted")));
+		lib.pieces.insert("bfs".to_owned(), mock_piece("bfs", &["graph"], Some("graph")));
+		lib.pieces.insert("dijkstra".to_owned(), mock_piece("dijkstra", &["edge", "bfs"], Some("weighted")));
+		lib.pieces.insert("reverse".to_owned(), mock_piece("reverse", &["edge"], Some("edge")));
+		lib.verify().unwrap();
+		assert_eq!(
+			replace(&lib, "dijkstra", "").await,
+			r#"
+struct Graph {
+	{{bfs}}
+	struct Weighted {
+		struct Edge {
+		};
+		{{dijkstra}}
+	};
+};
+
+"#
+		);
+		assert_eq!(
+			replace(&lib, "reverse", "").await,
+			r#"
+struct Graph {
+	struct Weighted {
+		struct Edge {
+			{{reverse}}
+		};
+	};
+};
+
+"#
+		);
+	}
+
+	#[tokio::test]
+	async fn placing_markers() {
+		let mut lib = Library::new_empty();
+		let graph = mock_struct("graph", "struct Graph {", &[], None);
+		lib.pieces.insert("graph".to_owned(), Piece { version: Some("0123456789abcdef".to_owned()), ..graph });
+		let bfs = mock_piece("bfs", &["graph"], Some("graph"));
+		lib.pieces.insert("bfs".to_owned(), Piece { version: Some("00000000000000bf".to_owned()), ..bfs });
+		let tree = mock_piece("tree", &["graph"], None);
+		lib.pieces.insert("tree".to_owned(), Piece { version: Some("000000000000001e".to_owned()), ..tree });
+		lib.verify().unwrap();
+		let mut source = "using namespace std;\n\nint main() {\n}\n".to_owned();
+		lib.walk_graph("bfs", PlaceMockContext { lib: &lib, buf: &mut source }).await.unwrap();
+		lib.walk_graph("tree", PlaceMockContext { lib: &lib, buf: &mut source }).await.unwrap();
+		assert_eq!(
+			source,
+			r#"using namespace std;
+
+// qpaste graph 0123456789abcdef
+struct Graph {
+	// qpaste bfs 00000000000000bf
+	{{bfs}}
+	// qpaste end bfs
+};
+// qpaste end graph
+
+// qpaste tree 000000000000001e
+{{tree}}
+// qpaste end tree
+
+int main() {
+}
+"#
+		);
+		assert_eq!(find_pasted(&source), vec![
+			mock_pasted("graph", "0123456789abcdef", 2..9, ""),
+			mock_pasted("bfs", "00000000000000bf", 4..7, "\t"),
+			mock_pasted("tree", "000000000000001e", 10..13, ""),
+		]);
+	}
+
+	#[test]
+	fn replacing_outdated() {
+		let mut lib = Library::new_empty();
+		let graph = mock_struct("graph", "struct Graph {", &[], None);
+		let graph = Piece { code: "struct Graph {\n\tint n;\n};".to_owned(), ..graph };
+		lib.pieces.insert("graph".to_owned(), Piece { version: Some("0000000000000002".to_owned()), ..graph });
+		let bfs = mock_piece("bfs", &["graph"], Some("graph"));
+		lib.pieces.insert("bfs".to_owned(), Piece { version: Some("00000000000000bf".to_owned()), ..bfs });
+		let source = r#"// qpaste graph 0000000000000001
+struct Graph {
+	// qpaste bfs 00000000000000bf
+	{{bfs}}
+	// qpaste end bfs
+};
+// qpaste end graph
+
+int main() {
+}
+"#;
+		let outdated = lib.find_outdated(source);
+		assert_eq!(outdated.len(), 1);
+		assert_eq!(outdated[0].id, "graph");
+		let (start, end, code) = lib.replace(&outdated[0], source);
+		assert_eq!(start, (0, 0));
+		assert_eq!(end, (7, 0));
+		assert_eq!(
+			code,
+			r#"// qpaste graph 0000000000000002
+struct Graph {
+	int n;
+	// qpaste bfs 00000000000000bf
+	{{bfs}}
+	// qpaste end bfs
+};
+// qpaste end graph
+"#
+		);
+	}
+
+	#[test]
+	fn unlocatable_outdated() {
+		let mut lib = Library::new_empty();
+		let graph = mock_struct("graph", "struct Graph {", &[], None);
+		lib.pieces.insert("graph".to_owned(), Piece { version: Some("0000000000000002".to_owned()), ..graph });
+		let bfs = mock_piece("bfs", &["graph"], Some("graph"));
+		lib.pieces.insert("bfs".to_owned(), Piece { version: Some("00000000000000bf".to_owned()), ..bfs });
+		let tree = mock_piece("tree", &[], None);
+		lib.pieces.insert("tree".to_owned(), Piece { version: Some("000000000000001e".to_owned()), ..tree });
+		let source = r#"// qpaste graph 0000000000000001
+struct Graph {
+	// qpaste bfs 00000000000000be
+	{{bfs}}
+};
+// qpaste end graph
+
+// qpaste tree 000000000000001e
+{{tree}}
+"#;
+		assert_eq!(lib.find_outdated(source).into_iter().map(|pasted| pasted.id).collect::<Vec<_>>(), vec!["graph"]);
+		assert_eq!(lib.find_unlocatable(source), vec!["bfs"]);
+		lib.markers = false;
+		assert_eq!(lib.marked_code("tree"), "{{tree}}");
+	}
+
+	#[test]
+	fn unused_dependencies() {
+		let mut lib = Library::new_empty();
+		lib.pieces.insert("graph".to_owned(), mock_piece("graph", &[], None));
+		lib.pieces.insert("dummyf".to_owned(), mock_piece("dummyf", &[], None));
+		lib.pieces.insert("dfs-impl".to_owned(), mock_piece("dfs-impl", &["graph", "dummyf"], Some("graph")));
+		lib.pieces.insert("dfs".to_owned(), mock_piece("dfs", &["graph", "dfs-impl", "dummyf"], Some("graph")));
+		lib.pieces.insert("qpow".to_owned(), mock_piece("qpow", &[], None));
+		lib.pieces.insert("mint".to_owned(), mock_piece("mint", &["qpow"], None));
+		lib.verify().unwrap();
+		let all = |_: &str| true;
+		assert_eq!(lib.find_unused("dfs", all)[0], "dfs");
+		assert_eq!(unused(&lib, "dfs", all), vec!["dfs", "dfs-impl", "dummyf", "graph"]);
+		assert_eq!(unused(&lib, "graph", all), vec!["dfs", "dfs-impl", "dummyf", "graph"]);
+		assert_eq!(unused(&lib, "mint", all), vec!["mint", "qpow"]);
+		assert_eq!(unused(&lib, "qpow", all), vec!["qpow"]);
+		lib.pieces.insert("bfs".to_owned(), mock_piece("bfs", &["graph"], Some("graph")));
+		assert_eq!(unused(&lib, "dfs", all), vec!["dfs", "dfs-impl", "dummyf"]);
+		assert_eq!(unused(&lib, "dfs", |id| id != "bfs"), vec!["dfs", "dfs-impl", "dummyf", "graph"]);
+	}
+
+	#[tokio::test]
+	async fn placing_in_space_indented() {
+		let mut lib = Library::new_empty();
+		let graph = mock_struct("graph", "struct Graph {", &[], None);
+		let code = "struct Graph {\n    void f() {\n        if (x) { g(\"}\"); } // }\n    }\n  };".to_owned();
+		lib.pieces.insert("graph".to_owned(), Piece { code, ..graph });
+		lib.pieces.insert("bfs".to_owned(), mock_piece("bfs", &["graph"], Some("graph")));
+		lib.verify().unwrap();
+		assert_eq!(
+			replace(&lib, "bfs", "").await,
+			r#"
+struct Graph {
+    void f() {
+        if (x) { g("}"); } // }
+    }
+	{{bfs}}
+  };
+
+"#
+		);
+		let mut broken = "struct Graph {\n    int n;\n".to_owned();
+		let result = lib.walk_graph("bfs", PlaceMockContext { lib: &lib, buf: &mut broken }).await;
+		assert!(result.is_err());
+	}
+
+	fn unused<'a>(lib: &'a Library, piece_id: &str, has: impl FnMut(&str) -> bool) -> Vec<&'a str> {
+		let mut unused = lib.find_unused(piece_id, has);
+		unused.sort();
+		unused
+	}
+
+	#[test]
+	fn locating() {
+		let mut lib = Library::new_empty();
+		lib.pieces.insert("graph".to_owned(), mock_struct("graph", "struct Graph {", &[], None));
+		lib.pieces.insert("bfs".to_owned(), mock_piece("bfs", &["graph"], Some("graph")));
+		lib.pieces.insert("qpow".to_owned(), mock_piece("qpow", &[], None));
+		let source = r#"using namespace std;
+
+// qpaste graph 0000000000000001
+struct Graph {
+	{{bfs}}
+};
+// qpaste end graph
+
+{{qpow}}
+
+int main() {
+}
+"#;
+		assert_eq!(lib.locate("graph", source), Some(2..8));
+		assert_eq!(lib.locate("bfs", source), Some(4..5));
+		assert_eq!(lib.locate("qpow", source), Some(8..10));
+		lib.pieces.get_mut("qpow").unwrap().code = "{{qpow2}}".to_owned();
+		assert_eq!(lib.locate("qpow", source), None);
+	}
+
 	async fn replace(lib: &Library, piece: &str, code: &str) -> String {
 		let mut buf = code.to_owned();
 		lib.walk_graph(piece, PlaceMockContext { lib: &lib, buf: &mut buf }).await.unwrap();
@@ -427,7 +920,7 @@ struct Graph {
 		}
 
 		async fn paste(&mut self, piece: &str) -> R<()> {
-			let ((line, column), snippet) = self.lib.place(piece, &self.buf);
+			let ((line, column), snippet) = self.lib.place(piece, &self.buf)?;
 			*self.buf = self
 				.buf
 				.split('\n')
@@ -469,14 +962,30 @@ struct Graph {
 			name: id.to_owned(),
 			description: None,
 			detail: None,
+			tags: Vec::new(),
+			complexity: None,
 			code: format!("{{{{{}}}}}", id),
 			guarantee: format!("{{{{{}}}}}", id),
 			dependencies: dependencies.iter().map(|s| (*s).to_owned()).collect(),
 			parent: parent.map(|s| s.to_owned()),
+			version: None,
+			test: None,
 			modified: SystemTime::now(),
 		}
 	}
 
+	fn mock_struct(id: &str, guarantee: &str, dependencies: &[&str], parent: Option<&str>) -> Piece {
+		Piece {
+			code: format!("{}\n}};", guarantee),
+			guarantee: guarantee.to_owned(),
+			..mock_piece(id, dependencies, parent)
+		}
+	}
+
+	fn mock_pasted(id: &str, version: &str, lines: Range<usize>, indent: &str) -> Pasted {
+		Pasted { id: id.to_owned(), version: version.to_owned(), lines, indent: indent.to_owned() }
+	}
+
 	fn example_library() -> Library {
 		let mut lib = Library::new_empty();
 		lib.pieces.insert("dummyf".to_owned(), mock_piece("dummyf", &[], None));
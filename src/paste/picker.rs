@@ -0,0 +1,179 @@
+use crate::{
+	assets, paste::logic::{Library, Piece}, util::{fmt::html_escape, time_now}
+};
+use evscode::{quick_pick::Item, Scope, State, E, R};
+use std::{cmp::Ordering, collections::HashMap, time::UNIX_EPOCH};
+
+/// Show a preview of the highlighted piece's code beside the editor when choosing a piece to paste.
+#[evscode::config]
+static PREVIEW: evscode::Config<bool> = true;
+
+/// Times of the latest pastes of every piece, in seconds since the Unix epoch.
+static USAGE: State<HashMap<String, Vec<u64>>> = State::new("icie.paste.usage", Scope::Global);
+
+/// Number of the most recently and frequently pasted pieces listed before all others.
+const RECENT_LIMIT: usize = 5;
+/// Number of the latest pastes of every piece that are remembered.
+const USE_LIMIT: usize = 20;
+/// Time after which a paste counts as half a paste when ranking pieces, in seconds.
+const USE_HALF_LIFE: f64 = 7. * 24. * 60. * 60.;
+
+/// Lets the user choose a piece, listing the recently and frequently pasted ones first and the rest sorted by their
+/// first tag. Typed text is also matched against the tags and complexity.
+pub async fn select_piece(library: &Library) -> R<String> {
+	let usage = USAGE.get()?.unwrap_or_default();
+	let pieces = arrange(library, &usage, now());
+	let items = pieces.iter().map(|(id, recent)| {
+		let piece = &library.pieces[*id];
+		let mut item = piece_item(id, piece);
+		if *recent {
+			item = item.description(format!("$(history) {}", describe(piece)).trim_end().to_owned());
+		}
+		item
+	});
+	let picker = evscode::QuickPick::new().match_on_all().items(items);
+	let id = if PREVIEW.get() && !pieces.is_empty() {
+		let previews = pieces
+			.iter()
+			.map(|(id, _)| ((*id).to_owned(), render_preview(&library.pieces[*id])))
+			.collect::<HashMap<_, _>>();
+		let preview = evscode::Webview::new("icie.paste.preview", "ICIE Paste Preview", 2).preserve_focus().create();
+		preview.webview.set_html(&previews[pieces[0].0]);
+		let webview = (*preview.webview).clone();
+		let id = picker.on_highlight(move |id: String| webview.set_html(&previews[&id])).show().await;
+		preview.webview.dispose();
+		id
+	} else {
+		picker.show().await
+	};
+	id.ok_or_else(E::cancel)
+}
+
+/// Remembers that a piece was pasted, so that it will be listed higher.
+pub async fn record_use(piece_id: &str) -> R<()> {
+	let mut usage = USAGE.get()?.unwrap_or_default();
+	let uses = usage.entry(piece_id.to_owned()).or_default();
+	uses.push(now());
+	if uses.len() > USE_LIMIT {
+		uses.remove(0);
+	}
+	USAGE.set(&usage).await;
+	Ok(())
+}
+
+pub fn piece_item(id: &str, piece: &Piece) -> Item<String> {
+	let mut item = Item::new(id.to_owned(), piece.name.clone());
+	let description = describe(piece);
+	if !description.is_empty() {
+		item = item.description(description);
+	}
+	let detail = match (&piece.description, &piece.detail) {
+		(Some(description), Some(detail)) => Some(format!("{} {}", description, detail)),
+		(description, detail) => description.as_ref().or(detail.as_ref()).cloned(),
+	};
+	if let Some(detail) = detail {
+		item = item.detail(detail);
+	}
+	item
+}
+
+fn describe(piece: &Piece) -> String {
+	let tags = piece.tags.iter().map(|tag| format!("#{}", tag));
+	tags.chain(piece.complexity.clone()).collect::<Vec<_>>().join(" ")
+}
+
+/// Orders pieces for the quick pick, returning their ids and whether they are listed as recently used.
+fn arrange<'a>(library: &'a Library, usage: &HashMap<String, Vec<u64>>, now: u64) -> Vec<(&'a str, bool)> {
+	let mut recent = library
+		.pieces
+		.keys()
+		.filter_map(|id| Some((id.as_str(), frecency(usage.get(id)?, now))))
+		.filter(|(_, score)| *score > 0.)
+		.collect::<Vec<_>>();
+	recent.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal).then(a.0.cmp(b.0)));
+	recent.truncate(RECENT_LIMIT);
+	let mut rest =
+		library.pieces.iter().filter(|(id, _)| !recent.iter().any(|(recent, _)| recent == id)).collect::<Vec<_>>();
+	// Untagged pieces go last, after all the tagged ones.
+	rest.sort_by_key(|(id, piece)| (piece.tags.is_empty(), piece.tags.first(), &piece.name, *id));
+	let recent = recent.into_iter().map(|(id, _)| (id, true));
+	recent.chain(rest.into_iter().map(|(id, _)| (id.as_str(), false))).collect()
+}
+
+/// Scores a piece by how often it was pasted, with recent pastes counting more.
+fn frecency(uses: &[u64], now: u64) -> f64 {
+	uses.iter().map(|time| 0.5f64.powf(now.saturating_sub(*time) as f64 / USE_HALF_LIFE)).sum()
+}
+
+fn render_preview(piece: &Piece) -> String {
+	let description = piece.description.iter().chain(&piece.detail).map(|text| html_escape(text)).collect::<Vec<_>>();
+	format!(
+		r#"
+		<html>
+			<head>
+				{css}
+			</head>
+			<body>
+				<h2>{name}</h2>
+				<p class="tags">{tags}</p>
+				<p>{description}</p>
+				<pre class="code">{code}</pre>
+			</body>
+		</html>
+	"#,
+		css = assets::html_css_dynamic(include_str!("preview.css")),
+		name = html_escape(&piece.name),
+		tags = html_escape(&describe(piece)),
+		description = description.join(" "),
+		code = html_escape(&piece.code),
+	)
+}
+
+fn now() -> u64 {
+	time_now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+#[test]
+fn test_arrange() {
+	let mut library = Library::new_empty();
+	let piece = |name: &str, tags: &[&str]| Piece {
+		name: name.to_owned(),
+		description: None,
+		detail: None,
+		tags: tags.iter().map(|tag| (*tag).to_owned()).collect(),
+		complexity: None,
+		code: String::new(),
+		guarantee: String::new(),
+		dependencies: Vec::new(),
+		parent: None,
+		version: None,
+		test: None,
+		modified: UNIX_EPOCH,
+	};
+	library.pieces.insert("dijkstra".to_owned(), piece("Dijkstra", &["graph"]));
+	library.pieces.insert("bfs".to_owned(), piece("BFS", &["graph"]));
+	library.pieces.insert("fft".to_owned(), piece("FFT", &["math"]));
+	library.pieces.insert("qpow".to_owned(), piece("Fast power", &["math"]));
+	library.pieces.insert("misc".to_owned(), piece("Misc", &[]));
+	let week = USE_HALF_LIFE as u64;
+	let now = 10 * week;
+	let mut usage = HashMap::new();
+	usage.insert("qpow".to_owned(), vec![now - 5 * week, now - 4 * week, now - 3 * week]);
+	usage.insert("misc".to_owned(), vec![now - 60]);
+	assert_eq!(arrange(&library, &usage, now), vec![
+		("misc", true),
+		("qpow", true),
+		("bfs", false),
+		("dijkstra", false),
+		("fft", false)
+	]);
+}
+
+#[test]
+fn test_frecency() {
+	let week = USE_HALF_LIFE as u64;
+	let now = 10 * week;
+	assert_eq!(frecency(&[], now), 0.);
+	assert_eq!(frecency(&[now, now], now), 2.);
+	assert_eq!(frecency(&[now - week, now - 2 * week], now), 0.75);
+}
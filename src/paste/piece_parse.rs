@@ -1,11 +1,47 @@
-use crate::paste::{logic::Piece, qpaste_doc_error};
+use crate::{
+	bundle::{is_pragma_once, local_includes}, paste::{logic::Piece, qpaste_doc_error}
+};
 use evscode::{error::ResultExt, E, R};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::{collections::HashMap, time::SystemTime};
 
+/// Header formats of the library pieces, so that libraries written for other tools can be used without changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+	/// ICIE's own `/// Name:` headers, described in QUICKPASTE.md.
+	Icie,
+	/// [KACTL](https://github.com/kth-competitive-programming/kactl) `/** Description: ... */` blocks, with the
+	/// dependencies included with `#include`.
+	Kactl,
+	/// Headers with `#pragma once`, with the dependencies included with `#include`.
+	PragmaOnce,
+}
+
+impl Format {
+	pub fn detect(code: &str) -> Option<Format> {
+		if code.lines().any(|line| line.starts_with("///")) {
+			Some(Format::Icie)
+		} else if code.trim_start().starts_with("/**") {
+			Some(Format::Kactl)
+		} else if code.lines().any(is_pragma_once) {
+			Some(Format::PragmaOnce)
+		} else {
+			None
+		}
+	}
+}
+
 impl Piece {
 	pub fn parse(code: &str, id: String, modified: SystemTime) -> R<Piece> {
+		match Format::detect(code) {
+			Some(Format::Icie) | None => Piece::parse_icie(code, id, modified),
+			Some(Format::Kactl) => Piece::parse_kactl(code, id, modified),
+			Some(Format::PragmaOnce) => Piece::parse_pragma_once(code, id, modified),
+		}
+	}
+
+	fn parse_icie(code: &str, id: String, modified: SystemTime) -> R<Piece> {
 		let headers = code
 			.lines()
 			.filter(|line| line.starts_with("///"))
@@ -19,18 +55,126 @@ impl Piece {
 		let name = headers.field("Name")?.to_owned();
 		let description = headers.optfield("Description").map(String::from);
 		let detail = headers.optfield("Detail").map(String::from);
+		let tags = headers.listfield("Tags");
+		let complexity = headers.optfield("Complexity").map(String::from);
 		let guarantee = headers.field("Guarantee")?.to_owned();
-		let dependencies = headers
-			.optfield("Dependencies")
-			.unwrap_or("")
-			.split(',')
-			.filter(|s| !s.trim().is_empty())
-			.map(|s| s.trim().to_owned())
-			.collect::<Vec<_>>();
+		let dependencies = headers.listfield("Dependencies");
 		let parent = headers.optfield("Parent").map(String::from);
+		let test = headers.optfield("Test").map(String::from);
 		let code = code.lines().filter(|line| !line.starts_with("///")).collect::<Vec<_>>().join("\n");
-		Ok(Piece { name, description, detail, code, guarantee, dependencies, parent, modified })
+		let version = Some(version_hash(&code));
+		Ok(Piece {
+			name,
+			description,
+			detail,
+			tags,
+			complexity,
+			code,
+			guarantee,
+			dependencies,
+			parent,
+			version,
+			test,
+			modified,
+		})
+	}
+
+	fn parse_kactl(code: &str, id: String, modified: SystemTime) -> R<Piece> {
+		let start = code.find("/**").unwrap() + 3;
+		let end = start + code[start..].find("*/").wrap(format!("unterminated header comment in piece {:?}", id))?;
+		// Fields can span many lines, which are joined like in Markdown paragraphs.
+		let mut fields: Vec<(&str, String)> = Vec::new();
+		for line in code[start..end].lines().map(|line| line.trim().trim_start_matches('*').trim()) {
+			if let Some(cap) = KACTL_FIELD_REGEX.captures(line) {
+				fields.push((cap.get(1).unwrap().as_str(), cap[2].to_owned()));
+			} else if let Some((_, value)) = fields.last_mut().filter(|_| !line.is_empty()) {
+				*value += " ";
+				*value += line;
+			}
+		}
+		let field = |key: &str| fields.iter().find(|(k, _)| *k == key).map(|(_, value)| value.clone());
+		let description = field("Description");
+		let complexity = field("Time");
+		Piece::parse_header_file(&code[end + 2..], id, description, complexity, modified)
+	}
+
+	fn parse_pragma_once(code: &str, id: String, modified: SystemTime) -> R<Piece> {
+		Piece::parse_header_file(code, id, None, None, modified)
+	}
+
+	/// Creates a piece from a header file, named after the file, tagged with the name of its' directory and depending
+	/// on the locally included headers. The first line of code is used as the guarantee.
+	fn parse_header_file(
+		code: &str,
+		id: String,
+		description: Option<String>,
+		complexity: Option<String>,
+		modified: SystemTime,
+	) -> R<Piece> {
+		let dependencies = local_includes(code).map(|include| resolve_include(&id, include)).collect();
+		let code = code
+			.lines()
+			.filter(|line| !is_pragma_once(line) && local_includes(line).next().is_none())
+			.collect::<Vec<_>>()
+			.join("\n");
+		let code = code.trim_start_matches('\n').trim_end().to_owned();
+		let guarantee = code
+			.lines()
+			.map(str::trim)
+			.find(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with("//"))
+			.wrap(format!("piece {:?} does not contain any code", id))?
+			.to_owned();
+		let mut path = id.rsplit('/');
+		let name = path.next().unwrap().to_owned();
+		let tags = path.next().map(String::from).into_iter().collect();
+		let version = Some(version_hash(&code));
+		Ok(Piece {
+			name,
+			description,
+			detail: None,
+			tags,
+			complexity,
+			code,
+			guarantee,
+			dependencies,
+			parent: None,
+			version,
+			test: None,
+			modified,
+		})
+	}
+}
+
+/// Converts a path from an #include directive to the id of the included piece, resolving it relative to the directory
+/// of the including piece.
+fn resolve_include(id: &str, include: &str) -> String {
+	let mut components = id.split('/').collect::<Vec<_>>();
+	components.pop();
+	for component in include.split('/') {
+		match component {
+			"." => (),
+			".." => {
+				components.pop();
+			},
+			component => components.push(component),
+		}
+	}
+	let path = components.join("/");
+	match path.rfind('.') {
+		Some(dot) if !path[dot..].contains('/') => path[..dot].to_owned(),
+		_ => path,
+	}
+}
+
+/// Computes a FNV-1a hash of the piece code, which unlike the standard library hashers is guaranteed to stay the same
+/// between ICIE versions, so that markers in old solutions are still recognized.
+fn version_hash(code: &str) -> String {
+	let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+	for byte in code.trim_end().bytes() {
+		hash ^= u64::from(byte);
+		hash = hash.wrapping_mul(0x0100_0000_01b3);
 	}
+	format!("{:016x}", hash)
 }
 
 struct Headers<'a> {
@@ -48,9 +192,15 @@ impl Headers<'_> {
 	fn optfield(&self, key: &str) -> Option<&str> {
 		self.headers.get(key).and_then(|value| if value.trim().is_empty() { None } else { Some(value.trim()) })
 	}
+
+	fn listfield(&self, key: &str) -> Vec<String> {
+		let list = self.optfield(key).unwrap_or("").split(',');
+		list.filter(|s| !s.trim().is_empty()).map(|s| s.trim().to_owned()).collect()
+	}
 }
 
 static HEADER_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new("/// *([a-zA-Z]+): *(.*)").unwrap());
+static KACTL_FIELD_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new("^([A-Z][a-zA-Z]*): *(.*)$").unwrap());
 
 #[test]
 fn test_simple() {
@@ -97,7 +247,11 @@ struct FU {
 	);
 	assert_eq!(piece.guarantee, "struct FU {");
 	assert_eq!(piece.dependencies, Vec::<String>::new());
+	assert_eq!(piece.tags, Vec::<String>::new());
+	assert_eq!(piece.complexity, None);
 	assert_eq!(piece.parent, None);
+	assert_eq!(piece.test, None);
+	assert_eq!(piece.version, Some("e1fbf86e5d828ce7".to_owned()));
 	assert_eq!(piece.modified, modified);
 }
 
@@ -107,8 +261,11 @@ fn test_complex() {
 /// Description: Depth First Search
 /// Detail: An algorithm for traversing or searching tree or graph data structures with backtracking.
 /// Guarantee: void DFS(
+/// Tags: graph, dfs
+/// Complexity: O(n + m)
 /// Dependencies: graph, dummyf, dfs-impl
 /// Parent: graph
+/// Test: graph/dfs-test.cpp
 template <typename Pre=Dummyf, typename Post=Dummyf, typename PreE=Dummyf, typename PostE=Dummyf, typename FailE=Dummyf> void DFS(int source, Pre pre, Post post, PreE pree, PostE poste, FailE faile) const {
 	auto visit = vector<bool>(size(), false);
 	implDFS(source, visit, pre, post, pree, poste, faile);
@@ -130,7 +287,61 @@ template <typename Pre=Dummyf, typename Post=Dummyf, typename PreE=Dummyf, typen
 }"#
 	);
 	assert_eq!(piece.guarantee, "void DFS(");
+	assert_eq!(piece.tags, &["graph", "dfs"]);
+	assert_eq!(piece.complexity, Some("O(n + m)".to_owned()));
 	assert_eq!(piece.dependencies, &["graph", "dummyf", "dfs-impl"]);
 	assert_eq!(piece.parent, Some("graph".to_owned()));
+	assert_eq!(piece.test, Some("graph/dfs-test.cpp".to_owned()));
 	assert_eq!(piece.modified, modified);
 }
+
+#[test]
+fn test_kactl() {
+	let code = r#"/**
+ * Author: Lukas Polacek, Simon Lindholm
+ * Date: 2019-12-26
+ * License: CC0
+ * Source: folklore
+ * Description: Disjoint-set data structure with undo.
+ * If undo is not needed, skip st, time() and rollback().
+ * Usage: int t = uf.time(); ...; uf.rollback(t);
+ * Time: $O(\log(N))$
+ */
+#pragma once
+
+#include "../various/Rollback.h"
+
+struct RollbackUF {
+	vi e; vector<pii> st;
+};
+"#;
+	let piece = Piece::parse(code, "kactl/data-structures/UnionFindRollback".to_owned(), SystemTime::now()).unwrap();
+	assert_eq!(piece.name, "UnionFindRollback");
+	assert_eq!(
+		piece.description,
+		Some("Disjoint-set data structure with undo. If undo is not needed, skip st, time() and rollback().".to_owned())
+	);
+	assert_eq!(piece.detail, None);
+	assert_eq!(piece.tags, &["data-structures"]);
+	assert_eq!(piece.complexity, Some("$O(\\log(N))$".to_owned()));
+	assert_eq!(piece.code, "struct RollbackUF {\n\tvi e; vector<pii> st;\n};");
+	assert_eq!(piece.guarantee, "struct RollbackUF {");
+	assert_eq!(piece.dependencies, &["kactl/various/Rollback"]);
+}
+
+#[test]
+fn test_pragma_once() {
+	let code = r#"#pragma once
+#include <vector>
+#include "modint.hpp"
+
+// Number theoretic transform.
+void ntt(std::vector<mint>& a) {
+}
+"#;
+	let piece = Piece::parse(code, "math/ntt".to_owned(), SystemTime::now()).unwrap();
+	assert_eq!(piece.name, "ntt");
+	assert_eq!(piece.code, "#include <vector>\n\n// Number theoretic transform.\nvoid ntt(std::vector<mint>& a) {\n}");
+	assert_eq!(piece.guarantee, "void ntt(std::vector<mint>& a) {");
+	assert_eq!(piece.dependencies, &["math/modint"]);
+}
@@ -0,0 +1,95 @@
+use crate::{
+	checker::FreeWhitespaceChecker, compile::{self, Codegen}, executable::Environment, paste::{
+		library::{CACHED_LIBRARY, TEST_SUFFIX}, logic::{Library, PasteContext}, qpaste_doc_error
+	}, test::{judge::simple_test, time_limit, Task, Verdict}, util::{fs, path::Path, tempfile::Tempfile, SourceTarget}
+};
+use async_trait::async_trait;
+use evscode::{E, R};
+
+/// Compiles and runs the test programs of all library pieces. Each test program is a C++ file with the piece and its'
+/// dependencies pasted in, like into a solution. The test passes if it exits successfully and prints the contents of
+/// the .out file next to it, if there is one.
+#[evscode::command(title = "ICIE Test library")]
+async fn test_library() -> R<()> {
+	let _status = crate::STATUS.push("Testing library");
+	let library = CACHED_LIBRARY.update().await?;
+	let mut tested = Vec::new();
+	for (id, piece) in &library.pieces {
+		if let Some(test) = find_test(&library, id).await? {
+			tested.push((id, piece, test));
+		}
+	}
+	if tested.is_empty() {
+		return Err(qpaste_doc_error(E::error("none of the library pieces have tests")));
+	}
+	tested.sort_by_key(|(_, piece, _)| &piece.name);
+	// The checker configured for the current task has nothing to do with the library.
+	let task = Task {
+		checker: Box::new(FreeWhitespaceChecker),
+		environment: Environment { time_limit: time_limit(), cwd: None },
+	};
+	let progress = evscode::Progress::new().title("Testing library").show().0;
+	let mut failures = Vec::new();
+	for (id, piece, test) in &tested {
+		if let Err(e) = test_piece(&library, id, test, &task).await {
+			failures.push(format!("{} ({}): {}", piece.name, id, e.human()));
+		}
+		progress.update_inc(100. / tested.len() as f64, &piece.name);
+	}
+	if failures.is_empty() {
+		let message = format!("All {} library tests passed", tested.len());
+		evscode::Message::new::<()>(&message).show().await;
+		Ok(())
+	} else {
+		let message = format!("{} of {} library tests failed", failures.len(), tested.len());
+		Err(E::error(message).extended(failures.join("\n")))
+	}
+}
+
+async fn find_test(library: &Library, piece_id: &str) -> R<Option<Path>> {
+	let path = &library.paths[piece_id];
+	if let Some(test) = &library.pieces[piece_id].test {
+		return Ok(Some(path.parent().join(test)));
+	}
+	let sibling = Path::from_native(format!("{}{}.cpp", path.without_extension(), TEST_SUFFIX));
+	Ok(if fs::exists(&sibling).await? { Some(sibling) } else { None })
+}
+
+async fn test_piece(library: &Library, piece_id: &str, test: &Path, task: &Task) -> R<()> {
+	let mut code = fs::read_to_string(test).await?;
+	library.walk_graph(piece_id, ScratchPaste { library, code: &mut code }).await?;
+	let input = read_if_exists(&test.with_extension("in")).await?;
+	let output = read_if_exists(&test.with_extension("out")).await?;
+	let source = Tempfile::new("library_test", ".cpp", &code).await?;
+	let executable = compile::compile(&SourceTarget::Custom(source.path().clone()), Codegen::Debug, false).await?;
+	let outcome = simple_test(&executable, input.as_deref().unwrap_or(""), output.as_deref(), None, task).await?;
+	match outcome.verdict {
+		Verdict::Accepted { .. } | Verdict::IgnoredNoOut => Ok(()),
+		verdict => Err(E::error(verdict.to_string()).extended(outcome.stderr)),
+	}
+}
+
+async fn read_if_exists(path: &Path) -> R<Option<String>> {
+	Ok(if fs::exists(path).await? { Some(fs::read_to_string(path).await?) } else { None })
+}
+
+/// Pastes pieces into a test program kept in memory instead of an editor.
+struct ScratchPaste<'a> {
+	library: &'a Library,
+	code: &'a mut String,
+}
+
+#[async_trait(?Send)]
+impl PasteContext for ScratchPaste<'_> {
+	fn has(&mut self, piece_id: &str) -> bool {
+		self.code.contains(&self.library.pieces[piece_id].guarantee)
+	}
+
+	async fn paste(&mut self, piece_id: &str) -> R<()> {
+		let ((line, column), snippet) = self.library.place(piece_id, self.code)?;
+		let mut lines = self.code.split('\n').map(str::to_owned).collect::<Vec<_>>();
+		lines[line].insert_str(column, &snippet);
+		*self.code = lines.join("\n");
+		Ok(())
+	}
+}
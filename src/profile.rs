@@ -0,0 +1,118 @@
+mod callgrind;
+mod perf;
+mod render;
+
+use crate::{
+	compile, compile::Codegen, debug::{require_native, VALGRIND}, executable::Environment, service::Service, test, util::{fs, path::Path, SourceTarget}
+};
+use evscode::{
+	stdlib::decorations::{Decoration, Style}, DecorationType, Position, Range, R
+};
+use once_cell::sync::Lazy;
+use std::{cmp::Ordering, collections::HashMap};
+
+pub const PERF: Service = Service {
+	human_name: "perf",
+	exec_linuxmac: Some("perf"),
+	exec_windows: None,
+	package_apt: Some("linux-perf"),
+	package_brew: None,
+	package_pacman: Some("perf"),
+	tutorial_url_windows: None,
+	supports_linux: true,
+	supports_windows: false,
+	supports_macos: false,
+};
+
+/// Lines that took less than this percentage of the running time are not decorated in the editor.
+#[evscode::config]
+static DECORATION_THRESHOLD: evscode::Config<u64> = 1;
+
+/// Share of the running time spent in functions and source lines, sorted from the hottest.
+#[derive(Debug, PartialEq)]
+pub struct Profile {
+	pub functions: Vec<Function>,
+	pub lines: Vec<Line>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Function {
+	pub name: String,
+	pub percent: f64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Line {
+	pub path: String,
+	pub line: usize,
+	pub percent: f64,
+}
+
+/// Runs the solution on a test under perf, or Callgrind if perf is not installed, and shows where it spent the most
+/// time. The solution is compiled with the Profile codegen, which keeps the optimizations but does not inline
+/// functions, so that the results are close to the real running time but can still be attributed to functions.
+pub async fn profile(in_path: &Path, source: SourceTarget) -> R<()> {
+	let _status = crate::STATUS.push("Profiling");
+	require_native(&source, "Profiling").await?;
+	let path = source.to_path().await?;
+	let executable = compile::compile_solution(&source, Codegen::Profile).await?;
+	let input = fs::read_to_string(in_path).await?;
+	let profile = if PERF.is_installed().await? {
+		let environment = Environment { time_limit: test::time_limit().map(|limit| limit * 2), cwd: None };
+		perf::profile(&executable, &input, &environment).await?
+	} else if VALGRIND.is_installed().await? {
+		// Callgrind simulates the processor, which makes the program run around fifty times slower.
+		let environment = Environment { time_limit: test::time_limit().map(|limit| limit * 50), cwd: None };
+		callgrind::profile(&executable, &input, &environment).await?
+	} else {
+		return Err(PERF.not_installed().await?);
+	};
+	let webview =
+		evscode::Webview::new("icie.profile", "ICIE Profile", 2).enable_find_widget().preserve_focus().create();
+	webview.webview.set_html(&render::render(&profile).await);
+	evscode::open_editor(path.as_str()).open().await?;
+	decorate(&path, &profile);
+	Ok(())
+}
+
+fn decorate(source: &Path, profile: &Profile) {
+	thread_local! {
+		static DECORATIONS: Lazy<DecorationType> = Lazy::new(|| DecorationType::new(Style {
+			after_color: Some("rgba(255, 128, 32, 0.8)"),
+			background_color: Some("rgba(255, 128, 32, 0.1)"),
+			whole_line: true,
+		}));
+	}
+	let threshold = DECORATION_THRESHOLD.get() as f64;
+	let decorations = profile
+		.lines
+		.iter()
+		.filter(|line| line.path == source.as_str() && line.percent >= threshold)
+		.map(|line| {
+			let position = || Position { line: line.line - 1, column: 0 };
+			Decoration {
+				range: Range { start: position(), end: position() },
+				hover_message: Some(format!("{:.1}% of the running time was spent on this line", line.percent)),
+				after_text: Some(format!("{:.1}%", line.percent)),
+			}
+		})
+		.collect::<Vec<_>>();
+	DECORATIONS.with(|decoration_type| decoration_type.set(source.as_str(), &decorations));
+}
+
+/// Converts the costs of functions and lines to percentages of the total cost, and sorts them from the hottest.
+fn from_costs(functions: HashMap<String, f64>, lines: HashMap<(String, usize), f64>) -> Profile {
+	let total = functions.values().sum::<f64>();
+	let percent = |cost: f64| if total > 0.0 { cost * 100.0 / total } else { 0.0 };
+	let mut functions = functions
+		.into_iter()
+		.map(|(name, cost)| Function { name, percent: percent(cost) })
+		.collect::<Vec<_>>();
+	let mut lines =
+		lines.into_iter().map(|((path, line), cost)| Line { path, line, percent: percent(cost) }).collect::<Vec<_>>();
+	functions.sort_by(|a, b| b.percent.partial_cmp(&a.percent).unwrap_or(Ordering::Equal).then(a.name.cmp(&b.name)));
+	lines.sort_by(|a, b| {
+		b.percent.partial_cmp(&a.percent).unwrap_or(Ordering::Equal).then((&a.path, a.line).cmp(&(&b.path, b.line)))
+	});
+	Profile { functions, lines }
+}
@@ -0,0 +1,146 @@
+use crate::{
+	debug::VALGRIND, executable::{Environment, Executable}, profile::{from_costs, Profile}, util::{fs, tempfile::Tempfile}
+};
+use evscode::{E, R};
+use std::collections::HashMap;
+
+pub async fn profile(executable: &Executable, input: &str, environment: &Environment) -> R<Profile> {
+	let valgrind = VALGRIND.find_executable().await?;
+	let output = Tempfile::new("callgrind", ".out", "").await?;
+	let output_flag = format!("--callgrind-out-file={}", output.path().as_str());
+	let mut args = vec!["--tool=callgrind", output_flag.as_str(), "--", executable.command.as_str()];
+	args.extend(executable.args.iter().map(String::as_str));
+	let run = valgrind.with_stack_size(executable.stack_size).run(input, &args, environment).await?;
+	let profile = fs::read_to_string(output.path()).await?;
+	if profile.is_empty() {
+		return Err(E::error("Callgrind did not record a profile").extended(run.stderr));
+	}
+	let (functions, lines) = parse(&profile);
+	Ok(from_costs(functions, lines))
+}
+
+/// Parses the [Callgrind profile format], summing the exclusive costs of the first event, which by default is the
+/// number of executed instructions.
+///
+/// [Callgrind profile format]: https://valgrind.org/docs/manual/cl-format.html
+fn parse(profile: &str) -> (HashMap<String, f64>, HashMap<(String, usize), f64>) {
+	let mut functions = HashMap::new();
+	let mut lines = HashMap::new();
+	// Names are compressed by giving them a number the first time they appear, like "fn=(1) main", and using only the
+	// number later, like "fn=(1)". Files and functions have separate numbering.
+	let mut file_names = HashMap::new();
+	let mut function_names = HashMap::new();
+	let mut line_position = 0;
+	let mut file = String::new();
+	let mut current_file = String::new();
+	let mut function = String::new();
+	let mut last_positions: Vec<i64> = Vec::new();
+	let mut skip_call_cost = false;
+	for line in profile.lines() {
+		if let Some(positions) = line.strip_prefix("positions:") {
+			let positions = positions.split_whitespace().collect::<Vec<_>>();
+			line_position = positions.iter().position(|position| *position == "line").unwrap_or(0);
+			last_positions = vec![0; positions.len()];
+		} else if let Some(name) = line.strip_prefix("fl=") {
+			file = decompress(&mut file_names, name);
+			current_file = file.clone();
+		} else if let Some(name) = line.strip_prefix("fi=").or_else(|| line.strip_prefix("fe=")) {
+			current_file = decompress(&mut file_names, name);
+		} else if let Some(name) = line.strip_prefix("fn=") {
+			function = decompress(&mut function_names, name);
+			current_file = file.clone();
+		} else if let Some(name) = line.strip_prefix("cfi=").or_else(|| line.strip_prefix("cfl=")) {
+			decompress(&mut file_names, name);
+		} else if let Some(name) = line.strip_prefix("cfn=") {
+			decompress(&mut function_names, name);
+		} else if line.starts_with("calls=") {
+			// The next cost line contains the inclusive cost of the call, which is already counted in the callee.
+			skip_call_cost = true;
+		} else if line.starts_with(|c: char| c.is_ascii_digit() || c == '+' || c == '-' || c == '*') {
+			if last_positions.is_empty() {
+				last_positions = vec![0];
+			}
+			let mut fields = line.split_whitespace();
+			for last in &mut last_positions {
+				*last = match fields.next() {
+					Some("*") | None => *last,
+					Some(field) if field.starts_with('+') || field.starts_with('-') => *last + parse_number(field),
+					Some(field) => parse_number(field),
+				};
+			}
+			let cost = fields.next().map_or(0, parse_number) as f64;
+			if skip_call_cost {
+				skip_call_cost = false;
+				continue;
+			}
+			*functions.entry(function.clone()).or_default() += cost;
+			let line_number = last_positions[line_position];
+			if line_number > 0 && current_file != "???" {
+				*lines.entry((current_file.clone(), line_number as usize)).or_default() += cost;
+			}
+		}
+	}
+	(functions, lines)
+}
+
+fn decompress(names: &mut HashMap<String, String>, compressed: &str) -> String {
+	let id_and_name = compressed.strip_prefix('(').and_then(|rest| Some(rest.split_at(rest.find(')')?)));
+	match id_and_name.map(|(id, name)| (id, name[1..].trim())) {
+		Some((id, "")) => names.get(id).cloned().unwrap_or_default(),
+		Some((id, name)) => {
+			names.insert(id.to_owned(), name.to_owned());
+			name.to_owned()
+		},
+		None => compressed.to_owned(),
+	}
+}
+
+fn parse_number(field: &str) -> i64 {
+	let (sign, field) = match field.strip_prefix('-') {
+		Some(field) => (-1, field),
+		None => (1, field.trim_start_matches('+')),
+	};
+	let value = match field.strip_prefix("0x") {
+		Some(hex) => i64::from_str_radix(hex, 16),
+		None => field.parse(),
+	};
+	sign * value.unwrap_or(0)
+}
+
+#[test]
+fn test_parse() {
+	let profile = "# callgrind format
+version: 1
+positions: line
+events: Ir
+
+fl=(1) /task/main.cpp
+fn=(1) solve(int)
+5 100
++1 300
+cfn=(2) helper()
+calls=2 10
+* 50
+
+fn=(2)
+10 50
+fi=(2) /usr/include/c++/12/bits/stl_vector.h
+1046 20
+fe=(1)
+12 5
+
+fl=(3) ???
+fn=(3) _start
+0 25
+totals: 500
+";
+	let (functions, lines) = parse(profile);
+	assert_eq!(functions["solve(int)"], 400.0);
+	assert_eq!(functions["helper()"], 75.0);
+	assert_eq!(functions["_start"], 25.0);
+	assert_eq!(lines[&("/task/main.cpp".to_owned(), 6)], 300.0);
+	assert_eq!(lines[&("/task/main.cpp".to_owned(), 10)], 50.0);
+	assert_eq!(lines[&("/task/main.cpp".to_owned(), 12)], 5.0);
+	assert_eq!(lines[&("/usr/include/c++/12/bits/stl_vector.h".to_owned(), 1046)], 20.0);
+	assert_eq!(lines.len(), 5);
+}
@@ -0,0 +1,69 @@
+use crate::{
+	debug::configure_kernel_perf_event_paranoid, executable::{Environment, Executable}, profile::{from_costs, Profile, PERF}, util::tempfile::Tempfile
+};
+use evscode::{E, R};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+pub async fn profile(executable: &Executable, input: &str, environment: &Environment) -> R<Profile> {
+	let perf = PERF.find_executable().await?;
+	let data = Tempfile::new("perf", ".data", "").await?;
+	let mut args = vec!["record", "-o", data.path().as_str(), "--", executable.command.as_str()];
+	args.extend(executable.args.iter().map(String::as_str));
+	let record = perf.with_stack_size(executable.stack_size).run(input, &args, environment).await?;
+	if record.stderr.contains("perf_event_paranoid") {
+		return Err(E::error("perf is not allowed to record events, kernel.perf_event_paranoid must be <= 1")
+			.action("🔐 Auto-configure", configure_kernel_perf_event_paranoid()));
+	}
+	let functions = report(&perf, &data, &["--sort", "symbol"]).await?;
+	let lines = report(&perf, &data, &["--sort", "srcline", "--full-source-path"]).await?;
+	Ok(from_costs(parse_functions(&functions), parse_lines(&lines)))
+}
+
+async fn report(perf: &Executable, data: &Tempfile, sort: &[&str]) -> R<String> {
+	let mut args = vec!["report", "-i", data.path().as_str(), "--stdio", "-q", "--no-children"];
+	args.extend(sort);
+	let run = perf.run("", &args, &Environment { time_limit: None, cwd: None }).await?;
+	if !run.success() {
+		return Err(E::error("perf could not read the recorded samples").extended(run.stderr));
+	}
+	Ok(run.stdout)
+}
+
+/// Parses lines like "  45.32%  [.] main", skipping the samples from the kernel.
+fn parse_functions(report: &str) -> HashMap<String, f64> {
+	static SYMBOL: Lazy<Regex> = Lazy::new(|| Regex::new("^\\s*([0-9.]+)%\\s+\\[(.)\\]\\s+(.+?)\\s*$").unwrap());
+	let mut functions = HashMap::new();
+	for cap in report.lines().filter_map(|line| SYMBOL.captures(line)) {
+		if &cap[2] != "k" {
+			*functions.entry(cap[3].to_owned()).or_default() += cap[1].parse::<f64>().unwrap();
+		}
+	}
+	functions
+}
+
+/// Parses lines like "  45.32%  /home/user/task/main.cpp:12", skipping the samples without a source location.
+fn parse_lines(report: &str) -> HashMap<(String, usize), f64> {
+	static SRCLINE: Lazy<Regex> = Lazy::new(|| Regex::new("^\\s*([0-9.]+)%\\s+(.+):([0-9]+)\\s*$").unwrap());
+	let mut lines = HashMap::new();
+	for cap in report.lines().filter_map(|line| SRCLINE.captures(line)) {
+		let line = cap[3].parse::<usize>().unwrap();
+		if &cap[2] != "??" && line != 0 {
+			*lines.entry((cap[2].to_owned(), line)).or_default() += cap[1].parse::<f64>().unwrap();
+		}
+	}
+	lines
+}
+
+#[test]
+fn test_parse_report() {
+	let functions = "    62.50%  [.] solve(int)\n    30.00%  [.] main\n     7.50%  [k] clear_page_erms\n";
+	let functions = parse_functions(functions);
+	assert_eq!(functions.len(), 2);
+	assert_eq!(functions["solve(int)"], 62.5);
+	let lines = "    50.00%  /task/main.cpp:12\n    25.00%  ??:0\n    25.00%  libc.so.6[9d1c4]\n";
+	let lines = parse_lines(lines);
+	assert_eq!(lines.len(), 1);
+	assert_eq!(lines[&("/task/main.cpp".to_owned(), 12)], 50.0);
+}
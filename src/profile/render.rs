@@ -0,0 +1,68 @@
+use crate::{
+	assets, profile::Profile, util::{fmt::html_escape, fs, path::Path}
+};
+use std::collections::HashMap;
+
+/// Maximum number of functions and lines displayed in the profile view.
+const ROW_LIMIT: usize = 50;
+
+pub async fn render(profile: &Profile) -> String {
+	let functions = profile
+		.functions
+		.iter()
+		.take(ROW_LIMIT)
+		.map(|function| render_row(function.percent, &html_escape(&function.name), None))
+		.collect::<String>();
+	let mut sources = HashMap::new();
+	let mut lines = String::new();
+	for line in profile.lines.iter().take(ROW_LIMIT) {
+		if !sources.contains_key(&line.path) {
+			// System headers may be missing, in which case only the location is displayed.
+			let code = fs::read_to_string(&Path::from_native(line.path.clone())).await.ok();
+			sources.insert(line.path.clone(), code);
+		}
+		let code = sources[&line.path].as_ref().and_then(|code| code.lines().nth(line.line - 1)).map(str::trim);
+		let location = format!("{}:{}", Path::from_native(line.path.clone()).fmt_workspace(), line.line);
+		lines += &render_row(line.percent, &html_escape(&location), code.map(html_escape).as_deref());
+	}
+	format!(
+		r#"
+		<html>
+			<head>
+				{css}
+			</head>
+			<body>
+				<h2>Hot functions</h2>
+				<table class="profile">
+					{functions}
+				</table>
+				<h2>Hot lines</h2>
+				<table class="profile">
+					{lines}
+				</table>
+			</body>
+		</html>
+	"#,
+		css = assets::html_css_dynamic(include_str!("style.css")),
+		functions = functions,
+		lines = lines,
+	)
+}
+
+fn render_row(percent: f64, name: &str, code: Option<&str>) -> String {
+	format!(
+		r#"
+		<tr>
+			<td class="percent">
+				<div class="bar" style="width: {percent:.1}%"></div>
+				<span>{percent:.1}%</span>
+			</td>
+			<td class="name">{name}</td>
+			<td class="code">{code}</td>
+		</tr>
+	"#,
+		percent = percent,
+		name = name,
+		code = code.unwrap_or(""),
+	)
+}
@@ -29,6 +29,13 @@ impl Service {
 		Ok(command.to_owned())
 	}
 
+	pub async fn is_installed(&'static self) -> R<bool> {
+		match self.get_exec() {
+			Some(command) => is_installed(command).await,
+			None => Ok(false),
+		}
+	}
+
 	fn fmt_supported_platforms(&self) -> String {
 		let mut platforms = Vec::new();
 		if self.supports_linux {
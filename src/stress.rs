@@ -2,7 +2,7 @@ pub mod manage;
 mod render;
 
 use crate::{
-	compile::{compile, Codegen}, executable::{Environment, Executable}, test::{judge::simple_test, Outcome, Task}, util::SourceTarget
+	compile::{compile, compile_solution, Codegen}, executable::{Environment, Executable}, test::{judge::simple_test, Outcome, Task}, util::SourceTarget
 };
 use evscode::{E, R};
 use futures::{Stream, StreamExt};
@@ -31,7 +31,7 @@ async fn open() -> R<()> {
 }
 
 pub async fn prepare_state() -> R<StressState> {
-	let solution = compile(&SourceTarget::Main, Codegen::Debug, false).await?;
+	let solution = compile_solution(&SourceTarget::Main, Codegen::Debug).await?;
 	let brute_force = compile(&SourceTarget::BruteForce, Codegen::Release, false).await?;
 	let test_generator = compile(&SourceTarget::TestGenerator, Codegen::Release, false).await?;
 	let task = Task::simple().await?;
@@ -1,9 +1,11 @@
+mod checks;
+
 use crate::{
-	dir, manifest::Manifest, net::{self, require_task, Session}, test, test::TestRun, util::{fs, retries::Retries, sleep, SourceTarget}
+	bundle, compile, dir, manifest::Manifest, net::{self, require_task, Session}, submit::checks::Check, test, test::TestRun, util::{retries::Retries, sleep, workspace_root, SourceTarget}
 };
 use evscode::{error::Severity, E, R};
 use log::debug;
-use std::time::Duration;
+use std::{future::Future, pin::Pin, time::Duration};
 use unijudge::{
 	boxed::{BoxedContest, BoxedTask}, Backend, Language, Resource, Submission
 };
@@ -16,7 +18,7 @@ const TRACK_NOT_SEEN_RETRY_DELAY: Duration = Duration::from_secs(5);
 async fn send() -> R<()> {
 	debug!("requesting submit");
 	let _status = crate::STATUS.push("Submitting");
-	let report = crate::test::view::manage::COLLECTION.get_force(SourceTarget::Main).await?.1;
+	let report = crate::test::view::manage::COLLECTION.get_force((workspace_root()?, SourceTarget::Main)).await?.1;
 	check_tests_passed(&report)?;
 	check_any_tests_ran(&report)?;
 	drop(_status);
@@ -43,16 +45,46 @@ fn check_any_tests_ran(report: &[TestRun]) -> R<()> {
 }
 
 async fn send_after_tests_passed() -> R<()> {
+	send_skipping_checks(Vec::new()).await
+}
+
+async fn send_skipping_checks(skipped: Vec<Check>) -> R<()> {
 	let _status = crate::STATUS.push("Submitting");
-	let code = fs::read_to_string(&dir::solution()?).await?;
+	let solution = dir::solution().await?;
+	let language = compile::Language::from_path(&solution)
+		.ok_or_else(|| E::error(format!("{} is not written in any of the supported languages", solution)))?;
+	let code = checks::transform(bundle::submitted_code(&solution, language).await?);
 	let (sess, task) = connect_to_workspace_task().await?;
-	let language = fetch_cpp_language(&task, &sess).await?;
+	if let Some(problem) = checks::find_problem(&code, language, sess.backend.source_limit, &skipped) {
+		debug!("submit aborted because of a failed {:?} check", problem.check);
+		let fixable = checks::is_fixable(problem.check).await?;
+		return Err(check_failed_error(problem, fixable, skipped));
+	}
+	let language = fetch_language(&task, &sess, language).await?;
 	let submit_id = sess.run(|backend, sess| backend.task_submit(sess, &task, &language, &code)).await?;
 	drop(_status);
 	track(&sess, &task, &submit_id).await?;
 	Ok(())
 }
 
+fn check_failed_error(problem: checks::Problem, fixable: bool, skipped: Vec<Check>) -> E {
+	let mut error = E::error(problem.message).context("submit aborted").severity(Severity::Workflow);
+	if let Some(fix_title) = problem.check.fix_title().filter(|_| fixable) {
+		let skipped = skipped.clone();
+		error = error.action(fix_title, async move {
+			checks::fix(problem.check).await?;
+			send_skipping_checks_boxed(skipped).await
+		});
+	}
+	let mut skipped = skipped;
+	skipped.push(problem.check);
+	error.action("Submit anyway", send_skipping_checks_boxed(skipped))
+}
+
+fn send_skipping_checks_boxed(skipped: Vec<Check>) -> Pin<Box<dyn Future<Output=R<()>>>> {
+	Box::pin(send_skipping_checks(skipped))
+}
+
 async fn connect_to_workspace_task() -> R<(Session, BoxedTask)> {
 	let manifest = Manifest::load().await?;
 	let url = manifest.req_task_url()?;
@@ -63,15 +95,15 @@ async fn connect_to_workspace_task() -> R<(Session, BoxedTask)> {
 	Ok((sess, task))
 }
 
-async fn fetch_cpp_language(task: &BoxedTask, sess: &Session) -> R<Language> {
+async fn fetch_language(task: &BoxedTask, sess: &Session, language: compile::Language) -> R<Language> {
 	let languages = fetch_languages(task, sess).await?;
 	debug!("found {} supported languages", languages.len());
-	let language = languages.iter().find(|lang| sess.backend.cpp.contains(&&*lang.name)).ok_or_else(|| {
-		E::error(format!("not found language {:?}", sess.backend.cpp))
-			.context("this task does not seem to allow C++ solutions")
+	let found = sess.backend.find_language(&languages, language).ok_or_else(|| {
+		E::error(format!("not found language {:?}", sess.backend.language_names(language)))
+			.context(format!("this task does not seem to allow {} solutions", language.name()))
 			.extended(format!("{:#?}", languages))
 	})?;
-	Ok(language.clone())
+	Ok(found.clone())
 }
 
 async fn fetch_languages(task: &BoxedTask, sess: &Session) -> R<Vec<Language>> {
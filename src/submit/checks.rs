@@ -0,0 +1,316 @@
+use crate::{
+	compile::Language, dir, util::fs
+};
+use evscode::R;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, evscode::Configurable)]
+pub enum Check {
+	#[evscode(name = "Leftover freopen")]
+	Freopen,
+	#[evscode(name = "Debug macros")]
+	DebugMacros,
+	#[evscode(name = "Output to cerr")]
+	Cerr,
+	#[evscode(name = "Source size limit")]
+	SourceSize,
+	#[evscode(name = "Missing main function")]
+	MissingMain,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, evscode::Configurable)]
+pub enum Transformation {
+	#[evscode(name = "Strip debug calls")]
+	StripDebugCalls,
+}
+
+/// Checks performed on the solution before submitting it. If one of them fails, an error will be displayed with
+/// actions to fix the problem or submit anyway. Code only compiled when one of the debug macros is defined, like the
+/// code inside #ifdef LOCAL, is not checked.
+#[evscode::config]
+static CHECKS: evscode::Config<Vec<Check>> =
+	vec![Check::Freopen, Check::DebugMacros, Check::Cerr, Check::SourceSize, Check::MissingMain];
+
+/// Transformations applied to the submitted code, without modifying the solution file. Stripping debug calls removes
+/// lines that only call one of the debug functions, like "dbg(x, y);".
+#[evscode::config]
+static TRANSFORMATIONS: evscode::Config<Vec<Transformation>> = Vec::new();
+
+/// Names of macros that enable debugging code, which should not be defined in submitted solutions.
+#[evscode::config]
+static DEBUG_MACROS: evscode::Config<Vec<String>> = vec!["LOCAL".to_owned(), "DEBUG".to_owned()];
+
+/// Names of functions and macros used for debugging, removed by the "Strip debug calls" transformation.
+#[evscode::config]
+static DEBUG_FUNCTIONS: evscode::Config<Vec<String>> = vec!["dbg".to_owned(), "debug".to_owned()];
+
+pub struct Problem {
+	pub check: Check,
+	pub message: String,
+}
+
+impl Check {
+	/// Returns the title of an action that fixes the problem automatically, if it can be done.
+	pub fn fix_title(self) -> Option<&'static str> {
+		match self {
+			Check::Freopen => Some("Remove freopen"),
+			Check::DebugMacros => Some("Remove #define"),
+			Check::Cerr => Some("Remove cerr"),
+			Check::SourceSize | Check::MissingMain => None,
+		}
+	}
+}
+
+pub fn transform(code: String) -> String {
+	let mut code = code;
+	for transformation in TRANSFORMATIONS.get() {
+		code = match transformation {
+			Transformation::StripDebugCalls => strip_debug_calls(&code, &DEBUG_FUNCTIONS.get()),
+		};
+	}
+	code
+}
+
+/// Returns the first problem found by the enabled checks, ignoring the skipped ones.
+pub fn find_problem(code: &str, language: Language, size_limit: usize, skipped: &[Check]) -> Option<Problem> {
+	let macros = DEBUG_MACROS.get();
+	CHECKS.get().into_iter().filter(|check| !skipped.contains(check)).find_map(|check| {
+		let message = run_check(check, code, language, size_limit, &macros)?;
+		Some(Problem { check, message })
+	})
+}
+
+/// Removes the lines that cause a given problem from the solution file.
+pub async fn fix(check: Check) -> R<()> {
+	let solution = dir::solution().await?;
+	let code = fs::read_to_string(&solution).await?;
+	fs::write(&solution, remove_offending_lines(&code, check, &DEBUG_MACROS.get())).await
+}
+
+/// Checks whether fixing the problem would change the solution file. The problem can also come from bundled headers,
+/// which are not edited, and then the fix action should not be offered.
+pub async fn is_fixable(check: Check) -> R<bool> {
+	if check.fix_title().is_none() {
+		return Ok(false);
+	}
+	let code = fs::read_to_string(&dir::solution().await?).await?;
+	let macros = DEBUG_MACROS.get();
+	Ok(lines_with_guards(&code, &macros).into_iter().any(|(line, guarded)| is_offending(line, guarded, check, &macros)))
+}
+
+fn run_check(check: Check, code: &str, language: Language, size_limit: usize, macros: &[String]) -> Option<String> {
+	let lines = lines_with_guards(code, macros);
+	let unguarded = || lines.iter().filter(|(_, guarded)| !guarded).map(|(line, _)| strip_line_comment(line));
+	match check {
+		Check::Freopen if language == Language::Cpp && unguarded().any(is_freopen) => {
+			Some("solution calls freopen, so it may not use the standard input and output".to_owned())
+		},
+		Check::DebugMacros if language == Language::Cpp => unguarded()
+			.find_map(|line| debug_macro(line, macros))
+			.map(|name| format!("solution defines the {} debugging macro", name)),
+		Check::Cerr if language == Language::Cpp => match unguarded().filter(|line| is_cerr_statement(line)).count() {
+			0 => None,
+			count => Some(format!("solution writes to cerr in {} places, which can exceed the time limit", count)),
+		},
+		Check::SourceSize if code.len() > size_limit => {
+			Some(format!("solution has {} bytes, more than the limit of {} bytes", code.len(), size_limit))
+		},
+		Check::MissingMain if !has_main(code, language) => Some("solution does not have a main function".to_owned()),
+		_ => None,
+	}
+}
+
+fn remove_offending_lines(code: &str, check: Check, macros: &[String]) -> String {
+	let mut fixed = String::new();
+	for (line, guarded) in lines_with_guards(code, macros) {
+		if !is_offending(line, guarded, check, macros) {
+			fixed += line;
+			fixed += "\n";
+		}
+	}
+	fixed
+}
+
+fn is_offending(line: &str, guarded: bool, check: Check, macros: &[String]) -> bool {
+	let content = strip_line_comment(line);
+	!guarded
+		&& match check {
+			Check::Freopen => is_freopen(content),
+			Check::DebugMacros => debug_macro(content, macros).is_some(),
+			Check::Cerr => is_cerr_statement(content),
+			Check::SourceSize | Check::MissingMain => false,
+		}
+}
+
+fn strip_debug_calls(code: &str, functions: &[String]) -> String {
+	let mut stripped = String::new();
+	for line in code.lines() {
+		if !debug_call(line).map_or(false, |name| functions.iter().any(|function| function == name)) {
+			stripped += line;
+			stripped += "\n";
+		}
+	}
+	stripped
+}
+
+/// Splits the code into lines, marking the ones that are only compiled when one of the debug macros is defined.
+/// Solutions often guard debugging code with #ifdef LOCAL or similar, and such code is fine to submit. Other branches,
+/// like the #else of #ifdef LOCAL or the contents of include guards, are submitted and have to be checked.
+fn lines_with_guards<'a>(code: &'a str, macros: &[String]) -> Vec<(&'a str, bool)> {
+	static DIRECTIVE: Lazy<Regex> = Lazy::new(|| Regex::new("^\\s*#\\s*(\\w+)\\s*(.*)").unwrap());
+	// Every open #if block stores whether its current branch is guarded and whether its #else branch would be.
+	let mut blocks: Vec<(bool, bool)> = Vec::new();
+	code.lines()
+		.map(|line| {
+			let guarded = blocks.iter().any(|(guarded, _)| *guarded);
+			if let Some(cap) = DIRECTIVE.captures(line) {
+				let debug = debug_condition(strip_line_comment(&cap[2]), macros);
+				match &cap[1] {
+					"if" => blocks.push((debug == Some(true), debug == Some(false))),
+					"ifdef" => blocks.push((debug.is_some(), false)),
+					"ifndef" => blocks.push((false, debug.is_some())),
+					"elif" => {
+						if let Some(block) = blocks.last_mut() {
+							*block = (debug == Some(true), false);
+						}
+					},
+					"else" => {
+						if let Some(block) = blocks.last_mut() {
+							*block = (block.1, false);
+						}
+					},
+					"endif" => {
+						blocks.pop();
+					},
+					_ => (),
+				}
+			}
+			(line, guarded)
+		})
+		.collect()
+}
+
+/// Checks whether the #if condition only tests one of the debug macros. Returns true if the branch is compiled when the
+/// macro is defined, false if it is compiled when it is not, and nothing if the condition is about something else.
+fn debug_condition(condition: &str, macros: &[String]) -> Option<bool> {
+	static CONDITION: Lazy<Regex> =
+		Lazy::new(|| Regex::new("^\\s*(!?)\\s*(?:defined\\s*\\(?\\s*)?(\\w+)\\s*\\)?\\s*$").unwrap());
+	let cap = CONDITION.captures(condition)?;
+	if macros.iter().any(|debug_macro| debug_macro == &cap[2]) { Some(cap[1].is_empty()) } else { None }
+}
+
+fn strip_line_comment(line: &str) -> &str {
+	line.find("//").map_or(line, |comment| &line[..comment])
+}
+
+fn is_freopen(line: &str) -> bool {
+	static FREOPEN: Lazy<Regex> = Lazy::new(|| Regex::new("\\bfreopen\\s*\\(").unwrap());
+	FREOPEN.is_match(line)
+}
+
+fn is_cerr_statement(line: &str) -> bool {
+	static CERR_STATEMENT: Lazy<Regex> = Lazy::new(|| Regex::new("^\\s*(std::)?cerr\\b.*;\\s*$").unwrap());
+	CERR_STATEMENT.is_match(line)
+}
+
+fn debug_macro<'a>(line: &'a str, macros: &[String]) -> Option<&'a str> {
+	static DEFINE: Lazy<Regex> = Lazy::new(|| Regex::new("^\\s*#\\s*define\\s+(\\w+)").unwrap());
+	let name = DEFINE.captures(line)?.get(1)?.as_str();
+	if macros.iter().any(|debug_macro| debug_macro == name) { Some(name) } else { None }
+}
+
+/// Returns the name of the function if the line consists of a single call, optionally followed by a semicolon.
+fn debug_call(line: &str) -> Option<&str> {
+	static CALL: Lazy<Regex> = Lazy::new(|| Regex::new("^\\s*(\\w+)\\s*\\(").unwrap());
+	let cap = CALL.captures(line)?;
+	let rest = &line[cap.get(0)?.end()..];
+	let mut depth = 1;
+	let mut quote = None;
+	let mut chars = rest.char_indices();
+	let end = loop {
+		let (i, c) = chars.next()?;
+		match (quote, c) {
+			(Some(_), '\\') => {
+				chars.next();
+			},
+			(Some(q), c) if c == q => quote = None,
+			(Some(_), _) => (),
+			(None, '"') | (None, '\'') => quote = Some(c),
+			(None, '(') => depth += 1,
+			(None, ')') => {
+				depth -= 1;
+				if depth == 0 {
+					break i + 1;
+				}
+			},
+			(None, _) => (),
+		}
+	};
+	let after = rest[end..].trim_start();
+	let after = after.strip_prefix(';').map_or(after, str::trim_start);
+	if after.is_empty() || after.starts_with("//") { Some(cap.get(1)?.as_str()) } else { None }
+}
+
+fn has_main(code: &str, language: Language) -> bool {
+	static MAIN: Lazy<Regex> = Lazy::new(|| Regex::new("\\bmain\\s*\\(").unwrap());
+	static RUST_MAIN: Lazy<Regex> = Lazy::new(|| Regex::new("\\bfn\\s+main\\b").unwrap());
+	match language {
+		Language::Cpp | Language::Java | Language::Kotlin => MAIN.is_match(code),
+		Language::Rust => RUST_MAIN.is_match(code),
+		Language::Python => true,
+	}
+}
+
+#[test]
+fn test_checks() {
+	let macros = ["LOCAL".to_owned()];
+	let code = "#define LOCAL\nint main() {\n#ifdef LOCAL\n\tfreopen(\"in\", \"r\", stdin);\n#endif\n\tcerr << 1;\n}\n";
+	let check = |check| run_check(check, code, Language::Cpp, 100, &macros);
+	assert_eq!(check(Check::Freopen), None);
+	assert_eq!(check(Check::DebugMacros).as_deref(), Some("solution defines the LOCAL debugging macro"));
+	assert!(check(Check::Cerr).is_some());
+	assert!(run_check(Check::Cerr, "int main() {\n\tif (x) cerr << x;\n}\n", Language::Cpp, 100, &macros).is_none());
+	assert!(check(Check::SourceSize).is_none());
+	assert!(run_check(Check::SourceSize, code, Language::Cpp, 10, &macros).is_some());
+	assert!(run_check(Check::MissingMain, "int solve() {}", Language::Cpp, 100, &macros).is_some());
+	let fixed = remove_offending_lines(code, Check::Cerr, &macros);
+	assert_eq!(fixed, "#define LOCAL\nint main() {\n#ifdef LOCAL\n\tfreopen(\"in\", \"r\", stdin);\n#endif\n}\n");
+}
+
+#[test]
+fn test_strip_debug_calls() {
+	let functions = ["dbg".to_owned()];
+	let code = "#define dbg(...) 0\nint main() {\n\tdbg(x, y);\n\tsolve(x);\n}\n";
+	assert_eq!(strip_debug_calls(code, &functions), "#define dbg(...) 0\nint main() {\n\tsolve(x);\n}\n");
+}
+
+#[test]
+fn test_lines_with_guards() {
+	let macros = ["LOCAL".to_owned()];
+	let guarded = |code: &str| {
+		lines_with_guards(code, &macros)
+			.into_iter()
+			.filter(|(_, guarded)| *guarded)
+			.map(|(line, _)| line.trim().to_owned())
+			.collect::<Vec<_>>()
+	};
+	assert_eq!(guarded("#ifdef LOCAL\na\n#else\nb\n#endif\nc\n"), ["a", "#else"]);
+	assert_eq!(guarded("#ifndef LOCAL\na\n#else\nb\n#endif\n"), ["b", "#endif"]);
+	assert_eq!(guarded("#if defined(LOCAL)\na\n#elif X\nb\n#endif\n"), ["a", "#elif X"]);
+	assert_eq!(guarded("#if !LOCAL\na\n#else\nb\n#endif\n"), ["b", "#endif"]);
+	assert!(guarded("#ifndef LIB_H\n#define LIB_H\ncerr << 1;\n#endif\n").is_empty());
+	assert_eq!(guarded("#ifdef LOCAL\n#ifndef X\na\n#endif\n#endif\nb\n"), ["#ifndef X", "a", "#endif", "#endif"]);
+	assert!(guarded("#if X\n#ifdef LOCAL\n#endif\n#else\nb\n#endif\n").iter().all(|line| line != "b"));
+}
+
+#[test]
+fn test_debug_call() {
+	assert_eq!(debug_call("\tdbg(x, y);"), Some("dbg"));
+	assert_eq!(debug_call("debug(f(x), \")\");  // why"), Some("debug"));
+	assert_eq!(debug_call("dbg(x)"), Some("dbg"));
+	assert_eq!(debug_call("debug(a) || f();"), None);
+	assert_eq!(debug_call("dbg(x), y = 2;"), None);
+	assert_eq!(debug_call("dbg(x); y = 2;"), None);
+	assert_eq!(debug_call("dbg(x"), None);
+}
@@ -1,9 +1,10 @@
 use crate::{
-	dir, util, util::{expand_path, fs, path::Path, workspace_root, OS}
+	compile::Language, dir, util, util::{expand_path, fs, path::Path, workspace_root, OS}
 };
 use evscode::{E, R};
 use log::debug;
 use std::collections::HashMap;
+use unijudge::{chrono::Local, TaskDetails};
 
 const ID_BRUTE_FORCE: &str = "C++ Brute force";
 const ID_TEST_GENERATOR: &str = "C++ Test generator";
@@ -12,10 +13,22 @@ const ID_CHECKER: &str = "C++ Checker";
 /// Path to your C++ template file. Set this to a value like `/home/jonsmith/template.cpp` or
 /// `C:\Users\JohnSmith\template.cpp`. When opening a new task or contest, the contents of this file
 /// will be copy-pasted into main.cpp. Be sure to leave an empty line in the main function, because
-/// ICIE will then place the cursor there!
+/// ICIE will then place the cursor there, or mark the place with {{cursor}}!
 #[evscode::config]
 static SOLUTION: evscode::Config<String> = "".to_owned();
 
+/// Paths to solution template files used instead of the one above for tasks from a given site. The keys are site names,
+/// like "codeforces", "atcoder", "codechef", "sio2" or "spoj". For example, a template with `int t; cin >> t;` can be
+/// used only for Codeforces, where most tasks have multiple test cases.
+#[evscode::config]
+static SOLUTION_PER_SITE: evscode::Config<HashMap<String, String>> = HashMap::new();
+
+/// Your name, which replaces the {{author}} placeholder in templates. Templates can also contain {{task.title}},
+/// {{task.url}}, {{task.id}}, {{contest}}, {{site}} and {{date}}, and the {{cursor}} placeholder marks where the cursor
+/// will be placed after opening the file.
+#[evscode::config]
+static AUTHOR: evscode::Config<String> = "".to_owned();
+
 /// Paths to additional C++ template files. A list of these will appear when you press Alt+=; if you
 /// want to change the basic C++ template file, see the ICIE.Template.Solution configuration option
 /// instead. If you see "Edit in settings.json", click it, then add a new entry starting with
@@ -69,8 +82,71 @@ async fn enter_filename(suggested: &str) -> R<String> {
 }
 
 pub async fn write(path: &Path, template: &LoadedTemplate) -> R<()> {
-	fs::write(path, &template.code).await?;
-	util::open_source(path).await
+	let (code, cursor) = fill(&template.code, &Variables::new(None));
+	fs::write(path, &code).await?;
+	util::open_source_at(path, cursor).await
+}
+
+/// Values of the placeholders in templates, like {{task.title}}.
+pub struct Variables<'a> {
+	task: Option<&'a TaskDetails>,
+	date: String,
+	author: String,
+}
+
+impl Variables<'_> {
+	pub fn new(task: Option<&TaskDetails>) -> Variables {
+		Variables { task, date: Local::now().format("%Y-%m-%d").to_string(), author: AUTHOR.get() }
+	}
+
+	fn get(&self, name: &str) -> Option<&str> {
+		let task = |field: fn(&TaskDetails) -> &String| self.task.map_or("", |task| field(task).as_str());
+		Some(match name {
+			"task.title" => task(|task| &task.title),
+			"task.url" => task(|task| &task.url),
+			"task.id" => task(|task| &task.id),
+			"contest" => task(|task| &task.contest_id),
+			"site" => task(|task| &task.site_short),
+			"date" => &self.date,
+			"author" => &self.author,
+			_ => return None,
+		})
+	}
+}
+
+/// Replaces the placeholders in a template and removes the {{cursor}} placeholder, returning its' position as a line
+/// and a column. Unknown placeholders are left as they are, because C++ code like `{{1, 2}}` looks similar.
+pub fn fill(template: &str, variables: &Variables) -> (String, Option<(usize, usize)>) {
+	let mut code = String::new();
+	let mut cursor = None;
+	let mut rest = template;
+	while let Some(start) = rest.find("{{") {
+		code += &rest[..start];
+		rest = &rest[start..];
+		let placeholder = match rest.find("}}") {
+			Some(end) => &rest[..end + 2],
+			None => break,
+		};
+		match placeholder[2..placeholder.len() - 2].trim() {
+			"cursor" => {
+				if cursor.is_none() {
+					let line_start = code.rfind('\n').map_or(0, |i| i + 1);
+					cursor = Some((code.matches('\n').count(), code[line_start..].chars().count()));
+				}
+			},
+			name => match variables.get(name) {
+				Some(value) => code += value,
+				None => {
+					code += "{{";
+					rest = &rest[2..];
+					continue;
+				},
+			},
+		}
+		rest = &rest[placeholder.len()..];
+	}
+	code += rest;
+	(code, cursor)
 }
 
 #[evscode::command(title = "ICIE Template configure")]
@@ -90,22 +166,34 @@ const PSEUDOPATH_BRUTE_FORCE: &str = "(replace this with a path to your brute fo
 const PSEUDOPATH_TEST_GENERATOR: &str = "(replace this with a path to your test generator template)";
 const PSEUDOPATH_CHECKER: &str = "(replace this with a path to your checker template)";
 
-pub async fn load_solution() -> R<LoadedTemplate> {
-	let template = match SOLUTION.get() {
-		raw_path if !raw_path.is_empty() => {
+/// Loads the solution template for tasks from a given site, or the one for all sites if there is none.
+pub async fn load_solution(site: Option<&str>) -> R<LoadedTemplate> {
+	let language = dir::LANGUAGE.get();
+	let site_template = site.and_then(|site| SOLUTION_PER_SITE.get().get(site).cloned());
+	let template = match site_template.unwrap_or_else(|| SOLUTION.get()) {
+		raw_path if !raw_path.is_empty() && is_template_in(&raw_path, language) => {
 			debug!("found solution path, unexpanded = {:?}", raw_path);
 			let path = expand_path(&raw_path);
 			debug!("found solution path, {:?}", path);
 			load_additional(&path).await.map_err(|e| e.action("Configure C++ template", configure()))?
 		},
 		_ => LoadedTemplate {
-			suggested_filename: format!("{}.{}", dir::SOLUTION_STEM.get(), dir::CPP_EXTENSION.get()),
-			code: default_solution()?,
+			suggested_filename: format!("{}.{}", dir::SOLUTION_STEM.get(), language.extension()),
+			code: default_solution_in(language)?,
 		},
 	};
 	Ok(template)
 }
 
+/// Checks whether a custom template can be used for the given language. Templates are assumed to be written in C++,
+/// unless their extension says otherwise.
+fn is_template_in(raw_path: &str, language: Language) -> bool {
+	match Language::from_path(&expand_path(raw_path)) {
+		Some(template_language) => template_language == language,
+		None => language == Language::Cpp,
+	}
+}
+
 pub async fn load_brute_force() -> R<LoadedTemplate> {
 	load_by_id_or(ID_BRUTE_FORCE, PSEUDOPATH_BRUTE_FORCE).await
 }
@@ -124,7 +212,7 @@ async fn load_by_id_or(id: &str, default_path: &str) -> R<LoadedTemplate> {
 pub async fn load_additional(path: &str) -> R<LoadedTemplate> {
 	let suggested_filename = additional_suggested_filename(path);
 	let template = if path == PSEUDOPATH_BRUTE_FORCE {
-		LoadedTemplate { suggested_filename, code: default_brute_force()? }
+		LoadedTemplate { suggested_filename, code: default_brute_force_in(dir::LANGUAGE.get())? }
 	} else if path == PSEUDOPATH_TEST_GENERATOR {
 		LoadedTemplate { suggested_filename, code: default_test_generator()? }
 	} else if path == PSEUDOPATH_CHECKER {
@@ -139,7 +227,7 @@ pub async fn load_additional(path: &str) -> R<LoadedTemplate> {
 
 fn additional_suggested_filename(path: &str) -> String {
 	if path == PSEUDOPATH_BRUTE_FORCE {
-		format!("{}.{}", dir::BRUTE_FORCE_STEM.get(), dir::CPP_EXTENSION.get())
+		format!("{}.{}", dir::BRUTE_FORCE_STEM.get(), dir::LANGUAGE.get().extension())
 	} else if path == PSEUDOPATH_TEST_GENERATOR {
 		format!("{}.{}", dir::TEST_GENERATOR_STEM.get(), dir::CPP_EXTENSION.get())
 	} else if path == PSEUDOPATH_CHECKER {
@@ -161,6 +249,23 @@ pub fn default_solution() -> R<String> {
 	)
 }
 
+pub fn default_solution_in(language: Language) -> R<String> {
+	match language {
+		Language::Cpp => default_solution(),
+		_ => Ok(generate_in(language, "💖 Hi, thanks for using ICIE! 💖")),
+	}
+}
+
+fn default_brute_force_in(language: Language) -> R<String> {
+	match language {
+		Language::Cpp => default_brute_force(),
+		_ => {
+			let comment = "💻 Here, write a slow but simple solution, used to generate correct outputs in stress tests. 💻";
+			Ok(generate_in(language, comment))
+		},
+	}
+}
+
 fn default_brute_force() -> R<String> {
 	generate(
 		r#"// 💻 Here in brute-force.cpp, write a straighforward, brute-force solution that will be used to generate correct test outputs from inputs. 💻
@@ -173,7 +278,12 @@ fn default_brute_force() -> R<String> {
 }
 
 fn default_test_generator() -> R<String> {
-	generate(
+	Ok(default_test_generator_on(OS::query()?))
+}
+
+pub fn default_test_generator_on(os: OS) -> String {
+	generate_on(
+		os,
 		r#"minstd_rand rng(chrono::high_resolution_clock::now().time_since_epoch().count());
 template <typename T> T randint(T a, T b) { return uniform_int_distribution<T>(a, b)(rng); }
 template <typename T> T uniform(T a, T b) { return uniform_real_distribution<T>(a, b)(rng); }
@@ -204,15 +314,19 @@ fn default_checker() -> R<String> {
 }
 
 fn generate(prelude: &str, main_args: bool, main_prelude: &str) -> R<String> {
+	Ok(generate_on(OS::query()?, prelude, main_args, main_prelude))
+}
+
+fn generate_on(os: OS, prelude: &str, main_args: bool, main_prelude: &str) -> String {
 	// TODO: Does bits/stdc++.h work on macOS? I heard it doesn't.
-	let includes = match OS::query()? {
+	let includes = match os {
 		OS::Linux => "#include <bits/stdc++.h>",
 		OS::Windows | OS::MacOS => {
 			"#include <iostream>\n#include <vector>\n#include <algorithm>\n#include <random>\n#include <chrono>"
 		},
 	};
 	let main_args = if main_args { "int argc, char* argv[]" } else { "" };
-	Ok(format!(
+	format!(
 		r#"{}
 using namespace std;
 
@@ -224,5 +338,102 @@ int main({}) {{
 }}
 "#,
 		includes, prelude, main_args, main_prelude,
-	))
+	)
+}
+
+/// Generates a template for languages other than C++, with a comment on top and an empty line in main, where ICIE will
+/// place the cursor.
+fn generate_in(language: Language, comment: &str) -> String {
+	match language {
+		Language::Cpp => unreachable!(),
+		Language::Python => format!(
+			r#"# {}
+import sys
+input = sys.stdin.readline
+
+
+def main():
+    
+    pass
+
+
+main()
+"#,
+			comment
+		),
+		Language::Rust => format!(
+			r#"// {}
+use std::io::{{self, BufWriter, Read, Write}};
+
+fn main() {{
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input).unwrap();
+    let mut input = input.split_ascii_whitespace();
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    
+}}
+"#,
+			comment
+		),
+		Language::Java => format!(
+			r#"// {}
+import java.io.*;
+import java.util.*;
+
+class Main {{
+    public static void main(String[] args) throws IOException {{
+        BufferedReader in = new BufferedReader(new InputStreamReader(System.in));
+        PrintWriter out = new PrintWriter(new BufferedWriter(new OutputStreamWriter(System.out)));
+        
+        out.flush();
+    }}
+}}
+"#,
+			comment
+		),
+		Language::Kotlin => format!(
+			r#"// {}
+import java.io.*
+import java.util.*
+
+fun main() {{
+    val input = BufferedReader(InputStreamReader(System.`in`))
+    
+}}
+"#,
+			comment
+		),
+	}
+}
+
+#[test]
+fn test_fill() {
+	let task = TaskDetails {
+		id: "A".to_owned(),
+		title: "Watermelon".to_owned(),
+		contest_id: "4".to_owned(),
+		site_short: "codeforces".to_owned(),
+		examples: None,
+		statement: None,
+		url: "https://codeforces.com/contest/4/problem/A".to_owned(),
+	};
+	let variables = Variables { task: Some(&task), date: "2020-01-01".to_owned(), author: "Jon Smith".to_owned() };
+	let template = r#"// {{task.id}}. {{ task.title }} ({{site}} {{contest}})
+// {{task.url}}, {{date}}, {{author}}
+int main() {
+	vector<vector<int>> v{{1, 2}};
+	{{cursor}}
+}
+"#;
+	let code = r#"// A. Watermelon (codeforces 4)
+// https://codeforces.com/contest/4/problem/A, 2020-01-01, Jon Smith
+int main() {
+	vector<vector<int>> v{{1, 2}};
+	
+}
+"#;
+	assert_eq!(fill(template, &variables), (code.to_owned(), Some((4, 1))));
+	let variables = Variables { task: None, date: String::new(), author: String::new() };
+	assert_eq!(fill("{{task.title}}{{unknown}} {{", &variables), ("{{unknown}} {{".to_owned(), None));
 }
@@ -51,6 +51,6 @@ fn get_unobtrusive() -> impl Terminal {
 	Internal
 }
 
-fn bash_escape_command(command: &[&str]) -> String {
+pub fn bash_escape_command(command: &[&str]) -> String {
 	command.iter().copied().map(util::bash_escape).collect::<Vec<_>>().join(" ")
 }
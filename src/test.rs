@@ -1,5 +1,6 @@
 mod data;
 pub mod judge;
+mod report;
 pub mod scan;
 pub mod view;
 
@@ -7,6 +8,7 @@ use crate::{
 	compile::{self, Codegen}, dir, test::{judge::simple_test, scan::scan_for_tests}, util, util::{fs, path::Path, SourceTarget}
 };
 use evscode::R;
+use icie_common::test::mex;
 use std::time::Duration;
 
 pub use data::{Outcome, Task, TestRun, Verdict};
@@ -19,7 +21,7 @@ static TIME_LIMIT: evscode::Config<Option<u64>> = Some(1500);
 
 pub async fn run(source: SourceTarget) -> R<Vec<TestRun>> {
 	let _status = crate::STATUS.push("Testing");
-	let solution = compile::compile(&source, Codegen::Debug, false).await?;
+	let solution = compile::compile_solution(&source, Codegen::Debug).await?;
 	let task = Task::simple().await?;
 	let inputs = scan_for_tests(&dir::TESTS_DIRECTORY.get()).await;
 	let progress = evscode::Progress::new().title(util::fmt::verb_on_source("Testing", &source)).show().0;
@@ -57,19 +59,35 @@ fn update_test_progress(run: &TestRun, count: usize, progress: &evscode::Progres
 
 #[evscode::command(title = "ICIE Open Test View", key = "alt+0")]
 pub async fn view() -> R<()> {
-	view::manage::COLLECTION.get_force(SourceTarget::Main).await?;
+	view::manage::COLLECTION.get_force((util::workspace_root()?, SourceTarget::Main)).await?;
 	Ok(())
 }
 
 #[evscode::command(title = "ICIE Open Test View (current editor)", key = "alt+\\ alt+0")]
 async fn view_current() -> R<()> {
-	view::manage::COLLECTION.get_force(util::active_tab().await?).await?;
+	view::manage::COLLECTION.get_force((util::workspace_root()?, util::active_tab().await?)).await?;
+	Ok(())
+}
+
+#[evscode::command(title = "ICIE Test Report")]
+async fn report() -> R<()> {
+	let runs = run(SourceTarget::Main).await?;
+	let written = report::write(&runs).await?;
+	let passed = runs.iter().filter(|run| run.success()).count();
+	let files = written.iter().map(|path| path.fmt_workspace()).collect::<Vec<_>>();
+	let files = files.iter().map(String::as_str).collect::<Vec<_>>();
+	let message = format!("{}/{} tests passed, report written to {}", passed, runs.len(), util::fmt::list(&files));
+	evscode::Message::new::<()>(&message).show().await;
 	Ok(())
 }
 
 #[evscode::command(title = "ICIE New Test", key = "alt+-")]
 pub async fn input() -> evscode::R<()> {
-	let webview = view::manage::COLLECTION.active_or_lazy(SourceTarget::Main).await?;
+	// The task is only needed if no test view is active, which is the case when the focus is in the test view itself.
+	let webview = match view::manage::COLLECTION.find_active().await {
+		Some(webview) => webview,
+		None => view::manage::COLLECTION.get_lazy((util::workspace_root()?, SourceTarget::Main)).await?,
+	};
 	// FIXME: JS .focus() does not work despite the reveal.
 	webview.reveal(2, false);
 	webview.post_message(view::manage::Food::NewStart).await?;
@@ -91,7 +109,7 @@ pub async fn add_test(input: &str, desired: &str) -> R<()> {
 async fn unused_test_id(dir: &Path) -> R<i64> {
 	let tests = fs::read_dir(dir).await?;
 	let taken = tests.into_iter().filter_map(|test| test.file_stem().parse().ok()).collect();
-	Ok(util::mex(1, taken))
+	Ok(mex(1, taken))
 }
 
 pub fn time_limit() -> Option<Duration> {
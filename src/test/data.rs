@@ -2,7 +2,9 @@ use crate::{
 	checker::{get_checker, Checker}, executable::Environment, test::time_limit, util::path::Path
 };
 use evscode::R;
-use std::{fmt, time::Duration};
+use std::time::Duration;
+
+pub use icie_common::test::Verdict;
 
 #[derive(Debug)]
 pub struct Outcome {
@@ -25,15 +27,6 @@ pub struct TestRun {
 	pub outcome: Outcome,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum Verdict {
-	Accepted { alternative: bool },
-	WrongAnswer,
-	RuntimeError,
-	TimeLimitExceeded,
-	IgnoredNoOut,
-}
-
 impl Outcome {
 	pub fn success(&self) -> bool {
 		self.verdict.success()
@@ -53,22 +46,3 @@ impl TestRun {
 		self.outcome.success()
 	}
 }
-
-impl Verdict {
-	pub fn success(self) -> bool {
-		matches!(self, Verdict::Accepted { .. })
-	}
-}
-
-impl fmt::Display for Verdict {
-	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		let message = match self {
-			Verdict::Accepted { .. } => "Accept",
-			Verdict::WrongAnswer => "Wrong Answer",
-			Verdict::RuntimeError => "Runtime Error",
-			Verdict::TimeLimitExceeded => "Time Limit Exceeded",
-			Verdict::IgnoredNoOut => "Ignored (no output file)",
-		};
-		write!(f, "{}", message)
-	}
-}
@@ -0,0 +1,45 @@
+use crate::{
+	dir, test::TestRun, util::{fs, path::Path, workspace_root}
+};
+use evscode::R;
+use icie_common::report::{Report, ReportTest};
+
+/// File name of the test report written by ICIE Test Report, relative to the task directory. The JUnit XML report, if
+/// enabled, is written next to it with the extension changed to .xml. For example, if this is set to
+/// "test-report.json", the reports will be called "test-report.json" and "test-report.xml".
+#[evscode::config]
+static FILE: evscode::Config<String> = "test-report.json";
+
+/// Whether ICIE Test Report should also write a JUnit XML report, which can be consumed by most CI systems.
+#[evscode::config]
+static JUNIT: evscode::Config<bool> = false;
+
+/// The maximum number of lines of the output and standard error included in the test report for every test.
+#[evscode::config]
+static EXCERPT_LINES: evscode::Config<u64> = 20u64;
+
+/// Writes the JSON report and, if enabled, the JUnit XML report. Returns the paths of the written files.
+pub async fn write(runs: &[TestRun]) -> R<Vec<Path>> {
+	let json_path = workspace_root()?.join(&*FILE.get());
+	let report = build(runs, &dir::tests()?, EXCERPT_LINES.get() as usize);
+	fs::write(&json_path, report.json()).await?;
+	let mut written = vec![json_path.clone()];
+	if JUNIT.get() {
+		let xml_path = json_path.with_extension("xml");
+		fs::write(&xml_path, report.junit()).await?;
+		written.push(xml_path);
+	}
+	Ok(written)
+}
+
+fn build(runs: &[TestRun], tests: &Path, excerpt_lines: usize) -> Report {
+	Report::new(
+		runs.iter()
+			.map(|run| {
+				let outcome = &run.outcome;
+				let path = run.in_path.fmt_relative(tests);
+				ReportTest::new(path, outcome.verdict, outcome.time, &outcome.out, &outcome.stderr, excerpt_lines)
+			})
+			.collect(),
+	)
+}
@@ -1,13 +1,14 @@
 use crate::{
-	compile::{compile, Codegen}, debug::{gdb, rr}, dir, executable::Environment, test::{
+	compile::{compile, Codegen}, debug::{address_sanitizer, gdb, rr, valgrind}, dir, executable::Environment, profile::profile, test::{
 		add_test, run, time_limit, view::{render::render, SCROLL_TO_FIRST_FAILED, SKILL_ACTIONS, SKILL_ADD}, TestRun
-	}, util::{self, fs, path::Path, SourceTarget}
+	}, util::{self, fs, in_task, path::Path, SourceTarget}
 };
 use async_trait::async_trait;
 use evscode::{
 	error::cancel_on, goodies::webview_collection::{Behaviour, Collection}, stdlib::webview::{Disposer, Listener}, webview::{WebviewMeta, WebviewRef}, Webview, E, R
 };
 use futures::StreamExt;
+use std::future::Future;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
@@ -15,22 +16,25 @@ pub static COLLECTION: Lazy<Collection<TestView>> = Lazy::new(|| Collection::new
 
 pub struct TestView;
 
+/// Test views are kept separately for every task directory, and everything started from a view happens in its task,
+/// even if the active editor belongs to another one.
 #[async_trait(?Send)]
 impl Behaviour for TestView {
-	type K = SourceTarget;
+	type K = (Path, SourceTarget);
 	type V = Vec<TestRun>;
 
-	fn create_empty(&self, source: Self::K) -> R<WebviewMeta> {
+	fn create_empty(&self, (_, source): Self::K) -> R<WebviewMeta> {
 		let title = util::fmt::verb_on_source("ICIE Test View", &source);
 		Ok(Webview::new("icie.test.view", &title, 2).enable_scripts().retain_context_when_hidden().create())
 	}
 
-	async fn compute(&self, source: Self::K) -> R<Self::V> {
-		run(source.clone()).await
+	async fn compute(&self, key: Self::K) -> R<Self::V> {
+		let (root, source) = key;
+		in_task(root, run(source)).await
 	}
 
-	async fn update(&self, _: Self::K, report: &Self::V, webview: WebviewRef) -> R<()> {
-		webview.set_html(&render(report).await?);
+	async fn update(&self, key: Self::K, report: &Self::V, webview: WebviewRef) -> R<()> {
+		webview.set_html(&in_task(key.0, render(report)).await?);
 		webview.reveal(2, true);
 		if SCROLL_TO_FIRST_FAILED.get() {
 			let _ = webview.post_message(Food::ScrollToWA).await;
@@ -38,69 +42,98 @@ impl Behaviour for TestView {
 		Ok(())
 	}
 
-	async fn manage(&self, source: Self::K, webview: WebviewRef, listener: Listener, disposer: Disposer) -> R<()> {
-		let mut stream = cancel_on(listener, disposer);
-		while let Some(note) = stream.next().await {
-			let note: Note = note?.into_serde().unwrap();
-			match note {
-				Note::TriggerRR { in_path } => {
-					let source = source.clone();
-					evscode::spawn(async move { rr(&in_path, source).await });
-				},
-				Note::TriggerGDB { in_path } => {
-					let source = source.clone();
-					evscode::spawn(async move { gdb(&in_path, source).await });
-				},
-				Note::NewTest { input, desired } => evscode::spawn(async move {
-					if !input.is_empty() && !desired.is_empty() {
-						SKILL_ADD.add_use().await;
-					}
-					add_test(&input, &desired).await
-				}),
-				Note::SetAlt { in_path, out } => evscode::spawn(async move {
-					let in_alt_path = in_path.with_extension("alt.out");
-					fs::write(&in_alt_path, out).await?;
-					COLLECTION.update_all().await?;
-					Ok(())
-				}),
-				Note::DelAlt { in_path } => evscode::spawn(async move {
-					let in_alt_path = in_path.with_extension("alt.out");
-					fs::remove_file(&in_alt_path).await?;
-					COLLECTION.update_all().await?;
-					Ok(())
-				}),
-				Note::Edit { path } => {
-					if !fs::exists(&path).await? {
-						fs::write(&path, "").await?;
-					}
-					util::open_source(&path).await?;
-				},
-				Note::ActionNotice => SKILL_ACTIONS.add_use().await,
-				Note::EvalReq { id, input } => {
-					if let Ok(brute_force) = dir::brute_force() {
-						if fs::exists(&brute_force).await? {
-							let webview = webview.clone();
-							evscode::spawn(async move {
-								let _status = crate::STATUS.push("Evaluating");
-								let brute_force = compile(&SourceTarget::BruteForce, Codegen::Release, false).await?;
-								let environment = Environment { time_limit: time_limit(), cwd: None };
-								let run = brute_force.run(&input, &[], &environment).await?;
-								drop(_status);
-								if run.success() {
-									add_test(&input, &run.stdout).await?;
-									let _ = webview.post_message(Food::EvalResp { id, input }).await;
-									Ok(())
-								} else {
-									Err(E::error("brute force solution did not evaluate test successfully"))
-								}
-							});
-						}
+	async fn manage(&self, key: Self::K, webview: WebviewRef, listener: Listener, disposer: Disposer) -> R<()> {
+		let (root, source) = key;
+		in_task(root.clone(), manage(root, source, webview, listener, disposer)).await
+	}
+}
+
+async fn manage(
+	root: Path,
+	source: SourceTarget,
+	webview: WebviewRef,
+	listener: Listener,
+	disposer: Disposer,
+) -> R<()> {
+	let mut stream = cancel_on(listener, disposer);
+	while let Some(note) = stream.next().await {
+		let note: Note = note?.into_serde().unwrap();
+		match note {
+			Note::TriggerRR { in_path } => {
+				let source = source.clone();
+				spawn_in(&root, async move { rr(&in_path, source).await });
+			},
+			Note::TriggerGDB { in_path } => {
+				let source = source.clone();
+				spawn_in(&root, async move { gdb(&in_path, source).await });
+			},
+			Note::TriggerAsan { in_path } => {
+				let source = source.clone();
+				spawn_in(&root, async move { address_sanitizer(&in_path, source).await });
+			},
+			Note::TriggerValgrind { in_path } => {
+				let source = source.clone();
+				spawn_in(&root, async move { valgrind(&in_path, source).await });
+			},
+			Note::TriggerProfile { in_path } => {
+				let source = source.clone();
+				spawn_in(&root, async move { profile(&in_path, source).await });
+			},
+			Note::NewTest { input, desired } => spawn_in(&root, async move {
+				if !input.is_empty() && !desired.is_empty() {
+					SKILL_ADD.add_use().await;
+				}
+				add_test(&input, &desired).await
+			}),
+			Note::SetAlt { in_path, out } => spawn_in(&root, async move {
+				let in_alt_path = in_path.with_extension("alt.out");
+				fs::write(&in_alt_path, out).await?;
+				COLLECTION.update_all().await?;
+				Ok(())
+			}),
+			Note::DelAlt { in_path } => spawn_in(&root, async move {
+				let in_alt_path = in_path.with_extension("alt.out");
+				fs::remove_file(&in_alt_path).await?;
+				COLLECTION.update_all().await?;
+				Ok(())
+			}),
+			Note::Edit { path } => {
+				if !fs::exists(&path).await? {
+					fs::write(&path, "").await?;
+				}
+				util::open_source(&path).await?;
+			},
+			Note::ActionNotice => SKILL_ACTIONS.add_use().await,
+			Note::EvalReq { id, input } => {
+				if let Ok(brute_force) = dir::brute_force().await {
+					if fs::exists(&brute_force).await? {
+						let webview = webview.clone();
+						spawn_in(&root, async move {
+							let _status = crate::STATUS.push("Evaluating");
+							let brute_force = compile(&SourceTarget::BruteForce, Codegen::Release, false).await?;
+							let environment = Environment { time_limit: time_limit(), cwd: None };
+							let run = brute_force.run(&input, &[], &environment).await?;
+							drop(_status);
+							if run.success() {
+								add_test(&input, &run.stdout).await?;
+								let _ = webview.post_message(Food::EvalResp { id, input }).await;
+								Ok(())
+							} else {
+								Err(E::error("brute force solution did not evaluate test successfully"))
+							}
+						});
 					}
-				},
-			}
+				}
+			},
 		}
-		Ok(())
 	}
+	Ok(())
+}
+
+/// Spawns a future started from the test view. Spawned futures are not polled inside the [`in_task`] of the view, so
+/// they have to be given its directory again.
+fn spawn_in(root: &Path, future: impl Future<Output=R<()>>+'static) {
+	evscode::spawn(in_task(root.clone(), future));
 }
 
 #[derive(Deserialize)]
@@ -110,6 +143,12 @@ enum Note {
 	TriggerRR { in_path: Path },
 	#[serde(rename = "trigger_gdb")]
 	TriggerGDB { in_path: Path },
+	#[serde(rename = "trigger_asan")]
+	TriggerAsan { in_path: Path },
+	#[serde(rename = "trigger_valgrind")]
+	TriggerValgrind { in_path: Path },
+	#[serde(rename = "trigger_profile")]
+	TriggerProfile { in_path: Path },
 	#[serde(rename = "new_test")]
 	NewTest { input: String, desired: String },
 	#[serde(rename = "set_alt")]
@@ -1,7 +1,9 @@
 use crate::{
 	assets, test::{
 		view::{SKILL_ACTIONS, SKILL_ADD}, TestRun, Verdict
-	}, util, util::fs
+	}, util, util::{
+		fmt::{html_escape, html_escape_spaced}, fs
+	}
 };
 use evscode::R;
 use std::cmp::max;
@@ -26,6 +28,9 @@ const ACTION_COPY: Action = Action { onclick: "action_copy()", icon: "file_copy"
 const ACTION_EDIT: Action = Action { onclick: "action_edit()", icon: "edit", hint: "Edit" };
 const ACTION_GDB: Action = Action { onclick: "action_gdb()", icon: "skip_previous", hint: "Debug in GDB" };
 const ACTION_RR: Action = Action { onclick: "action_rr()", icon: "fast_rewind", hint: "Debug in RR" };
+const ACTION_ASAN: Action = Action { onclick: "action_asan()", icon: "memory", hint: "Rerun with AddressSanitizer" };
+const ACTION_PROFILE: Action = Action { onclick: "action_profile()", icon: "timer", hint: "Profile" };
+const ACTION_VALGRIND: Action = Action { onclick: "action_valgrind()", icon: "bug_report", hint: "Run in Valgrind" };
 const ACTION_SET_ALT: Action = Action { onclick: "action_setalt()", icon: "check", hint: "Mark as correct" };
 const ACTION_DEL_ALT: Action = Action { onclick: "action_delalt()", icon: "close", hint: "Unmark as correct" };
 
@@ -150,6 +155,9 @@ async fn render_out_cell(test: &TestRun, folded: bool) -> R<String> {
 		(test.outcome.verdict == Verdict::Accepted { alternative: true }, ACTION_DEL_ALT),
 		(true, ACTION_GDB),
 		(true, ACTION_RR),
+		(!test.success(), ACTION_ASAN),
+		(!test.success(), ACTION_VALGRIND),
+		(true, ACTION_PROFILE),
 	];
 	Ok(render_cell(
 		"output",
@@ -301,22 +309,3 @@ impl HideBehaviour {
 fn count_lines(s: &str) -> usize {
 	if !s.trim().is_empty() { s.trim().matches('\n').count() + 1 } else { 0 }
 }
-
-fn html_escape(s: &str) -> String {
-	translate(s, &[('&', "&amp;"), ('<', "&lt;"), ('>', "&gt;"), ('"', "&quot;"), ('\'', "&#39;")])
-}
-
-fn html_escape_spaced(s: &str) -> String {
-	translate(s, &[('&', "&amp;"), ('<', "&lt;"), ('>', "&gt;"), ('"', "&quot;"), ('\'', "&#39;"), ('\n', "<br/>")])
-}
-
-fn translate(s: &str, table: &[(char, &str)]) -> String {
-	let mut buf = String::new();
-	for c in s.chars() {
-		match table.iter().find(|rule| rule.0 == c) {
-			Some(rule) => buf += rule.1,
-			_ => buf.push(c),
-		}
-	}
-	buf
-}
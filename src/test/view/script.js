@@ -30,6 +30,9 @@ action_copy = make_action(ev => {
 });
 action_rr = make_action(ev => vscode.postMessage({ tag: "trigger_rr", in_path: ev.path_in }));
 action_gdb = make_action(ev => vscode.postMessage({ tag: "trigger_gdb", in_path: ev.path_in }));
+action_asan = make_action(ev => vscode.postMessage({ tag: "trigger_asan", in_path: ev.path_in }));
+action_valgrind = make_action(ev => vscode.postMessage({ tag: "trigger_valgrind", in_path: ev.path_in }));
+action_profile = make_action(ev => vscode.postMessage({ tag: "trigger_profile", in_path: ev.path_in }));
 action_setalt = make_action(ev => vscode.postMessage({ tag: "set_alt", in_path: ev.path_in, out: ev.row.dataset['raw_out'] }));
 action_delalt = make_action(ev => vscode.postMessage({ tag: "del_alt", in_path: ev.path_in }));
 action_edit = make_action(ev => {
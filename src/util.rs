@@ -1,8 +1,8 @@
 use crate::{dir, util::path::Path};
 use evscode::{error::ResultExt, Position, E, R};
-use futures::{channel::oneshot, future::join_all};
+use futures::{channel::oneshot, stream, StreamExt};
 use std::{
-	future::Future, sync::Arc, time::{Duration, SystemTime}
+	cell::RefCell, future::Future, pin::Pin, sync::Arc, task::{Context, Poll}, time::{Duration, SystemTime}
 };
 pub use tempfile::Tempfile;
 use wasm_bindgen::{closure::Closure, JsValue};
@@ -16,7 +16,7 @@ pub mod tempfile;
 
 pub async fn active_tab() -> R<SourceTarget> {
 	let source = Path::from_native(evscode::active_editor_file().await.ok_or_else(E::cancel)?);
-	Ok(if source != crate::dir::solution()? { SourceTarget::Custom(source) } else { SourceTarget::Main })
+	Ok(if source != crate::dir::solution().await? { SourceTarget::Custom(source) } else { SourceTarget::Main })
 }
 
 pub fn bash_escape(raw: &str) -> String {
@@ -57,31 +57,20 @@ pub fn env(key: &'static str) -> R<String> {
 		.unwrap())
 }
 
-pub fn mex(x0: i64, mut xs: Vec<i64>) -> i64 {
-	xs.sort_unstable();
-	xs.dedup();
-	for (i, x) in xs.iter().enumerate() {
-		if x0 + i as i64 != *x {
-			return x0 + i as i64;
-		}
-	}
-	x0 + xs.len() as i64
-}
-
-#[test]
-fn test_mex() {
-	assert_eq!(mex(0, vec![5, 3, 2, 0, 1]), 4);
-	assert_eq!(mex(0, vec![]), 0);
-	assert_eq!(mex(5, vec![10, 5, 7, 9, 8]), 6);
-	assert_eq!(mex(5, vec![]), 5);
-}
-
 pub fn time_now() -> SystemTime {
 	SystemTime::UNIX_EPOCH + Duration::from_millis(js_sys::Date::now() as u64)
 }
 
 pub async fn open_source(path: &Path) -> R<()> {
-	let cursor = find_cursor_place(path).await;
+	open_source_at(path, None).await
+}
+
+/// Opens a source file with the cursor at a given line and column, or at the guessed place for new code if not given.
+pub async fn open_source_at(path: &Path, cursor: Option<(usize, usize)>) -> R<()> {
+	let cursor = match cursor {
+		Some((line, column)) => Some(Position { line, column }),
+		None => find_cursor_place(path).await,
+	};
 	evscode::open_editor(path).cursor(cursor).open().await
 }
 
@@ -126,11 +115,11 @@ pub enum SourceTarget {
 }
 
 impl SourceTarget {
-	pub fn to_path(&self) -> R<Path> {
+	pub async fn to_path(&self) -> R<Path> {
 		match self {
-			SourceTarget::Main => dir::solution(),
-			SourceTarget::BruteForce => dir::brute_force(),
-			SourceTarget::TestGenerator => dir::test_generator(),
+			SourceTarget::Main => dir::solution().await,
+			SourceTarget::BruteForce => dir::brute_force().await,
+			SourceTarget::TestGenerator => dir::test_generator().await,
 			SourceTarget::Custom(source) => Ok(source.clone()),
 		}
 	}
@@ -165,9 +154,47 @@ impl OS {
 	}
 }
 
+thread_local! {
+	static TASK_ROOT: RefCell<Option<Path>> = RefCell::new(None);
+}
+
+/// Returns the directory of the current task. This is the directory given to [`in_task`] if called inside it, or the
+/// folder of the file in the active editor. When a contest is open as a multi-root workspace and no editor is active,
+/// the task can not be determined and an error is returned.
 pub fn workspace_root() -> R<Path> {
-	let buf = evscode::workspace_root().map_err(suggest_open)?;
-	Ok(Path::from_native(buf))
+	if let Some(root) = TASK_ROOT.with(|root| root.borrow().clone()) {
+		return Ok(root);
+	}
+	if let Some(folder) = evscode::active_workspace_folder() {
+		return Ok(Path::from_native(folder));
+	}
+	if evscode::workspace_folders().len() > 1 {
+		return Err(E::error("several tasks are open, open a file of one of them in the editor to choose the task"));
+	}
+	Ok(Path::from_native(evscode::workspace_root().map_err(suggest_open)?))
+}
+
+/// Runs the future with [`workspace_root`] returning the given task directory. Actions that do not start from an
+/// editor, like the ones triggered from the test view, use this to tell which task they belong to.
+pub async fn in_task<T>(root: Path, future: impl Future<Output=T>) -> T {
+	InTask { root, future: Box::pin(future) }.await
+}
+
+struct InTask<F> {
+	root: Path,
+	future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for InTask<F> {
+	type Output = F::Output;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<F::Output> {
+		// Other futures can run between the polls, so the directory is only set while this one is being polled.
+		let previous = TASK_ROOT.with(|root| root.replace(Some(self.root.clone())));
+		let result = self.future.as_mut().poll(cx);
+		TASK_ROOT.with(|root| *root.borrow_mut() = previous);
+		result
+	}
 }
 
 pub fn suggest_open(e: E) -> E {
@@ -184,6 +211,20 @@ pub fn join_all_with_progress<I>(
 	title: &str,
 	i: I,
 ) -> impl Future<Output=Vec<<<I as IntoIterator>::Item as Future>::Output>>
+where
+	I: IntoIterator,
+	<I as IntoIterator>::Item: Future,
+{
+	join_all_with_progress_bounded(title, usize::MAX, i)
+}
+
+/// Like [`join_all_with_progress`], but runs at most `limit` futures at the same time, starting the next one as soon
+/// as any of the running ones finishes. The results are returned in the original order.
+pub fn join_all_with_progress_bounded<I>(
+	title: &str,
+	limit: usize,
+	i: I,
+) -> impl Future<Output=Vec<<<I as IntoIterator>::Item as Future>::Output>>
 where
 	I: IntoIterator,
 	<I as IntoIterator>::Item: Future,
@@ -192,12 +233,19 @@ where
 	let progress = Arc::new(progress);
 	let objects: Vec<_> = i.into_iter().collect();
 	let increment = 100. / (objects.len() as f64);
-	join_all(objects.into_iter().map(move |fut| {
+	let results = stream::iter(objects.into_iter().enumerate().map(move |(index, fut)| {
 		let progress = Arc::clone(&progress);
 		async move {
 			let result = fut.await;
 			progress.increment(increment);
-			result
+			(index, result)
 		}
 	}))
+	.buffer_unordered(limit)
+	.collect::<Vec<_>>();
+	async move {
+		let mut results = results.await;
+		results.sort_by_key(|(index, _)| *index);
+		results.into_iter().map(|(_, result)| result).collect()
+	}
 }
@@ -51,3 +51,22 @@ pub fn list(xs: &[&str]) -> String {
 		[head @ .., tail] => format!("{} and {}", head.join(", "), tail),
 	}
 }
+
+pub fn html_escape(s: &str) -> String {
+	translate(s, &[('&', "&amp;"), ('<', "&lt;"), ('>', "&gt;"), ('"', "&quot;"), ('\'', "&#39;")])
+}
+
+pub fn html_escape_spaced(s: &str) -> String {
+	translate(s, &[('&', "&amp;"), ('<', "&lt;"), ('>', "&gt;"), ('"', "&quot;"), ('\'', "&#39;"), ('\n', "<br/>")])
+}
+
+fn translate(s: &str, table: &[(char, &str)]) -> String {
+	let mut buf = String::new();
+	for c in s.chars() {
+		match table.iter().find(|rule| rule.0 == c) {
+			Some(rule) => buf += rule.1,
+			_ => buf.push(c),
+		}
+	}
+	buf
+}
@@ -49,6 +49,15 @@ pub async fn remove_file(path: &Path) -> R<()> {
 	Ok(())
 }
 
+/// Moves the file to a new path, replacing the file that was there before. The replacement is atomic, so other
+/// processes see either the old or the new file, but never a partially written one.
+pub async fn rename(old_path: &Path, new_path: &Path) -> R<()> {
+	let (tx, rx) = make_callback1();
+	node_sys::fs::rename(old_path.as_str(), new_path.as_str(), tx);
+	rx.await?;
+	Ok(())
+}
+
 pub fn remove_file_sync(path: &Path) -> R<()> {
 	node_sys::fs::unlink_sync(path.as_str());
 	Ok(())
@@ -83,6 +92,7 @@ pub async fn exists(path: &Path) -> R<bool> {
 
 pub struct Metadata {
 	pub modified: SystemTime,
+	pub is_directory: bool,
 }
 
 pub async fn metadata(path: &Path) -> R<Metadata> {
@@ -94,7 +104,13 @@ pub async fn metadata(path: &Path) -> R<Metadata> {
 		.as_f64()
 		.unwrap();
 	let modified = SystemTime::UNIX_EPOCH + Duration::from_millis(mtime_ms as u64);
-	Ok(Metadata { modified })
+	let is_directory = js_sys::Reflect::get(&stat, &JsValue::from_str("isDirectory"))
+		.map_err(|_| E::error("javascript file stats object has no directory check"))?
+		.unchecked_into::<js_sys::Function>()
+		.call0(&stat)
+		.map_err(|_| E::error("could not check whether a file is a directory"))?
+		.is_truthy();
+	Ok(Metadata { modified, is_directory })
 }
 
 fn make_callback1() -> (JsValue, impl Future<Output=Result<(), js_sys::Error>>) {
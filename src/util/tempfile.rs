@@ -19,6 +19,11 @@ impl Tempfile {
 	}
 }
 
+/// Checks whether the file is in the system temporary directory, where [`Tempfile`] creates files.
+pub fn is_temporary(path: &Path) -> bool {
+	path.as_str().starts_with(node_sys::os::tmpdir().as_str())
+}
+
 impl Drop for Tempfile {
 	fn drop(&mut self) {
 		fs::remove_file_sync(&self.path).unwrap();
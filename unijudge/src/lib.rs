@@ -25,13 +25,13 @@ use serde::{Deserialize, Serialize};
 use std::{fmt, fmt::Debug};
 use url::Url;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Example {
 	pub input: String,
 	pub output: String,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Statement {
 	HTML {
 		html: String,
@@ -134,6 +134,33 @@ extern "C" {
 	#[wasm_bindgen(static_method_of = Uri)]
 	pub fn parse(path: &str, strict: bool) -> Uri;
 
+	pub type Diagnostic;
+
+	#[wasm_bindgen(constructor)]
+	pub fn new(range: &Range, message: &str, severity: DiagnosticSeverity) -> Diagnostic;
+
+	#[wasm_bindgen(method, setter, js_name = relatedInformation)]
+	pub fn set_related_information(this: &Diagnostic, related_information: js_sys::Array);
+
+	#[wasm_bindgen(method, setter)]
+	pub fn set_source(this: &Diagnostic, source: &str);
+
+	pub type DiagnosticCollection;
+
+	#[wasm_bindgen(method)]
+	pub fn clear(this: &DiagnosticCollection);
+
+	#[wasm_bindgen(method)]
+	pub fn delete(this: &DiagnosticCollection, uri: &Uri);
+
+	#[wasm_bindgen(method)]
+	pub fn set(this: &DiagnosticCollection, uri: &Uri, diagnostics: js_sys::Array);
+
+	pub type DiagnosticRelatedInformation;
+
+	#[wasm_bindgen(constructor)]
+	pub fn new(location: &Location, message: &str) -> DiagnosticRelatedInformation;
+
 	pub type ExtensionContext;
 
 	#[wasm_bindgen(method, getter, js_name = extensionPath)]
@@ -142,6 +169,9 @@ extern "C" {
 	#[wasm_bindgen(method, getter, js_name = globalState)]
 	pub fn global_state(this: &ExtensionContext) -> Memento;
 
+	#[wasm_bindgen(method, getter, js_name = globalStoragePath)]
+	pub fn get_global_storage_path(this: &ExtensionContext) -> String;
+
 	pub type OutputChannel;
 
 	#[wasm_bindgen(method)]
@@ -194,6 +224,9 @@ extern "C" {
 	#[wasm_bindgen(method, getter, js_name = fileName)]
 	pub fn file_name(this: &TextDocument) -> String;
 
+	#[wasm_bindgen(method, getter)]
+	pub fn uri(this: &TextDocument) -> Uri;
+
 	#[wasm_bindgen(method, js_name = getText)]
 	pub fn text(this: &TextDocument) -> String;
 
@@ -208,14 +241,25 @@ extern "C" {
 	#[wasm_bindgen(method, js_name = revealRange)]
 	pub fn reveal_range(this: &TextEditor, range: &Range, reveal_type: TextEditorRevealType);
 
+	#[wasm_bindgen(method, js_name = setDecorations)]
+	pub fn set_decorations(this: &TextEditor, decoration_type: &TextEditorDecorationType, options: js_sys::Array);
+
 	#[wasm_bindgen(method, setter)]
 	pub fn set_selection(this: &TextEditor, selection: Selection);
 
+	pub type TextEditorDecorationType;
+
+	#[wasm_bindgen(method)]
+	pub fn dispose(this: &TextEditorDecorationType);
+
 	pub type TextEditorEdit;
 
 	#[wasm_bindgen(method)]
 	pub fn insert(this: &TextEditorEdit, location: &Position, value: &str);
 
+	#[wasm_bindgen(method)]
+	pub fn replace(this: &TextEditorEdit, location: &Range, value: &str);
+
 	pub type Position;
 
 	#[wasm_bindgen(constructor)]
@@ -244,6 +288,11 @@ extern "C" {
 	#[wasm_bindgen(method)]
 	pub fn show(this: &Terminal, preserve_focus: Option<bool>);
 
+	pub type Location;
+
+	#[wasm_bindgen(constructor)]
+	pub fn new(uri: &Uri, range: &Range) -> Location;
+
 	pub type Memento;
 
 	#[wasm_bindgen(method)]
@@ -289,6 +338,11 @@ extern "C" {
 		configuration_target: ConfigurationTarget,
 	) -> Thenable<()>;
 
+	pub type WorkspaceFolder;
+
+	#[wasm_bindgen(method, getter)]
+	pub fn uri(this: &WorkspaceFolder) -> Uri;
+
 }
 
 #[derive(Deserialize)]
@@ -305,6 +359,15 @@ pub enum TextEditorRevealType {
 }
 wasm_abi_enumi32!(TextEditorRevealType);
 
+#[repr(i32)]
+pub enum DiagnosticSeverity {
+	Error = 0,
+	Warning = 1,
+	Information = 2,
+	Hint = 3,
+}
+wasm_abi_enumi32!(DiagnosticSeverity);
+
 #[repr(i32)]
 pub enum ConfigurationTarget {
 	Global = 1,
@@ -336,6 +399,23 @@ pub mod commands {
 	}
 }
 
+pub mod debug {
+
+	use crate::Thenable;
+	use wasm_bindgen::prelude::*;
+
+	#[wasm_bindgen(module = vscode)]
+	extern "C" {
+
+		#[wasm_bindgen(js_namespace = debug, js_name = startDebugging)]
+		pub fn start_debugging(
+			folder: JsValue,
+			name_or_configuration: JsValue,
+		) -> Thenable<Result<bool, js_sys::Error>>;
+
+	}
+}
+
 pub mod env {
 
 	use crate::{Clipboard, Thenable, Uri};
@@ -353,9 +433,25 @@ pub mod env {
 	}
 }
 
+pub mod languages {
+
+	use crate::DiagnosticCollection;
+	use wasm_bindgen::prelude::*;
+
+	#[wasm_bindgen(module = vscode)]
+	extern "C" {
+
+		#[wasm_bindgen(js_namespace = languages, js_name = createDiagnosticCollection)]
+		pub fn create_diagnostic_collection(name: &str) -> DiagnosticCollection;
+
+	}
+}
+
 pub mod window {
 
-	use crate::{OutputChannel, StatusBarItem, Terminal, TextDocument, TextEditor, Thenable, Uri, WebviewPanel};
+	use crate::{
+		OutputChannel, StatusBarItem, Terminal, TextDocument, TextEditor, TextEditorDecorationType, Thenable, Uri, WebviewPanel
+	};
 	use serde::{Serialize, Serializer};
 	use std::collections::HashMap;
 	use wasm_bindgen::prelude::*;
@@ -366,6 +462,10 @@ pub mod window {
 		#[wasm_bindgen(js_namespace = window, js_name = activeTextEditor)]
 		pub static ACTIVE_TEXT_EDITOR: Option<TextEditor>;
 
+		/// The namespace object, for reading properties that change over time, because statics are only read once.
+		#[wasm_bindgen(js_name = window)]
+		pub static NAMESPACE: JsValue;
+
 		#[wasm_bindgen(js_namespace = window, js_name = createOutputChannel)]
 		pub fn create_output_channel(name: &str) -> OutputChannel;
 
@@ -375,6 +475,9 @@ pub mod window {
 		#[wasm_bindgen(js_namespace = window, js_name = createTerminal)]
 		pub fn create_terminal(options: TerminalOptions) -> Terminal;
 
+		#[wasm_bindgen(js_namespace = window, js_name = createTextEditorDecorationType)]
+		pub fn create_text_editor_decoration_type(options: DecorationRenderOptions) -> TextEditorDecorationType;
+
 		#[wasm_bindgen(js_namespace = window, js_name = createWebviewPanel)]
 		pub fn create_webview_panel(
 			view_type: &str,
@@ -398,6 +501,9 @@ pub mod window {
 		#[wasm_bindgen(js_namespace = window, js_name = showQuickPick)]
 		pub fn show_quick_pick(items: &js_sys::Array, options: ShowQuickPickOptions) -> Thenable<JsValue>;
 
+		#[wasm_bindgen(js_namespace = window, js_name = showQuickPick)]
+		pub fn show_quick_pick_with_callbacks(items: &js_sys::Array, options: &JsValue) -> Thenable<JsValue>;
+
 		#[wasm_bindgen(js_namespace = window, js_name = showTextDocument)]
 		pub fn show_text_document(document: &TextDocument) -> Thenable<TextEditor>;
 
@@ -421,6 +527,21 @@ pub mod window {
 	}
 	wasm_abi_serde!(CreateWebviewPanelOptions);
 
+	#[derive(Serialize)]
+	pub struct DecorationInstanceRenderOptions<'a> {
+		pub after: ThemableDecorationAttachmentRenderOptions<'a>,
+	}
+
+	#[derive(Serialize)]
+	pub struct DecorationRenderOptions<'a> {
+		pub after: ThemableDecorationAttachmentRenderOptions<'a>,
+		#[serde(rename = "backgroundColor", skip_serializing_if = "Option::is_none")]
+		pub background_color: Option<&'a str>,
+		#[serde(rename = "isWholeLine")]
+		pub is_whole_line: bool,
+	}
+	wasm_abi_serde!(DecorationRenderOptions<'_>);
+
 	#[derive(Serialize)]
 	pub struct InputBoxOptions<'a> {
 		#[serde(rename = "ignoreFocusOut")]
@@ -487,6 +608,16 @@ pub mod window {
 	}
 	wasm_abi_serde!(TerminalOptions<'_>);
 
+	#[derive(Serialize)]
+	pub struct ThemableDecorationAttachmentRenderOptions<'a> {
+		#[serde(skip_serializing_if = "Option::is_none")]
+		pub color: Option<&'a str>,
+		#[serde(rename = "contentText", skip_serializing_if = "Option::is_none")]
+		pub content_text: Option<&'a str>,
+		#[serde(skip_serializing_if = "Option::is_none")]
+		pub margin: Option<&'a str>,
+	}
+
 	#[derive(Serialize)]
 	pub struct WebviewPanelOptions {
 		#[serde(rename = "enableFindWidget")]
@@ -555,7 +686,8 @@ pub mod window {
 
 pub mod workspace {
 
-	use crate::{TextDocument, Thenable, Uri, WorkspaceConfiguration};
+	use crate::{TextDocument, Thenable, Uri, WorkspaceConfiguration, WorkspaceFolder};
+	use serde::Serialize;
 	use wasm_bindgen::prelude::*;
 
 	#[wasm_bindgen(module = vscode)]
@@ -567,14 +699,36 @@ pub mod workspace {
 		#[wasm_bindgen(js_namespace = workspace, js_name = getConfiguration)]
 		pub fn get_configuration(section: &str) -> WorkspaceConfiguration;
 
+		#[wasm_bindgen(js_namespace = workspace, js_name = getWorkspaceFolder)]
+		pub fn get_workspace_folder(uri: &Uri) -> Option<WorkspaceFolder>;
+
+		/// The namespace object, for reading properties that change over time, because statics are only read once.
+		#[wasm_bindgen(js_name = workspace)]
+		pub static NAMESPACE: JsValue;
+
 		#[wasm_bindgen(js_namespace = workspace, js_name = openTextDocument)]
 		pub fn open_text_document(file_name: &str) -> Thenable<Result<TextDocument, js_sys::Error>>;
 
+		#[wasm_bindgen(js_namespace = workspace, js_name = openTextDocument)]
+		pub fn open_untitled_text_document(
+			options: OpenTextDocumentOptions,
+		) -> Thenable<Result<TextDocument, js_sys::Error>>;
+
 		#[wasm_bindgen(js_namespace = workspace, js_name = rootPath)]
 		pub static ROOT_PATH: JsValue;
 
 		#[wasm_bindgen(js_namespace = workspace, js_name = saveAll)]
 		pub fn save_all(include_untitled: bool) -> Thenable<bool>;
 
+		#[wasm_bindgen(js_namespace = workspace, js_name = updateWorkspaceFolders, variadic)]
+		pub fn update_workspace_folders(start: f64, delete_count: &JsValue, folders: js_sys::Array) -> bool;
+
+	}
+
+	#[derive(Serialize)]
+	pub struct OpenTextDocumentOptions<'a> {
+		pub content: &'a str,
+		pub language: &'a str,
 	}
+	wasm_abi_serde!(OpenTextDocumentOptions<'_>);
 }